@@ -15,7 +15,6 @@ use crate::{connect_peer, ClientError, Network, Peer};
 
 #[derive(Clone)]
 pub struct Client {
-    network_id: String,
     network: Network,
     connector: Connector,
     state: Arc<Mutex<ClientState>>,
@@ -25,7 +24,6 @@ pub struct Client {
 impl fmt::Debug for Client {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Client")
-            .field("network_id", &self.network_id)
             .field("network", &self.network)
             .finish()
     }
@@ -47,9 +45,16 @@ pub struct ClientState {
 }
 
 impl Client {
-    pub fn new(network_id: String, network: Network, connector: Connector) -> Self {
+    /// Creates a client that identifies itself to peers with the given network id.
+    /// The id replaces [`Network::network_id`], so that existing callers keep their behavior.
+    pub fn new(network_id: String, mut network: Network, connector: Connector) -> Self {
+        network.network_id = network_id;
+        Self::with_network(network, connector)
+    }
+
+    /// Creates a client for the given network, using its network id during the handshake.
+    pub fn with_network(network: Network, connector: Connector) -> Self {
         Self {
-            network_id,
             network,
             connector,
             state: Arc::new(Mutex::new(ClientState::default())),
@@ -57,7 +62,7 @@ impl Client {
     }
 
     pub fn network_id(&self) -> &str {
-        &self.network_id
+        &self.network.network_id
     }
 
     pub fn network(&self) -> &Network {
@@ -68,8 +73,12 @@ impl Client {
         &self,
         socket_addr: SocketAddr,
     ) -> Result<mpsc::Receiver<Message>, ClientError> {
-        let (peer, receiver) =
-            connect_peer(self.network_id.clone(), self.connector.clone(), socket_addr).await?;
+        let (peer, receiver) = connect_peer(
+            self.network.network_id.clone(),
+            self.connector.clone(),
+            socket_addr,
+        )
+        .await?;
 
         let mut state = self.state.lock().await;
         let ip_addr = peer.socket_addr().ip();
@@ -57,5 +57,7 @@ pub async fn connect_peer(
         ));
     }
 
+    peer.set_handshake(handshake);
+
     Ok((peer, receiver))
 }
@@ -55,4 +55,10 @@ pub enum ClientError {
 
     #[error("The peer is banned")]
     BannedPeer,
+
+    #[error("The batch size must be greater than zero")]
+    InvalidBatchSize,
+
+    #[error("The peer does not support {0:?} messages with protocol version {1}")]
+    UnsupportedByPeer(ProtocolMessageTypes, String),
 }
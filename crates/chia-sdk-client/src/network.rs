@@ -1,39 +1,87 @@
 use std::{net::SocketAddr, time::Duration};
 
+use chia_consensus::consensus_constants::ConsensusConstants;
 use chia_protocol::Bytes32;
+use chia_sdk_signer::AggSigConstants;
 use chia_sdk_types::{MAINNET_CONSTANTS, TESTNET11_CONSTANTS};
 use futures_util::{stream::FuturesUnordered, StreamExt};
 use tracing::{info, instrument, warn};
 
 use crate::ClientError;
 
+/// The parameters of a Chia network, kept together so that they can't be mismatched.
+///
+/// The signing constants are derived from [`Network::constants`] by [`Network::agg_sig_constants`].
+/// A simulator or fork can be described with [`Network::new`].
 #[derive(Debug, Clone)]
 pub struct Network {
+    /// The network id that peers report during the handshake, such as `mainnet`.
+    pub network_id: String,
+    /// The bech32m prefix used for addresses, such as `xch`.
+    pub address_prefix: String,
     pub default_port: u16,
+    #[deprecated(note = "use `Network::genesis_challenge`, which reads it from the constants")]
     pub genesis_challenge: Bytes32,
+    pub constants: ConsensusConstants,
     pub dns_introducers: Vec<String>,
 }
 
 impl Network {
-    pub fn default_mainnet() -> Self {
+    /// Creates a network from its parameters. The genesis challenge is taken from the constants.
+    pub fn new(
+        network_id: String,
+        address_prefix: String,
+        default_port: u16,
+        constants: ConsensusConstants,
+        dns_introducers: Vec<String>,
+    ) -> Self {
+        #[allow(deprecated)]
         Self {
-            default_port: 8444,
-            genesis_challenge: MAINNET_CONSTANTS.genesis_challenge,
-            dns_introducers: vec![
+            network_id,
+            address_prefix,
+            default_port,
+            genesis_challenge: constants.genesis_challenge,
+            constants,
+            dns_introducers,
+        }
+    }
+
+    pub fn default_mainnet() -> Self {
+        Self::new(
+            "mainnet".to_string(),
+            "xch".to_string(),
+            8444,
+            MAINNET_CONSTANTS.clone(),
+            vec![
                 "dns-introducer.chia.net".to_string(),
                 "chia.ctrlaltdel.ch".to_string(),
                 "seeder.dexie.space".to_string(),
                 "chia.hoffmang.com".to_string(),
             ],
-        }
+        )
     }
 
     pub fn default_testnet11() -> Self {
-        Self {
-            default_port: 58444,
-            genesis_challenge: TESTNET11_CONSTANTS.genesis_challenge,
-            dns_introducers: vec!["dns-introducer-testnet11.chia.net".to_string()],
-        }
+        Self::new(
+            "testnet11".to_string(),
+            "txch".to_string(),
+            58444,
+            TESTNET11_CONSTANTS.clone(),
+            vec!["dns-introducer-testnet11.chia.net".to_string()],
+        )
+    }
+
+    pub fn genesis_challenge(&self) -> Bytes32 {
+        self.constants.genesis_challenge
+    }
+
+    pub fn agg_sig_me_additional_data(&self) -> Bytes32 {
+        self.constants.agg_sig_me_additional_data
+    }
+
+    /// The constants needed to calculate the signatures required by spends on this network.
+    pub fn agg_sig_constants(&self) -> AggSigConstants {
+        AggSigConstants::from(&self.constants)
     }
 
     #[instrument]
@@ -77,3 +125,9 @@ impl Network {
         Ok(result)
     }
 }
+
+impl From<&Network> for AggSigConstants {
+    fn from(network: &Network) -> Self {
+        network.agg_sig_constants()
+    }
+}
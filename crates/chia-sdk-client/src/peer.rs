@@ -1,18 +1,21 @@
-use std::{net::SocketAddr, sync::Arc};
+use std::{
+    net::SocketAddr,
+    sync::{Arc, OnceLock},
+};
 
 use chia_protocol::{
-    Bytes32, ChiaProtocolMessage, CoinStateFilters, Message, PuzzleSolutionResponse,
-    RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState, RejectPuzzleSolution,
-    RejectPuzzleState, RequestChildren, RequestCoinState, RequestPeers, RequestPuzzleSolution,
-    RequestPuzzleState, RequestRemoveCoinSubscriptions, RequestRemovePuzzleSubscriptions,
-    RequestTransaction, RespondChildren, RespondCoinState, RespondPeers, RespondPuzzleSolution,
-    RespondPuzzleState, RespondRemoveCoinSubscriptions, RespondRemovePuzzleSubscriptions,
-    RespondToCoinUpdates, RespondToPhUpdates, RespondTransaction, SendTransaction, SpendBundle,
-    TransactionAck,
+    Bytes32, ChiaProtocolMessage, CoinStateFilters, Handshake, Message, ProtocolMessageTypes,
+    PuzzleSolutionResponse, RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState,
+    RejectPuzzleSolution, RejectPuzzleState, RequestChildren, RequestCoinState, RequestPeers,
+    RequestPuzzleSolution, RequestPuzzleState, RequestRemoveCoinSubscriptions,
+    RequestRemovePuzzleSubscriptions, RequestTransaction, RespondChildren, RespondCoinState,
+    RespondPeers, RespondPuzzleSolution, RespondPuzzleState, RespondRemoveCoinSubscriptions,
+    RespondRemovePuzzleSubscriptions, RespondToCoinUpdates, RespondToPhUpdates, RespondTransaction,
+    SendTransaction, SpendBundle, TransactionAck,
 };
 use chia_traits::Streamable;
 use futures_util::{
-    stream::{SplitSink, SplitStream},
+    stream::{FuturesOrdered, SplitSink, SplitStream},
     SinkExt, StreamExt,
 };
 use tokio::{
@@ -21,7 +24,7 @@ use tokio::{
     task::JoinHandle,
 };
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
-use tracing::{debug, warn};
+use tracing::{debug, instrument, warn};
 
 use crate::{request_map::RequestMap, ClientError};
 
@@ -33,6 +36,13 @@ type Sink = SplitSink<WebSocket, tungstenite::Message>;
 type Stream = SplitStream<WebSocket>;
 type Response<T, E> = std::result::Result<T, E>;
 
+/// The protocol version that added coin and puzzle state requests, and removing subscriptions.
+///
+/// Full nodes report this version from chia-blockchain 2.4.0, which added these messages.
+/// See `protocol_version` for `NodeType.FULL_NODE` in
+/// [`shared_protocol.py`](https://github.com/Chia-Network/chia-blockchain/blob/2.4.0/chia/protocols/shared_protocol.py).
+pub const WALLET_SYNC_PROTOCOL_VERSION: &str = "0.0.37";
+
 #[derive(Debug, Clone)]
 pub struct Peer(Arc<PeerInner>);
 
@@ -42,6 +52,7 @@ struct PeerInner {
     inbound_handle: JoinHandle<()>,
     requests: Arc<RequestMap>,
     socket_addr: SocketAddr,
+    handshake: OnceLock<Handshake>,
 }
 
 impl Peer {
@@ -103,6 +114,7 @@ impl Peer {
             inbound_handle,
             requests,
             socket_addr,
+            handshake: OnceLock::new(),
         }));
 
         Ok((peer, receiver))
@@ -113,6 +125,66 @@ impl Peer {
         self.0.socket_addr
     }
 
+    /// The handshake that the peer responded with, if the connection was made with `connect_peer`.
+    pub fn handshake(&self) -> Option<&Handshake> {
+        self.0.handshake.get()
+    }
+
+    /// The protocol version of the peer, if its handshake is known.
+    pub fn protocol_version(&self) -> Option<&str> {
+        self.handshake()
+            .map(|handshake| handshake.protocol_version.as_str())
+    }
+
+    /// Whether the peer's handshake enabled the given capability.
+    /// This is always false if the handshake isn't known.
+    pub fn has_capability(&self, capability: u16) -> bool {
+        self.handshake().is_some_and(|handshake| {
+            handshake
+                .capabilities
+                .iter()
+                .any(|(id, value)| *id == capability && value == "1")
+        })
+    }
+
+    /// Whether the peer's protocol version is at least the given version.
+    /// A version that can't be parsed is unsupported. This is always false if the handshake isn't known.
+    pub fn supports_protocol_version(&self, version: &str) -> bool {
+        self.protocol_version()
+            .is_some_and(|found| version_at_least(found, version))
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    pub(crate) fn set_handshake(&self, handshake: Handshake) {
+        self.0.handshake.set(handshake).ok();
+    }
+
+    /// Returns an error rather than sending a message that the peer doesn't understand,
+    /// since old peers don't respond to unknown messages.
+    ///
+    /// If the handshake isn't known, such as when the connection wasn't made with `connect_peer`,
+    /// the message is sent anyway with a warning, since there's no way to check the version.
+    fn require_protocol_version(
+        &self,
+        msg_type: ProtocolMessageTypes,
+        version: &str,
+    ) -> Result<(), ClientError> {
+        let Some(found) = self.protocol_version() else {
+            warn!(
+                "Sending {msg_type:?} to peer {} without a known protocol version",
+                self.socket_addr()
+            );
+            return Ok(());
+        };
+
+        if version_at_least(found, version) {
+            return Ok(());
+        }
+
+        Err(ClientError::UnsupportedByPeer(msg_type, found.to_string()))
+    }
+
+    #[instrument(skip_all, fields(transaction_id = %spend_bundle.name()))]
     pub async fn send_transaction(
         &self,
         spend_bundle: SpendBundle,
@@ -129,6 +201,11 @@ impl Peer {
         filters: CoinStateFilters,
         subscribe_when_finished: bool,
     ) -> Result<Response<RespondPuzzleState, RejectPuzzleState>, ClientError> {
+        self.require_protocol_version(
+            ProtocolMessageTypes::RequestPuzzleState,
+            WALLET_SYNC_PROTOCOL_VERSION,
+        )?;
+
         self.request_fallible(RequestPuzzleState::new(
             puzzle_hashes,
             previous_height,
@@ -146,6 +223,11 @@ impl Peer {
         header_hash: Bytes32,
         subscribe: bool,
     ) -> Result<Response<RespondCoinState, RejectCoinState>, ClientError> {
+        self.require_protocol_version(
+            ProtocolMessageTypes::RequestCoinState,
+            WALLET_SYNC_PROTOCOL_VERSION,
+        )?;
+
         self.request_fallible(RequestCoinState::new(
             coin_ids,
             previous_height,
@@ -173,10 +255,98 @@ impl Peer {
             .await
     }
 
+    /// Subscribes to a large number of puzzle hashes, split into messages of at most `batch_size` each.
+    ///
+    /// Every message is sent up front without waiting for the previous response, and the responses
+    /// are yielded in the same order as the batches as soon as they arrive.
+    ///
+    /// An error is returned if `batch_size` is zero.
+    pub fn register_for_ph_updates_batched(
+        &self,
+        puzzle_hashes: &[Bytes32],
+        min_height: u32,
+        batch_size: usize,
+    ) -> Result<
+        impl futures_util::Stream<Item = Result<RespondToPhUpdates, ClientError>>,
+        ClientError,
+    > {
+        let batches = batches(puzzle_hashes, batch_size)?;
+
+        Ok(batches
+            .into_iter()
+            .map(|batch| {
+                let peer = self.clone();
+                async move { peer.register_for_ph_updates(batch, min_height).await }
+            })
+            .collect::<FuturesOrdered<_>>())
+    }
+
+    /// Subscribes to a large number of coin ids, split into messages of at most `batch_size` each.
+    ///
+    /// The requests are pipelined in the same way as [`Peer::register_for_ph_updates_batched`].
+    ///
+    /// An error is returned if `batch_size` is zero.
+    pub fn register_for_coin_updates_batched(
+        &self,
+        coin_ids: &[Bytes32],
+        min_height: u32,
+        batch_size: usize,
+    ) -> Result<
+        impl futures_util::Stream<Item = Result<RespondToCoinUpdates, ClientError>>,
+        ClientError,
+    > {
+        let batches = batches(coin_ids, batch_size)?;
+
+        Ok(batches
+            .into_iter()
+            .map(|batch| {
+                let peer = self.clone();
+                async move { peer.register_for_coin_updates(batch, min_height).await }
+            })
+            .collect::<FuturesOrdered<_>>())
+    }
+
+    /// Requests the state of a large number of coin ids, split into messages of at most `batch_size` each.
+    ///
+    /// The requests are pipelined in the same way as [`Peer::register_for_ph_updates_batched`].
+    ///
+    /// An error is returned if `batch_size` is zero.
+    pub fn request_coin_state_batched(
+        &self,
+        coin_ids: &[Bytes32],
+        previous_height: Option<u32>,
+        header_hash: Bytes32,
+        subscribe: bool,
+        batch_size: usize,
+    ) -> Result<
+        impl futures_util::Stream<
+            Item = Result<Response<RespondCoinState, RejectCoinState>, ClientError>,
+        >,
+        ClientError,
+    > {
+        let batches = batches(coin_ids, batch_size)?;
+
+        Ok(batches
+            .into_iter()
+            .map(|batch| {
+                let peer = self.clone();
+                async move {
+                    peer.request_coin_state(batch, previous_height, header_hash, subscribe)
+                        .await
+                }
+            })
+            .collect::<FuturesOrdered<_>>())
+    }
+
     pub async fn remove_puzzle_subscriptions(
         &self,
         puzzle_hashes: Option<Vec<Bytes32>>,
     ) -> Result<RespondRemovePuzzleSubscriptions, ClientError> {
+        self.require_protocol_version(
+            ProtocolMessageTypes::RequestRemovePuzzleSubscriptions,
+            WALLET_SYNC_PROTOCOL_VERSION,
+        )?;
+
         self.request_infallible(RequestRemovePuzzleSubscriptions::new(puzzle_hashes))
             .await
     }
@@ -185,6 +355,11 @@ impl Peer {
         &self,
         coin_ids: Option<Vec<Bytes32>>,
     ) -> Result<RespondRemoveCoinSubscriptions, ClientError> {
+        self.require_protocol_version(
+            ProtocolMessageTypes::RequestRemoveCoinSubscriptions,
+            WALLET_SYNC_PROTOCOL_VERSION,
+        )?;
+
         self.request_infallible(RequestRemoveCoinSubscriptions::new(coin_ids))
             .await
     }
@@ -197,6 +372,7 @@ impl Peer {
             .await
     }
 
+    #[instrument(skip(self))]
     pub async fn request_puzzle_and_solution(
         &self,
         coin_id: Bytes32,
@@ -213,6 +389,7 @@ impl Peer {
         }
     }
 
+    #[instrument(skip(self))]
     pub async fn request_children(&self, coin_id: Bytes32) -> Result<RespondChildren, ClientError> {
         self.request_infallible(RequestChildren::new(coin_id)).await
     }
@@ -222,6 +399,7 @@ impl Peer {
     }
 
     /// Sends a message to the peer, but does not expect any response.
+    #[instrument(skip_all, fields(msg_type = ?T::msg_type()))]
     pub async fn send<T>(&self, body: T) -> Result<(), ClientError>
     where
         T: Streamable + ChiaProtocolMessage,
@@ -273,6 +451,7 @@ impl Peer {
     }
 
     /// Sends a message to the peer and expects any arbitrary protocol message without parsing it.
+    #[instrument(skip_all, fields(msg_type = ?T::msg_type()))]
     pub async fn request_raw<T>(&self, body: T) -> Result<Message, ClientError>
     where
         T: Streamable + ChiaProtocolMessage,
@@ -303,6 +482,29 @@ impl Drop for PeerInner {
     }
 }
 
+/// Compares dotted version strings numerically. Versions that can't be parsed are treated as unsupported,
+/// since there's no way to tell whether the peer understands the message.
+fn version_at_least(found: &str, required: &str) -> bool {
+    let parse = |version: &str| {
+        version
+            .split('.')
+            .map(str::parse::<u32>)
+            .collect::<Result<Vec<u32>, _>>()
+    };
+
+    match (parse(found), parse(required)) {
+        (Ok(found), Ok(required)) => found >= required,
+        _ => false,
+    }
+}
+
+fn batches<T: Clone>(items: &[T], batch_size: usize) -> Result<Vec<Vec<T>>, ClientError> {
+    if batch_size == 0 {
+        return Err(ClientError::InvalidBatchSize);
+    }
+    Ok(items.chunks(batch_size).map(<[T]>::to_vec).collect())
+}
+
 async fn handle_inbound_messages(
     mut stream: Stream,
     sender: mpsc::Sender<Message>,
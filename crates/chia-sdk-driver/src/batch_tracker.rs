@@ -0,0 +1,216 @@
+use chia_protocol::Bytes32;
+use chia_sdk_types::{max_spend_bundle_cost, MAINNET_CONSTANTS};
+
+/// An item that can be processed in batches by a [`BatchTracker`].
+pub trait BatchItem {
+    /// An upper bound on the CLVM cost that each item adds to a spend bundle.
+    /// This is checked against the measured cost in tests.
+    const COST_ESTIMATE: u64;
+
+    /// An upper bound on the CLVM cost of a batch that doesn't depend on how many items it has,
+    /// such as spending the coins that fund it. This is left out of the cost limit before it's split into items.
+    const BASE_COST_ESTIMATE: u64 = 0;
+}
+
+/// The progress of an individual item in a [`BatchTracker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchStatus {
+    /// The item hasn't been included in a batch yet.
+    Pending,
+    /// The item has been included in a batch, but it hasn't been confirmed yet.
+    Submitted { coin_id: Bytes32 },
+    /// The coin created for the item has been confirmed on-chain.
+    Confirmed { coin_id: Bytes32 },
+}
+
+/// An item in a [`BatchTracker`], along with its progress.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedItem<T> {
+    pub item: T,
+    pub status: BatchStatus,
+}
+
+/// Splits a list of items into batches that each fit in a single spend bundle,
+/// and tracks the progress of each item until it has been confirmed.
+///
+/// Each item is identified by the id of a coin it creates once it has been submitted,
+/// such as the launcher id of a minted NFT, which is used to confirm it later.
+#[derive(Debug, Clone)]
+pub struct BatchTracker<T> {
+    items: Vec<TrackedItem<T>>,
+    batch_size: usize,
+}
+
+impl<T> BatchTracker<T>
+where
+    T: BatchItem,
+{
+    /// Creates a new tracker where every item is pending.
+    /// By default, the batch size is chosen to fit within the mempool's spend bundle cost limit on mainnet.
+    pub fn new(items: Vec<T>) -> Self {
+        Self {
+            items: items
+                .into_iter()
+                .map(|item| TrackedItem {
+                    item,
+                    status: BatchStatus::Pending,
+                })
+                .collect(),
+            batch_size: Self::batch_size_for_cost(max_spend_bundle_cost(&MAINNET_CONSTANTS)),
+        }
+    }
+
+    /// Sets the batch size such that the estimated cost of each batch is within the given limit.
+    #[must_use]
+    pub fn with_max_cost(self, max_cost: u64) -> Self {
+        self.with_batch_size(Self::batch_size_for_cost(max_cost))
+    }
+
+    /// The number of items that can be included in a batch within the given cost,
+    /// after the base cost of the batch has been subtracted.
+    pub fn batch_size_for_cost(max_cost: u64) -> usize {
+        usize::try_from(max_cost.saturating_sub(T::BASE_COST_ESTIMATE) / T::COST_ESTIMATE)
+            .unwrap_or(usize::MAX)
+            .max(1)
+    }
+}
+
+impl<T> BatchTracker<T> {
+    /// Sets the maximum number of items that will be included in a single batch.
+    #[must_use]
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// The maximum number of items that will be included in a single batch.
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    /// The items, in the order they were given.
+    pub fn items(&self) -> &[TrackedItem<T>] {
+        &self.items
+    }
+
+    /// The number of items that haven't been included in a batch yet.
+    pub fn pending_count(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Pending))
+    }
+
+    /// The number of items that have been submitted but not confirmed yet.
+    pub fn submitted_count(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Submitted { .. }))
+    }
+
+    /// The number of items that have been confirmed on-chain.
+    pub fn confirmed_count(&self) -> usize {
+        self.count(|status| matches!(status, BatchStatus::Confirmed { .. }))
+    }
+
+    /// Whether every item has been confirmed.
+    pub fn is_complete(&self) -> bool {
+        self.confirmed_count() == self.items.len()
+    }
+
+    /// Marks the item that was submitted with the given coin id as confirmed.
+    /// Returns `false` if it wasn't part of a submitted batch.
+    pub fn confirm(&mut self, coin_id: Bytes32) -> bool {
+        for item in &mut self.items {
+            if item.status == (BatchStatus::Submitted { coin_id }) {
+                item.status = BatchStatus::Confirmed { coin_id };
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Moves every submitted but unconfirmed item back to pending, so that it will be included in a later batch.
+    /// This should be used if a batch failed to make it on-chain.
+    pub fn reset_submitted(&mut self) {
+        for item in &mut self.items {
+            if matches!(item.status, BatchStatus::Submitted { .. }) {
+                item.status = BatchStatus::Pending;
+            }
+        }
+    }
+
+    /// The indices of the pending items to include in the next batch, up to the batch size.
+    /// Items for which the filter returns `false` are skipped, and left for a later batch.
+    pub fn next_batch(&self, mut filter: impl FnMut(&T) -> bool) -> Vec<usize> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.status == BatchStatus::Pending)
+            .filter(|(_, item)| filter(&item.item))
+            .map(|(index, _)| index)
+            .take(self.batch_size)
+            .collect()
+    }
+
+    /// Marks the item at the given index as submitted, with the id of the coin that will confirm it.
+    pub fn submit(&mut self, index: usize, coin_id: Bytes32) {
+        self.items[index].status = BatchStatus::Submitted { coin_id };
+    }
+
+    fn count(&self, f: impl Fn(&BatchStatus) -> bool) -> usize {
+        self.items.iter().filter(|item| f(&item.status)).count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct Item(u8);
+
+    impl BatchItem for Item {
+        const COST_ESTIMATE: u64 = 1_000;
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    struct FundedItem;
+
+    impl BatchItem for FundedItem {
+        const COST_ESTIMATE: u64 = 1_000;
+        const BASE_COST_ESTIMATE: u64 = 5_000;
+    }
+
+    #[test]
+    fn test_batch_size_for_cost() {
+        assert_eq!(BatchTracker::<Item>::batch_size_for_cost(0), 1);
+        assert_eq!(BatchTracker::<Item>::batch_size_for_cost(10_500), 10);
+        assert_eq!(BatchTracker::<FundedItem>::batch_size_for_cost(10_500), 5);
+        assert_eq!(BatchTracker::<FundedItem>::batch_size_for_cost(4_000), 1);
+        assert_eq!(
+            BatchTracker::new(vec![Item(0)]).batch_size(),
+            usize::try_from(max_spend_bundle_cost(&MAINNET_CONSTANTS) / 1_000).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_batch_tracker() {
+        let mut tracker = BatchTracker::new((0..5).map(Item).collect()).with_batch_size(2);
+
+        let batch = tracker.next_batch(|item| item.0 != 0);
+        assert_eq!(batch, vec![1, 2]);
+
+        for index in batch {
+            tracker.submit(index, Bytes32::new([tracker.items()[index].item.0; 32]));
+        }
+
+        assert_eq!(tracker.pending_count(), 3);
+        assert_eq!(tracker.submitted_count(), 2);
+
+        assert!(tracker.confirm(Bytes32::new([1; 32])));
+        assert!(!tracker.confirm(Bytes32::new([1; 32])));
+        assert!(!tracker.confirm(Bytes32::new([3; 32])));
+
+        tracker.reset_submitted();
+        assert_eq!(tracker.pending_count(), 4);
+        assert_eq!(tracker.confirmed_count(), 1);
+        assert_eq!(tracker.next_batch(|_| true), vec![0, 2]);
+        assert!(!tracker.is_complete());
+    }
+}
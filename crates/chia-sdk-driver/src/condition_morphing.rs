@@ -0,0 +1,218 @@
+use chia_protocol::Bytes32;
+use chia_puzzles::{cat::CatArgs, singleton::SingletonArgs};
+use chia_sdk_types::{announcement_id, Condition, Conditions, CreateCoin};
+
+/// Computes the conditions that the CAT layer outputs for the given inner puzzle conditions.
+///
+/// Each `CREATE_COIN` puzzle hash is wrapped in the CAT layer with the same asset id.
+/// Other conditions are passed through unchanged. The ring announcements and assertions
+/// that the CAT layer adds on top of these aren't included.
+pub fn morph_cat_conditions<T>(asset_id: Bytes32, conditions: Conditions<T>) -> Conditions<T> {
+    Conditions::default().extend(conditions.into_iter().map(|condition| match condition {
+        Condition::CreateCoin(create_coin) => Condition::CreateCoin(CreateCoin {
+            puzzle_hash: CatArgs::curry_tree_hash(asset_id, create_coin.puzzle_hash.into()).into(),
+            ..create_coin
+        }),
+        condition => condition,
+    }))
+}
+
+/// Computes the conditions that the singleton layer outputs for the given inner puzzle conditions.
+///
+/// The `CREATE_COIN` with an odd amount (the singleton's child) is wrapped in the singleton layer
+/// with the same launcher id. Other conditions, including even amount outputs, are passed through unchanged.
+pub fn morph_singleton_conditions<T>(
+    launcher_id: Bytes32,
+    conditions: Conditions<T>,
+) -> Conditions<T> {
+    Conditions::default().extend(conditions.into_iter().map(|condition| {
+        match condition {
+            Condition::CreateCoin(create_coin) if create_coin.amount % 2 == 1 => {
+                Condition::CreateCoin(CreateCoin {
+                    puzzle_hash: SingletonArgs::curry_tree_hash(
+                        launcher_id,
+                        create_coin.puzzle_hash.into(),
+                    )
+                    .into(),
+                    ..create_coin
+                })
+            }
+            condition => condition,
+        }
+    }))
+}
+
+/// Computes the ids of the coin announcements created by a coin with the given conditions.
+/// These are the values that another spend would assert with `ASSERT_COIN_ANNOUNCEMENT`.
+pub fn coin_announcement_ids<T>(coin_id: Bytes32, conditions: &Conditions<T>) -> Vec<Bytes32> {
+    conditions
+        .as_ref()
+        .iter()
+        .filter_map(|condition| match condition {
+            Condition::CreateCoinAnnouncement(announcement) => {
+                Some(announcement_id(coin_id, announcement.message.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+/// Computes the ids of the puzzle announcements created by a coin with the given conditions.
+/// These are the values that another spend would assert with `ASSERT_PUZZLE_ANNOUNCEMENT`.
+///
+/// The puzzle hash must be that of the outer puzzle, since wrapper layers don't change announcements.
+pub fn puzzle_announcement_ids<T>(
+    puzzle_hash: Bytes32,
+    conditions: &Conditions<T>,
+) -> Vec<Bytes32> {
+    conditions
+        .as_ref()
+        .iter()
+        .filter_map(|condition| match condition {
+            Condition::CreatePuzzleAnnouncement(announcement) => {
+                Some(announcement_id(puzzle_hash, announcement.message.clone()))
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::{Bytes, Coin, CoinSpend};
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::run_puzzle;
+    use clvm_traits::{FromClvm, ToClvm};
+    use clvmr::{Allocator, NodePtr};
+
+    use crate::{Cat, CatSpend, Launcher, SpendContext, SpendWithConditions, StandardLayer};
+
+    use super::*;
+
+    fn outer_conditions(coin_spend: &CoinSpend) -> anyhow::Result<Vec<Condition>> {
+        let mut allocator = Allocator::new();
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(&mut allocator)?;
+        let solution = coin_spend.solution.to_clvm(&mut allocator)?;
+        let output = run_puzzle(&mut allocator, puzzle, solution)?;
+        let conditions = Vec::<Condition<NodePtr>>::from_clvm(&allocator, output)?;
+
+        // Compare the conditions by value, since the pointers belong to different allocators.
+        Ok(conditions
+            .into_iter()
+            .filter(|condition| {
+                matches!(
+                    condition,
+                    Condition::CreateCoin(..)
+                        | Condition::CreateCoinAnnouncement(..)
+                        | Condition::CreatePuzzleAnnouncement(..)
+                )
+            })
+            .collect())
+    }
+
+    fn inner_conditions(puzzle_hash: Bytes32) -> Conditions {
+        Conditions::new()
+            .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 2, Vec::new())
+            .create_coin_announcement(Bytes::new(b"coin".to_vec()))
+            .create_puzzle_announcement(Bytes::new(b"puzzle".to_vec()))
+    }
+
+    #[test]
+    fn test_morph_cat_conditions() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(3)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            3,
+            Conditions::new().create_coin(puzzle_hash, 3, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 3);
+        let inner_spend = p2.spend_with_conditions(ctx, inner_conditions(puzzle_hash))?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+
+        let coin_spends = ctx.take();
+        let expected = morph_cat_conditions(cat.asset_id, inner_conditions(puzzle_hash));
+        let actual = outer_conditions(&coin_spends[0])?;
+
+        for condition in expected.as_ref() {
+            assert!(actual.contains(condition), "missing {condition:?}");
+        }
+
+        let coin_ids = coin_announcement_ids(cat.coin.coin_id(), &expected);
+        let puzzle_ids = puzzle_announcement_ids(cat.coin.puzzle_hash, &expected);
+
+        // Another spend in the same bundle can assert the predicted announcements.
+        let asserter = sim.new_coin(puzzle_hash, 0);
+        p2.spend(
+            ctx,
+            asserter,
+            Conditions::new()
+                .assert_coin_announcement(coin_ids[0])
+                .assert_puzzle_announcement(puzzle_ids[0]),
+        )?;
+
+        for coin_spend in coin_spends {
+            ctx.insert(coin_spend);
+        }
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        for condition in expected {
+            if let Condition::CreateCoin(create_coin) = condition {
+                let child = Coin::new(
+                    cat.coin.coin_id(),
+                    create_coin.puzzle_hash,
+                    create_coin.amount,
+                );
+                assert!(sim.coin_state(child.coin_id()).is_some());
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_morph_singleton_conditions() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(3)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(
+            ctx,
+            coin,
+            create_did.create_coin(puzzle_hash, 2, Vec::new()),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The singleton's child is created with the DID inner puzzle hash, and wrapped by the singleton layer.
+        let inner_puzzle_hash = did.info.inner_puzzle_hash().into();
+        let conditions = Conditions::new()
+            .create_coin(inner_puzzle_hash, 1, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 2, Vec::new());
+
+        let expected = morph_singleton_conditions(did.info.launcher_id, conditions);
+        let [Condition::CreateCoin(child), Condition::CreateCoin(even)] = expected.as_ref() else {
+            unreachable!();
+        };
+        assert_eq!(child.puzzle_hash, did.coin.puzzle_hash);
+        assert_eq!(even.puzzle_hash, puzzle_hash);
+
+        let _did = did.update(ctx, &p2, Conditions::new())?;
+        let coin_spends = ctx.take();
+        let actual = outer_conditions(&coin_spends[0])?;
+        assert!(actual.contains(&Condition::CreateCoin(child.clone())));
+
+        sim.spend_coins(coin_spends, &[sk])?;
+
+        Ok(())
+    }
+}
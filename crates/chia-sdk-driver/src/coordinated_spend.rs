@@ -0,0 +1,115 @@
+use chia_protocol::{Bytes, Bytes32};
+use chia_sdk_types::{announcement_id, Conditions};
+use clvmr::sha2::Sha256;
+
+/// Links a group of coin spends together, so that they're only valid if they're all spent in the same bundle.
+///
+/// Each coin creates a coin announcement and asserts the announcement of the next coin in the group,
+/// wrapping around at the end. If any coin is left out, the coin before it can't be spent.
+/// The coins can belong to different primitives, since only the inner conditions are needed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoordinatedSpend {
+    coin_ids: Vec<Bytes32>,
+    message: Bytes,
+}
+
+impl CoordinatedSpend {
+    /// The first byte of the announcement message. CAT announcements starting with `0xcb` are
+    /// reserved for the CAT ring, so the message can't be a plain hash.
+    pub const MESSAGE_PREFIX: u8 = b'c';
+
+    /// Creates a group from the ids of the coins that will be spent together.
+    /// The announcement message is derived from the coin ids, so it's unique to the group.
+    pub fn new(coin_ids: Vec<Bytes32>) -> Self {
+        let mut hasher = Sha256::new();
+        for coin_id in &coin_ids {
+            hasher.update(coin_id);
+        }
+
+        let mut message = vec![Self::MESSAGE_PREFIX];
+        message.extend_from_slice(&hasher.finalize());
+
+        Self {
+            coin_ids,
+            message: message.into(),
+        }
+    }
+
+    pub fn coin_ids(&self) -> &[Bytes32] {
+        &self.coin_ids
+    }
+
+    /// The message that each coin in the group announces.
+    pub fn message(&self) -> &Bytes {
+        &self.message
+    }
+
+    /// Returns the conditions that the inner puzzle of the given coin should output.
+    /// Returns [`None`] if the coin isn't part of the group.
+    pub fn conditions(&self, coin_id: Bytes32) -> Option<Conditions> {
+        let index = self.coin_ids.iter().position(|id| *id == coin_id)?;
+
+        let mut conditions = Conditions::new().create_coin_announcement(self.message.clone());
+
+        // A group of one coin has nothing else to be linked with.
+        if self.coin_ids.len() > 1 {
+            let next_coin_id = self.coin_ids[(index + 1) % self.coin_ids.len()];
+            conditions =
+                conditions.assert_coin_announcement(announcement_id(next_coin_id, &self.message));
+        }
+
+        Some(conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use chia_sdk_test::Simulator;
+
+    use crate::{Cat, CatSpend, SpendContext, SpendWithConditions, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_coordinated_spend() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1,
+            Conditions::new().create_coin(puzzle_hash, 1, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 1);
+        let xch = sim.new_coin(puzzle_hash, 1);
+        let group = CoordinatedSpend::new(vec![cat.coin.coin_id(), xch.coin_id()]);
+
+        assert_eq!(group.message()[0], CoordinatedSpend::MESSAGE_PREFIX);
+        assert!(group.conditions(coin.coin_id()).is_none());
+
+        // Spending only one coin of the group fails.
+        let xch_conditions = group
+            .conditions(xch.coin_id())
+            .context("missing XCH conditions")?;
+        p2.spend(ctx, xch, xch_conditions.clone())?;
+        assert!(sim.spend_coins(ctx.take(), &[sk.clone()]).is_err());
+
+        let cat_conditions = group
+            .conditions(cat.coin.coin_id())
+            .context("missing CAT conditions")?
+            .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()]);
+        let inner_spend = p2.spend_with_conditions(ctx, cat_conditions)?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+        p2.spend(ctx, xch, xch_conditions)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,63 @@
+use std::collections::HashMap;
+
+use chia_protocol::CoinSpend;
+
+use crate::DriverError;
+
+/// Removes coin spends that are identical to an earlier one, keeping the original order.
+///
+/// This is useful when combining spends from multiple sources, such as several offers that
+/// include the same settlement spend. A coin that's spent more than once with a different
+/// puzzle or solution can't be included in the same bundle, so that's an error.
+///
+/// Note that removing a duplicate spend doesn't remove its signature from an aggregated signature,
+/// so this should be done before signing, or with spends that don't require signatures.
+pub fn deduplicate_coin_spends(
+    coin_spends: impl IntoIterator<Item = CoinSpend>,
+) -> Result<Vec<CoinSpend>, DriverError> {
+    let mut indices = HashMap::new();
+    let mut result: Vec<CoinSpend> = Vec::new();
+
+    for coin_spend in coin_spends {
+        let coin_id = coin_spend.coin.coin_id();
+
+        if let Some(&index) = indices.get(&coin_id) {
+            if result[index] != coin_spend {
+                return Err(DriverError::ConflictingSpend(coin_id));
+            }
+            continue;
+        }
+
+        indices.insert(coin_id, result.len());
+        result.push(coin_spend);
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::{Bytes32, Coin, Program};
+
+    use super::*;
+
+    #[test]
+    fn test_deduplicate_coin_spends() {
+        let first = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1);
+        let second = Coin::new(Bytes32::new([3; 32]), Bytes32::new([4; 32]), 1);
+
+        let spend = |coin: Coin, solution: u8| {
+            CoinSpend::new(coin, Program::from(vec![1]), Program::from(vec![solution]))
+        };
+
+        let coin_spends =
+            deduplicate_coin_spends([spend(first, 0x80), spend(second, 0x80), spend(first, 0x80)])
+                .unwrap();
+        assert_eq!(coin_spends, [spend(first, 0x80), spend(second, 0x80)]);
+
+        assert!(matches!(
+            deduplicate_coin_spends([spend(first, 0x80), spend(first, 0x01)]),
+            Err(DriverError::ConflictingSpend(coin_id)) if coin_id == first.coin_id()
+        ));
+    }
+}
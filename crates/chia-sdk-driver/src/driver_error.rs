@@ -1,5 +1,7 @@
 use std::num::TryFromIntError;
 
+use chia_protocol::Bytes32;
+use chia_sdk_utils::CoinSelectionError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use clvmr::reduction::EvalErr;
 use thiserror::Error;
@@ -18,9 +20,15 @@ pub enum DriverError {
     #[error("failed to deserialize clvm value: {0}")]
     FromClvm(#[from] FromClvmError),
 
+    #[error("hex error: {0}")]
+    Hex(#[from] hex::FromHexError),
+
     #[error("clvm eval error: {0}")]
     Eval(#[from] EvalErr),
 
+    #[error("coin selection error: {0}")]
+    CoinSelection(#[from] CoinSelectionError),
+
     #[error("invalid mod hash")]
     InvalidModHash,
 
@@ -39,12 +47,70 @@ pub enum DriverError {
     #[error("invalid memo")]
     InvalidMemo,
 
+    #[error("too many memos: {0}")]
+    TooManyMemos(usize),
+
+    #[error("memo is too large: {0} bytes")]
+    MemoTooLarge(usize),
+
+    #[error("remark payload is missing a tag")]
+    MissingRemarkTag,
+
+    #[error("unhinted payment has a memo that would be treated as a hint")]
+    AmbiguousHint,
+
     #[error("invalid singleton struct")]
     InvalidSingletonStruct,
 
+    #[error("puzzle is not authorized to spend the store")]
+    UnauthorizedPuzzle,
+
     #[error("expected even oracle fee, but it was odd")]
     OddOracleFee,
 
+    #[error("hint type {0} is reserved and can't be registered")]
+    ReservedHintType(u8),
+
+    #[error("hint type {0} is unknown, and hasn't been registered")]
+    UnknownHintType(u8),
+
+    #[error("delegated puzzle has no registered hint type, so it can't be written to memos")]
+    UnhintedDelegatedPuzzle,
+
+    #[error(
+        "memos of the custom delegated puzzle with hint type {0} haven't been parsed or inserted"
+    )]
+    MissingCustomMemos(u8),
+
+    #[error("singleton amount must be odd, but it was {0}")]
+    EvenSingletonAmount(u64),
+
+    #[error(
+        "vanity launcher amounts must end at or below 1000000 mojos, but the range ended at {0}"
+    )]
+    VanityAmountTooLarge(u64),
+
+    #[error("CAT ring is unbalanced by {0}, so the spends would be rejected")]
+    UnbalancedCatRing(i128),
+
+    #[error("{0} verifications are required, but there are only {1} backup ids")]
+    TooManyVerifications(u64, usize),
+
+    #[error("royalty of {0} ten thousandths exceeds 100%")]
+    InvalidRoyalty(u16),
+
+    #[error("coin {0} is spent more than once with different puzzles or solutions")]
+    ConflictingSpend(Bytes32),
+
+    #[error("coin with puzzle hash {0} and amount {1} would be created more than once")]
+    DuplicateOutput(Bytes32, u64),
+
+    #[error("there are no coin spends to sponsor the fee of")]
+    NothingToSponsor,
+
+    #[error("fee of {0} exceeds the sponsor coin amount of {1}")]
+    FeeTooLarge(u64, u64),
+
     #[error("custom driver error: {0}")]
     Custom(String),
 }
@@ -0,0 +1,147 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_sdk_types::Conditions;
+
+use crate::{DriverError, SpendContext, SpendWithConditions};
+
+/// A coin from a separate wallet that pays the fee for a spend bundle built by someone else.
+///
+/// The sponsor coin asserts that a coin in the bundle is spent concurrently, so the fee can only be
+/// claimed if the bundle is included in the same block. The bundle itself doesn't need to change,
+/// so it can be signed before the fee is attached. The sponsor's signature is aggregated separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeSponsor {
+    /// The coin that funds the fee.
+    pub coin: Coin,
+    /// The puzzle hash that receives the remaining value of the coin, if there is any.
+    pub change_puzzle_hash: Bytes32,
+}
+
+impl FeeSponsor {
+    pub fn new(coin: Coin, change_puzzle_hash: Bytes32) -> Self {
+        Self {
+            coin,
+            change_puzzle_hash,
+        }
+    }
+
+    /// Returns the conditions that the sponsor coin's inner puzzle should output to pay the fee
+    /// for the given coin spends. The change is hinted with its own puzzle hash.
+    pub fn conditions(
+        &self,
+        coin_spends: &[CoinSpend],
+        fee: u64,
+    ) -> Result<Conditions, DriverError> {
+        let Some(coin_spend) = coin_spends.first() else {
+            return Err(DriverError::NothingToSponsor);
+        };
+
+        if coin_spends
+            .iter()
+            .any(|coin_spend| coin_spend.coin == self.coin)
+        {
+            return Err(DriverError::ConflictingSpend(self.coin.coin_id()));
+        }
+
+        let Some(change) = self.coin.amount.checked_sub(fee) else {
+            return Err(DriverError::FeeTooLarge(fee, self.coin.amount));
+        };
+
+        let mut conditions = Conditions::new()
+            .reserve_fee(fee)
+            .assert_concurrent_spend(coin_spend.coin.coin_id());
+
+        if change > 0 {
+            conditions = conditions.create_coin(
+                self.change_puzzle_hash,
+                change,
+                vec![self.change_puzzle_hash.into()],
+            );
+        }
+
+        Ok(conditions)
+    }
+
+    /// Spends the sponsor coin with the given inner puzzle to pay the fee for the given coin spends.
+    pub fn spend<P>(
+        &self,
+        ctx: &mut SpendContext,
+        p2: &P,
+        coin_spends: &[CoinSpend],
+        fee: u64,
+    ) -> Result<(), DriverError>
+    where
+        P: SpendWithConditions,
+    {
+        let conditions = self.conditions(coin_spends, fee)?;
+        let spend = p2.spend_with_conditions(ctx, conditions)?;
+        ctx.spend(self.coin, spend)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{test_secret_keys, Simulator};
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    #[test]
+    fn test_fee_sponsor() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let keys = test_secret_keys(2)?;
+        let (user_sk, sponsor_sk) = (keys[0].clone(), keys[1].clone());
+
+        let user = StandardLayer::new(user_sk.public_key());
+        let user_puzzle_hash = StandardArgs::curry_tree_hash(user_sk.public_key()).into();
+        let user_coin = sim.new_coin(user_puzzle_hash, 1);
+
+        let sponsor = StandardLayer::new(sponsor_sk.public_key());
+        let sponsor_puzzle_hash = StandardArgs::curry_tree_hash(sponsor_sk.public_key()).into();
+        let sponsor_coin = sim.new_coin(sponsor_puzzle_hash, 100);
+
+        user.spend(
+            ctx,
+            user_coin,
+            Conditions::new().create_coin(user_puzzle_hash, 1, Vec::new()),
+        )?;
+        let user_spends = ctx.take();
+
+        let fee_sponsor = FeeSponsor::new(sponsor_coin, sponsor_puzzle_hash);
+
+        assert!(matches!(
+            fee_sponsor.spend(ctx, &sponsor, &user_spends, 101),
+            Err(DriverError::FeeTooLarge(101, 100))
+        ));
+        assert!(matches!(
+            fee_sponsor.spend(ctx, &sponsor, &[], 1),
+            Err(DriverError::NothingToSponsor)
+        ));
+
+        // The fee can't be claimed without the sponsored bundle.
+        fee_sponsor.spend(ctx, &sponsor, &user_spends, 30)?;
+        let sponsor_spends = ctx.take();
+        assert!(sim
+            .spend_coins(sponsor_spends.clone(), &[sponsor_sk.clone()])
+            .is_err());
+
+        let mut coin_spends = user_spends;
+        coin_spends.extend(sponsor_spends);
+        sim.spend_coins(coin_spends, &[user_sk, sponsor_sk])?;
+
+        let change = sim.hinted_coins(sponsor_puzzle_hash);
+        assert_eq!(change.len(), 1);
+        assert_eq!(
+            sim.coin_state(change[0])
+                .expect("missing change")
+                .coin
+                .amount,
+            70
+        );
+
+        Ok(())
+    }
+}
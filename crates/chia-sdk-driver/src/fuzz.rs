@@ -0,0 +1,216 @@
+use chia_protocol::{CoinSpend, Program};
+use chia_puzzles::nft::NftMetadata;
+use chia_traits::Streamable;
+use clvm_traits::ToClvm;
+use clvmr::{run_program, Allocator, ChiaDialect, NodePtr};
+
+use crate::{
+    singleton_proof, Cat, Cat1, Cat1Layer, CatLayer, Did, DidLayer, Layer, Nft, NftOwnershipLayer,
+    NftStateLayer, P2DelegatedConditionsLayer, P2DelegatedSingletonLayer, P2OneOfMany, P2Singleton,
+    PlannedSpend, Puzzle, PuzzleRegistry, RoyaltyTransferLayer, SettlementLayer, SingletonLayer,
+    StandardLayer,
+};
+
+/// The heap limit used by the mempool, so that adversarial puzzles can't exhaust memory.
+const HEAP_LIMIT: usize = 500_000_000;
+
+/// Spends which cost more than this to run are skipped. The parsers themselves are capped at the
+/// block cost limit by [`run_puzzle`](chia_sdk_types::run_puzzle), which is too slow to reach on every fuzz input.
+const COST_LIMIT: u64 = 100_000_000;
+
+/// Deserializes a [`CoinSpend`] from arbitrary bytes, and runs it through each of the primitive parsers.
+///
+/// This is meant to be called from a fuzz target. Invalid input is ignored and any parsing errors
+/// are discarded, so the only way for this to fail is by panicking.
+pub fn fuzz_coin_spend_bytes(data: &[u8]) {
+    if let Ok(coin_spend) = CoinSpend::from_bytes(data) {
+        fuzz_coin_spend(&coin_spend);
+    }
+}
+
+/// Runs a [`CoinSpend`] through each of the primitive parsers, and discards the results.
+/// Spends that fail to run within the cost limit are skipped, since they can't be parsed anyway.
+pub fn fuzz_coin_spend(coin_spend: &CoinSpend) {
+    let mut allocator = Allocator::new_limited(HEAP_LIMIT);
+
+    let Ok(puzzle) = coin_spend.puzzle_reveal.to_clvm(&mut allocator) else {
+        return;
+    };
+    let Ok(solution) = coin_spend.solution.to_clvm(&mut allocator) else {
+        return;
+    };
+
+    if run_program(
+        &mut allocator,
+        &ChiaDialect::new(0),
+        puzzle,
+        solution,
+        COST_LIMIT,
+    )
+    .is_err()
+    {
+        return;
+    }
+
+    fuzz_parsers(&mut allocator, coin_spend, puzzle, solution);
+}
+
+/// Runs an already allocated puzzle and solution through each of the primitive parsers, without checking
+/// that the spend runs first. The [`CoinSpend`] is only used by the parsers that need the serialized spend.
+fn fuzz_parsers(
+    allocator: &mut Allocator,
+    coin_spend: &CoinSpend,
+    puzzle: NodePtr,
+    solution: NodePtr,
+) {
+    let coin = coin_spend.coin;
+
+    let _ = PuzzleRegistry::standard().describe(allocator, puzzle);
+
+    let puzzle = Puzzle::parse(allocator, puzzle);
+
+    fuzz_layer::<StandardLayer>(allocator, puzzle, solution);
+    fuzz_layer::<SettlementLayer>(allocator, puzzle, solution);
+    fuzz_layer::<CatLayer<Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<Cat1Layer<Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<SingletonLayer<Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<DidLayer<Program, Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<NftStateLayer<NftMetadata, Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<NftOwnershipLayer<RoyaltyTransferLayer, Puzzle>>(allocator, puzzle, solution);
+    fuzz_layer::<P2Singleton>(allocator, puzzle, solution);
+    fuzz_layer::<P2OneOfMany>(allocator, puzzle, solution);
+    fuzz_layer::<P2DelegatedConditionsLayer>(allocator, puzzle, solution);
+    fuzz_layer::<P2DelegatedSingletonLayer>(allocator, puzzle, solution);
+
+    #[cfg(feature = "chip-0035")]
+    {
+        fuzz_layer::<crate::DelegationLayer>(allocator, puzzle, solution);
+        fuzz_layer::<crate::WriterLayer<Puzzle>>(allocator, puzzle, solution);
+        fuzz_layer::<crate::OracleLayer>(allocator, puzzle, solution);
+        fuzz_layer::<crate::ExpiringOracleLayer>(allocator, puzzle, solution);
+    }
+
+    let children = PlannedSpend::from_coin_spend(allocator, coin_spend)
+        .map(|plan| plan.created_coins)
+        .unwrap_or_default();
+
+    let _ = Cat::parse_children(allocator, coin, puzzle, solution);
+    let _ = Cat1::parse_children(allocator, coin, puzzle, solution);
+    let _ = Nft::<NftMetadata>::parse_child(allocator, coin, puzzle, solution);
+
+    for &child in &children {
+        let _ = Did::<Program>::parse_child(allocator, coin, puzzle, solution, child);
+        let _ = singleton_proof(allocator, child, coin_spend);
+    }
+
+    #[cfg(feature = "chip-0035")]
+    let _ = crate::DataStore::<crate::DataStoreMetadata>::from_spend(allocator, coin_spend, &[]);
+}
+
+/// Parses a single [`Layer`] from the puzzle and solution, and discards the result.
+fn fuzz_layer<L>(allocator: &Allocator, puzzle: Puzzle, solution: NodePtr)
+where
+    L: Layer,
+{
+    let _ = L::parse_puzzle(allocator, puzzle);
+    let _ = L::parse_solution(allocator, solution);
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::{Bytes32, Coin};
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::test_secret_key;
+    use chia_sdk_types::Conditions;
+
+    use crate::{DidOwner, IntermediateLauncher, Launcher, NftMint, SpendContext};
+
+    use super::*;
+
+    fn seed_spends() -> anyhow::Result<Vec<CoinSpend>> {
+        let ctx = &mut SpendContext::new();
+        let pk = test_secret_key()?.public_key();
+        let p2 = StandardLayer::new(pk);
+        let puzzle_hash: Bytes32 = StandardArgs::curry_tree_hash(pk).into();
+        let coin = |index: u8| Coin::new(Bytes32::new([index; 32]), puzzle_hash, 3);
+
+        let (issue_cat, _cat) = Cat::single_issuance_eve(
+            ctx,
+            coin(0).coin_id(),
+            3,
+            Conditions::new().create_coin(puzzle_hash, 3, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin(0), issue_cat)?;
+
+        let (create_did, did) = Launcher::new(coin(1).coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin(1), create_did)?;
+
+        let mint = NftMint::new(
+            NftMetadata::default(),
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&did.info)),
+        );
+
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+        let _did = did.update(ctx, &p2, mint_nft)?;
+        let _nft = nft.transfer(ctx, &p2, puzzle_hash, Conditions::new())?;
+
+        Ok(ctx.take())
+    }
+
+    #[test]
+    fn test_fuzz_deeply_nested_spend() -> anyhow::Result<()> {
+        let depth = 100_000;
+        let mut bytes = vec![0xff; depth];
+        bytes.extend(vec![0x80; depth + 1]);
+
+        let program = Program::from(bytes);
+        let coin = Coin::new(Bytes32::default(), Bytes32::default(), 1);
+        let coin_spend = CoinSpend::new(coin, program.clone(), program);
+        fuzz_coin_spend(&coin_spend);
+
+        // The nested spend doesn't run, so it has to be passed to the parsers directly.
+        let mut allocator = Allocator::new_limited(HEAP_LIMIT);
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(&mut allocator)?;
+        let solution = coin_spend.solution.to_clvm(&mut allocator)?;
+        fuzz_parsers(&mut allocator, &coin_spend, puzzle, solution);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fuzz_mutated_spends() -> anyhow::Result<()> {
+        // A simple deterministic generator, so that failures are reproducible.
+        let mut state = 0x2545_f491_u32;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            usize::try_from(state).unwrap()
+        };
+
+        for coin_spend in seed_spends()? {
+            fuzz_coin_spend(&coin_spend);
+
+            let bytes = coin_spend.to_bytes()?;
+            fuzz_coin_spend_bytes(&bytes);
+
+            for _ in 0..100 {
+                let mut mutated = bytes.clone();
+                let index = next() % mutated.len();
+                mutated[index] ^= next().to_le_bytes()[0].max(1);
+
+                if next() % 4 == 0 {
+                    mutated.truncate(index);
+                }
+
+                fuzz_coin_spend_bytes(&mutated);
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,3 +1,4 @@
+mod cat1_layer;
 mod cat_layer;
 mod did_layer;
 mod nft_ownership_layer;
@@ -11,6 +12,7 @@ mod settlement_layer;
 mod singleton_layer;
 mod standard_layer;
 
+pub use cat1_layer::*;
 pub use cat_layer::*;
 pub use did_layer::*;
 pub use nft_ownership_layer::*;
@@ -1,4 +1,5 @@
 mod delegation_layer;
+mod expiring_oracle_layer;
 mod oracle_layer;
 mod writer_layer;
 
@@ -6,6 +7,7 @@ use clvm_utils::TreeHash;
 use hex_literal::hex;
 
 pub use delegation_layer::*;
+pub use expiring_oracle_layer::*;
 pub use oracle_layer::*;
 pub use writer_layer::*;
 
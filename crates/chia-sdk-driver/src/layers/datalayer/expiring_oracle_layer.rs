@@ -0,0 +1,104 @@
+use chia_protocol::{Bytes, Bytes32};
+use chia_sdk_types::Condition;
+use clvm_traits::{clvm_quote, match_quote, FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, Layer, Puzzle, Spend, SpendContext};
+
+/// The Expiring Oracle [`Layer`] is the same as the [`OracleLayer`](crate::OracleLayer),
+/// except that it can only be spent before a given block height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpiringOracleLayer {
+    /// The puzzle hash corresponding to the address the fee should be paid to.
+    pub oracle_puzzle_hash: Bytes32,
+    /// The amount of XCH that should be paid to the oracle.
+    pub oracle_fee: u64,
+    /// The block height at which the oracle stops working.
+    pub expiration_height: u32,
+}
+
+impl ExpiringOracleLayer {
+    /// Creates a new [`ExpiringOracleLayer`] if the fee is even.
+    /// Returns `None` if the fee is odd, which would make the puzzle invalid.
+    pub fn new(
+        oracle_puzzle_hash: Bytes32,
+        oracle_fee: u64,
+        expiration_height: u32,
+    ) -> Option<Self> {
+        if oracle_fee % 2 != 0 {
+            return None;
+        }
+
+        Some(Self {
+            oracle_puzzle_hash,
+            oracle_fee,
+            expiration_height,
+        })
+    }
+}
+
+impl Layer for ExpiringOracleLayer {
+    type Solution = ();
+
+    fn parse_puzzle(allocator: &Allocator, puzzle: Puzzle) -> Result<Option<Self>, DriverError> {
+        let Some(puzzle) = puzzle.as_raw() else {
+            return Ok(None);
+        };
+
+        let (_q, conditions) =
+            <match_quote!(Vec<Condition<NodePtr>>)>::from_clvm(allocator, puzzle.ptr)?;
+
+        if conditions.len() != 3 {
+            return Ok(None);
+        }
+
+        let Some(Condition::CreateCoin(create_coin)) = conditions.first() else {
+            return Ok(None);
+        };
+
+        let Some(Condition::AssertBeforeHeightAbsolute(assert_before)) = conditions.last() else {
+            return Ok(None);
+        };
+
+        Ok(Self::new(
+            create_coin.puzzle_hash,
+            create_coin.amount,
+            assert_before.height,
+        ))
+    }
+
+    fn parse_solution(_: &Allocator, _: NodePtr) -> Result<Self::Solution, DriverError> {
+        Ok(())
+    }
+
+    fn construct_puzzle(&self, ctx: &mut SpendContext) -> Result<NodePtr, DriverError> {
+        if self.oracle_fee % 2 == 1 {
+            return Err(DriverError::Custom("oracle fee must be even".to_string()));
+        }
+
+        let conditions: Vec<Condition<NodePtr>> = vec![
+            Condition::create_coin(self.oracle_puzzle_hash, self.oracle_fee, vec![]),
+            Condition::create_puzzle_announcement(Bytes::new("$".into())),
+            Condition::assert_before_height_absolute(self.expiration_height),
+        ];
+
+        Ok(clvm_quote!(conditions).to_clvm(&mut ctx.allocator)?)
+    }
+
+    fn construct_solution(
+        &self,
+        _: &mut SpendContext,
+        (): Self::Solution,
+    ) -> Result<NodePtr, DriverError> {
+        Ok(NodePtr::NIL)
+    }
+}
+
+impl ExpiringOracleLayer {
+    pub fn spend(self, ctx: &mut SpendContext) -> Result<Spend, DriverError> {
+        let puzzle = self.construct_puzzle(ctx)?;
+        let solution = self.construct_solution(ctx, ())?;
+
+        Ok(Spend { puzzle, solution })
+    }
+}
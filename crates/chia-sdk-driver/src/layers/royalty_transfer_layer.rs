@@ -14,6 +14,10 @@ use crate::{DriverError, Layer, Puzzle, SpendContext};
 /// The royalty transfer [`Layer`] is used to transfer NFTs with royalties.
 /// When an NFT is transferred, a percentage of the transfer amount is paid to an address.
 /// This address can for example be the creator, or a royalty split puzzle.
+///
+/// Note that this transfer program never outputs a new transfer program, so the ownership layer
+/// keeps it unchanged for the lifetime of the NFT. The royalty puzzle hash and percentage can't
+/// be rotated after minting, and [`Nft::parse_child`](crate::Nft::parse_child) relies on this.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct RoyaltyTransferLayer {
     /// The launcher id of the NFT this transfer program belongs to.
@@ -2,7 +2,7 @@ use chia_bls::PublicKey;
 use chia_protocol::Coin;
 use chia_puzzles::standard::{StandardArgs, StandardSolution, STANDARD_PUZZLE_HASH};
 use chia_sdk_types::Conditions;
-use clvm_traits::{clvm_quote, FromClvm};
+use clvm_traits::FromClvm;
 use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
 
@@ -104,13 +104,13 @@ impl SpendWithConditions for StandardLayer {
         ctx: &mut SpendContext,
         conditions: Conditions,
     ) -> Result<Spend, DriverError> {
-        let delegated_puzzle = ctx.alloc(&clvm_quote!(conditions))?;
+        let delegated_spend = Spend::conditions(ctx, conditions)?;
         self.construct_spend(
             ctx,
             StandardSolution {
                 original_public_key: None,
-                delegated_puzzle,
-                solution: NodePtr::NIL,
+                delegated_puzzle: delegated_spend.puzzle,
+                solution: delegated_spend.solution,
             },
         )
     }
@@ -1,23 +1,52 @@
 #![doc = include_str!("../docs.md")]
 
+mod batch_tracker;
+mod condition_morphing;
+mod coordinated_spend;
+mod deduplicate;
 mod driver_error;
+mod fee_sponsor;
 mod hashed_ptr;
 mod layer;
 mod layers;
-mod merkle_tree;
+mod memo_policy;
 mod primitives;
 mod puzzle;
+mod puzzle_registry;
 mod spend;
 mod spend_context;
+mod spend_plan;
 mod spend_with_conditions;
 
+pub use batch_tracker::*;
+pub use condition_morphing::*;
+pub use coordinated_spend::*;
+pub use deduplicate::*;
 pub use driver_error::*;
+pub use fee_sponsor::*;
 pub use hashed_ptr::*;
 pub use layer::*;
 pub use layers::*;
-pub use merkle_tree::*;
+pub use memo_policy::*;
 pub use primitives::*;
 pub use puzzle::*;
+pub use puzzle_registry::*;
 pub use spend::*;
 pub use spend_context::*;
+pub use spend_plan::*;
 pub use spend_with_conditions::*;
+
+// The merkle tree moved to chia-sdk-types, but is still exported here for existing users.
+pub use chia_sdk_types::MerkleTree;
+
+#[cfg(any(test, feature = "fuzzing"))]
+mod fuzz;
+
+#[cfg(feature = "fuzzing")]
+pub use fuzz::*;
+
+#[cfg(any(test, feature = "test-vectors"))]
+mod test_vectors;
+
+#[cfg(any(test, feature = "test-vectors"))]
+pub use test_vectors::*;
@@ -0,0 +1,213 @@
+use chia_protocol::{Bytes, CoinSpend};
+use chia_sdk_types::{run_puzzle, Condition, Conditions, CreateCoin};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, SpendContext};
+
+/// Limits on the memos of the `CREATE_COIN` conditions in a spend bundle.
+///
+/// Consensus doesn't limit the number or length of memos, other than through the cost of the
+/// bytes they add to the spend bundle (`cost_per_byte` in the consensus constants), so by default
+/// there are no limits. They can be set to match the policy of the node or indexer that the spend
+/// bundle will be submitted to.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoPolicy {
+    /// The maximum number of memos (including the hint) on each `CREATE_COIN` condition.
+    pub max_memos: Option<usize>,
+    /// The maximum length of an individual memo, in bytes.
+    pub max_memo_length: Option<usize>,
+    /// Whether to drop trailing memos past [`max_memos`](Self::max_memos) instead of failing.
+    /// The first memo is the hint, so it's always kept, even if the maximum is zero.
+    pub truncate: bool,
+}
+
+impl MemoPolicy {
+    #[must_use]
+    pub fn with_max_memos(self, max_memos: usize) -> Self {
+        Self {
+            max_memos: Some(max_memos),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_memo_length(self, max_memo_length: usize) -> Self {
+        Self {
+            max_memo_length: Some(max_memo_length),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_truncation(self, truncate: bool) -> Self {
+        Self { truncate, ..self }
+    }
+
+    /// Checks that the memos of a single condition are within the limits.
+    pub fn validate_memos(&self, memos: &[Bytes]) -> Result<(), DriverError> {
+        if self
+            .max_memos
+            .is_some_and(|max_memos| memos.len() > max_memos)
+        {
+            return Err(DriverError::TooManyMemos(memos.len()));
+        }
+
+        if let Some(max_memo_length) = self.max_memo_length {
+            if let Some(memo) = memos.iter().find(|memo| memo.len() > max_memo_length) {
+                return Err(DriverError::MemoTooLarge(memo.len()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Applies the policy to the `CREATE_COIN` conditions, before they are used in a spend.
+    ///
+    /// If truncation is enabled, extra memos are dropped, but the hint is kept. So if the maximum
+    /// is zero, a condition with memos is still rejected. Note that truncation can remove information
+    /// that's needed later, such as the delegated puzzle hints used to recreate a `DataStore`.
+    pub fn apply<T>(&self, conditions: Conditions<T>) -> Result<Conditions<T>, DriverError> {
+        let conditions = conditions
+            .into_iter()
+            .map(|condition| match condition {
+                Condition::CreateCoin(mut create_coin) => {
+                    if let (true, Some(max_memos)) = (self.truncate, self.max_memos) {
+                        create_coin.memos.truncate(max_memos.max(1));
+                    }
+                    self.validate_memos(&create_coin.memos)?;
+                    Ok(Condition::CreateCoin(create_coin))
+                }
+                condition => Ok(condition),
+            })
+            .collect::<Result<Vec<_>, DriverError>>()?;
+
+        Ok(Conditions::default().extend(conditions))
+    }
+
+    /// Runs each of the coin spends, and checks the memos of the `CREATE_COIN` conditions they output.
+    pub fn validate_coin_spends(
+        &self,
+        allocator: &mut Allocator,
+        coin_spends: &[CoinSpend],
+    ) -> Result<(), DriverError> {
+        for coin_spend in coin_spends {
+            let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+            let solution = coin_spend.solution.to_clvm(allocator)?;
+            let output = run_puzzle(allocator, puzzle, solution)?;
+            let conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+            for condition in conditions {
+                if let Condition::CreateCoin(CreateCoin { memos, .. }) = condition {
+                    self.validate_memos(&memos)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl SpendContext {
+    /// Checks the memos of each of the pending coin spends against the policy, without removing them.
+    pub fn validate_memos(&mut self, policy: &MemoPolicy) -> Result<(), DriverError> {
+        let coin_spends: Vec<CoinSpend> = self.iter().cloned().collect();
+        policy.validate_coin_spends(&mut self.allocator, &coin_spends)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_sdk_test::Simulator;
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    #[test]
+    fn test_memo_policy() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let mut memos = vec![Bytes::from(puzzle_hash)];
+        memos.extend(vec![Bytes::new(b"memo".to_vec()); 4]);
+
+        let conditions = Conditions::new().create_coin(puzzle_hash, 1, memos);
+
+        // There are no limits by default.
+        MemoPolicy::default().apply(conditions.clone())?;
+
+        let policy = MemoPolicy::default().with_max_memos(4);
+
+        assert!(matches!(
+            policy.apply(conditions.clone()),
+            Err(DriverError::TooManyMemos(5))
+        ));
+
+        // The spend isn't rejected by consensus, but can be checked before it's submitted.
+        p2.spend(ctx, coin, conditions.clone())?;
+        assert!(matches!(
+            ctx.validate_memos(&policy),
+            Err(DriverError::TooManyMemos(5))
+        ));
+        ctx.take();
+
+        let truncated = policy.with_truncation(true).apply(conditions)?;
+        let Some(Condition::CreateCoin(create_coin)) = truncated.as_ref().first() else {
+            panic!("expected create coin condition");
+        };
+        assert_eq!(create_coin.memos.len(), 4);
+        assert_eq!(create_coin.memos[0], Bytes::from(puzzle_hash));
+
+        p2.spend(ctx, coin, truncated)?;
+        ctx.validate_memos(&policy)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncation_keeps_hint() -> anyhow::Result<()> {
+        let hint = Bytes::from(Bytes32::default());
+        let conditions = Conditions::new().create_coin(
+            Bytes32::default(),
+            1,
+            vec![hint.clone(), Bytes::new(b"memo".to_vec())],
+        );
+
+        let policy = MemoPolicy::default().with_truncation(true);
+
+        let truncated = policy.with_max_memos(1).apply(conditions.clone())?;
+        let Some(Condition::CreateCoin(create_coin)) = truncated.as_ref().first() else {
+            panic!("expected create coin condition");
+        };
+        assert_eq!(create_coin.memos, [hint]);
+
+        // The hint is never dropped, so the condition is rejected instead.
+        assert!(matches!(
+            policy.with_max_memos(0).apply(conditions),
+            Err(DriverError::TooManyMemos(1))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_memo_length_policy() {
+        let policy = MemoPolicy::default()
+            .with_max_memo_length(32)
+            .with_truncation(true);
+
+        let conditions =
+            Conditions::new().create_coin(Bytes32::default(), 1, vec![Bytes::new(vec![0; 33])]);
+
+        // Truncation only drops extra memos, so oversized memos are still rejected.
+        assert!(matches!(
+            policy.apply(conditions),
+            Err(DriverError::MemoTooLarge(33))
+        ));
+    }
+}
@@ -1,14 +1,26 @@
 mod cat;
+mod cat1;
+mod custom_puzzle;
 mod did;
 mod intermediate_launcher;
 mod launcher;
 mod nft;
+mod payment;
+mod remark_payload;
+mod singleton_preimages;
+mod singleton_proof;
 
 pub use cat::*;
+pub use cat1::*;
+pub use custom_puzzle::*;
 pub use did::*;
 pub use intermediate_launcher::*;
 pub use launcher::*;
 pub use nft::*;
+pub use payment::*;
+pub use remark_payload::*;
+pub use singleton_preimages::*;
+pub use singleton_proof::*;
 
 #[cfg(feature = "chip-0035")]
 mod datalayer;
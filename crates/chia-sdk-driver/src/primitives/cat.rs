@@ -1,20 +1,25 @@
 use chia_bls::PublicKey;
-use chia_protocol::{Bytes32, Coin};
+use chia_protocol::{Bytes32, Coin, CoinSpend};
 use chia_puzzles::{
     cat::{CatArgs, CatSolution, EverythingWithSignatureTailArgs, GenesisByCoinIdTailArgs},
     CoinProof, LineageProof,
 };
 use chia_sdk_types::{run_puzzle, Condition, Conditions, CreateCoin};
-use clvm_traits::{clvm_quote, FromClvm};
+use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::CurriedProgram;
 use clvmr::{Allocator, NodePtr};
+use tracing::instrument;
 
 use crate::{CatLayer, DriverError, Layer, Puzzle, Spend, SpendContext};
 
+mod cat_airdrop;
 mod cat_spend;
+mod genesis_by_puzzle_hash_tail;
 mod single_cat_spend;
 
+pub use cat_airdrop::*;
 pub use cat_spend::*;
+pub use genesis_by_puzzle_hash_tail::*;
 pub use single_cat_spend::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -85,6 +90,34 @@ impl Cat {
         )
     }
 
+    /// Issues a CAT whose asset id is tied to the puzzle hash of the parent coin, rather than its coin id.
+    /// Any coin with the same puzzle hash can issue more of the CAT later, in the same way.
+    pub fn genesis_by_puzzle_hash_eve(
+        ctx: &mut SpendContext,
+        parent_coin: Coin,
+        amount: u64,
+        extra_conditions: Conditions,
+    ) -> Result<(Conditions, Cat), DriverError> {
+        let genesis_by_puzzle_hash_ptr = ctx.genesis_by_puzzle_hash_tail_puzzle()?;
+
+        let tail = ctx.alloc(&CurriedProgram {
+            program: genesis_by_puzzle_hash_ptr,
+            args: GenesisByPuzzleHashTailArgs::new(parent_coin.puzzle_hash),
+        })?;
+        let tail_solution = ctx.alloc(&GenesisByPuzzleHashTailSolution::new(
+            parent_coin.parent_coin_info,
+            parent_coin.amount,
+        ))?;
+
+        Self::create_and_spend_eve(
+            ctx,
+            parent_coin.coin_id(),
+            ctx.tree_hash(tail).into(),
+            amount,
+            extra_conditions.run_cat_tail(tail, tail_solution),
+        )
+    }
+
     /// Creates and spends an eve CAT with the provided conditions.
     /// To issue the CAT, you will need to reveal the TAIL puzzle and solution.
     /// This can be done with the [`RunCatTail`] condition.
@@ -95,9 +128,9 @@ impl Cat {
         amount: u64,
         conditions: Conditions,
     ) -> Result<(Conditions, Cat), DriverError> {
-        let inner_puzzle = ctx.alloc(&clvm_quote!(conditions))?;
-        let eve_layer = CatLayer::new(asset_id, inner_puzzle);
-        let inner_puzzle_hash = ctx.tree_hash(inner_puzzle).into();
+        let inner_spend = Spend::conditions(ctx, conditions)?;
+        let eve_layer = CatLayer::new(asset_id, inner_spend.puzzle);
+        let inner_puzzle_hash = ctx.tree_hash(inner_spend.puzzle).into();
         let puzzle_ptr = eve_layer.construct_puzzle(ctx)?;
         let puzzle_hash = ctx.tree_hash(puzzle_ptr).into();
 
@@ -110,11 +143,7 @@ impl Cat {
 
         eve.spend(
             ctx,
-            SingleCatSpend::eve(
-                eve.coin,
-                inner_puzzle_hash,
-                Spend::new(inner_puzzle, NodePtr::NIL),
-            ),
+            SingleCatSpend::eve(eve.coin, inner_puzzle_hash, inner_spend),
         )?;
 
         Ok((
@@ -127,19 +156,21 @@ impl Cat {
     /// Without the ring announcements, CAT spends cannot share inputs and outputs.
     ///
     /// Each item is a CAT and the inner spend for that CAT.
+    ///
+    /// Zero-amount CATs aren't rejected, since the network accepts them both as inputs and as outputs.
+    #[instrument(skip_all, fields(count = cat_spends.len()))]
     pub fn spend_all(ctx: &mut SpendContext, cat_spends: &[CatSpend]) -> Result<(), DriverError> {
         let len = cat_spends.len();
 
-        let mut total_delta = 0;
-
-        for (index, cat_spend) in cat_spends.iter().enumerate() {
-            let CatSpend {
-                cat,
-                inner_spend,
-                extra_delta,
-            } = cat_spend;
+        // Calculate the delta of each spend up front, so that nothing is spent if the ring is unbalanced.
+        let mut deltas = Vec::with_capacity(len);
 
-            // Calculate the delta and add it to the subtotal.
+        for CatSpend {
+            cat,
+            inner_spend,
+            extra_delta,
+        } in cat_spends
+        {
             let output = ctx.run(inner_spend.puzzle, inner_spend.solution)?;
             let conditions: Vec<NodePtr> = ctx.extract(output)?;
 
@@ -148,12 +179,28 @@ impl Cat {
                 .filter_map(|ptr| ctx.extract::<CreateCoin>(ptr).ok());
 
             let delta = create_coins.fold(
-                i128::from(cat.coin.amount) - i128::from(*extra_delta),
+                i128::from(cat.coin.amount) + i128::from(*extra_delta),
                 |delta, create_coin| delta - i128::from(create_coin.amount),
             );
 
-            let prev_subtotal = total_delta;
-            total_delta += delta;
+            deltas.push(delta);
+        }
+
+        // The ring can only be completed if the deltas cancel out, since the last subtotal must match the first.
+        let total_delta: i128 = deltas.iter().sum();
+
+        if total_delta != 0 {
+            return Err(DriverError::UnbalancedCatRing(total_delta));
+        }
+
+        let mut prev_subtotal = 0;
+
+        for (index, cat_spend) in cat_spends.iter().enumerate() {
+            let CatSpend {
+                cat,
+                inner_spend,
+                extra_delta,
+            } = cat_spend;
 
             // Find information of neighboring coins on the ring.
             let prev = &cat_spends[if index == 0 { len - 1 } else { index - 1 }];
@@ -173,6 +220,8 @@ impl Cat {
                     extra_delta: *extra_delta,
                 },
             )?;
+
+            prev_subtotal += deltas[index];
         }
 
         Ok(())
@@ -274,6 +323,44 @@ impl Cat {
 
         Ok(Some(outputs))
     }
+
+    /// Parses the CAT with the given coin from its parent's spend, including its lineage proof.
+    /// Returns [`None`] if the parent isn't a CAT, or if it didn't create the coin.
+    pub fn parse_child(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let Some(children) =
+            Self::parse_children(allocator, parent_coin, parent_puzzle, parent_solution)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(children.into_iter().find(|child| child.coin == coin))
+    }
+
+    /// Parses the CAT with the given coin from its parent's coin spend, such as one fetched from a peer.
+    /// This derives the lineage proof from the parent, rather than requiring it to be tracked separately.
+    pub fn from_parent_spend(
+        allocator: &mut Allocator,
+        parent_spend: &CoinSpend,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let parent_puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+        let parent_puzzle = Puzzle::parse(allocator, parent_puzzle);
+        let parent_solution = parent_spend.solution.to_clvm(allocator)?;
+
+        Self::parse_child(
+            allocator,
+            parent_spend.coin,
+            parent_puzzle,
+            parent_solution,
+            coin,
+        )
+    }
 }
 
 #[cfg(test)]
@@ -343,6 +430,83 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_genesis_by_puzzle_hash_cat() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let other = sim.new_coin(puzzle_hash, 2);
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::genesis_by_puzzle_hash_eve(
+            ctx,
+            coin,
+            1,
+            Conditions::new().create_coin(puzzle_hash, 1, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 1);
+        assert_eq!(
+            cat.asset_id,
+            GenesisByPuzzleHashTailArgs::curry_tree_hash(puzzle_hash).into()
+        );
+        assert!(sim.coin_state(cat.coin.coin_id()).is_some());
+
+        // A different coin with the same puzzle hash can issue more of the same CAT.
+        let (issue_cat, more) = Cat::genesis_by_puzzle_hash_eve(
+            ctx,
+            other,
+            2,
+            Conditions::new().create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, other, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert_eq!(more.asset_id, cat.asset_id);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_genesis_by_puzzle_hash_wrong_parent() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        // The TAIL is curried with a different puzzle hash than the coin that issues it.
+        let genesis_by_puzzle_hash_ptr = ctx.genesis_by_puzzle_hash_tail_puzzle()?;
+        let tail = ctx.alloc(&CurriedProgram {
+            program: genesis_by_puzzle_hash_ptr,
+            args: GenesisByPuzzleHashTailArgs::new(Bytes32::default()),
+        })?;
+        let tail_solution = ctx.alloc(&GenesisByPuzzleHashTailSolution::new(
+            coin.parent_coin_info,
+            coin.amount,
+        ))?;
+
+        let (issue_cat, _cat) = Cat::create_and_spend_eve(
+            ctx,
+            coin.coin_id(),
+            ctx.tree_hash(tail).into(),
+            1,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+                .run_cat_tail(tail, tail_solution),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+
+        // The TAIL raises an error, which is caught when the spend is run to find its signatures.
+        assert!(matches!(
+            sim.spend_coins(ctx.take(), &[sk]).unwrap_err(),
+            SimulatorError::Signer(..)
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_missing_cat_issuance_output() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
@@ -385,6 +549,135 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_cat_from_parent_spend() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(3)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            3,
+            Conditions::new().create_coin(puzzle_hash, 3, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 3);
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, vec![puzzle_hash.into()])
+                .create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // Only the parent spend is needed to reconstruct the child, including its lineage proof.
+        let parent_spend = CoinSpend::new(
+            cat.coin,
+            sim.puzzle_reveal(cat.coin.coin_id())
+                .expect("missing puzzle"),
+            sim.solution(cat.coin.coin_id()).expect("missing solution"),
+        );
+
+        let expected = cat.wrapped_child(puzzle_hash, 2);
+        let child = Cat::from_parent_spend(&mut ctx.allocator, &parent_spend, expected.coin)?
+            .expect("missing child");
+        assert_eq!(child, expected);
+
+        let missing = Coin::new(cat.coin.coin_id(), expected.coin.puzzle_hash, 5);
+        assert!(Cat::from_parent_spend(&mut ctx.allocator, &parent_spend, missing)?.is_none());
+
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(child, inner_spend)])?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zero_amount_cat() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            2,
+            Conditions::new()
+                .create_coin(puzzle_hash, 2, vec![puzzle_hash.into()])
+                .create_coin(puzzle_hash, 0, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The zero-amount CAT can be spent on its own, into another zero-amount CAT.
+        let zero = cat.wrapped_child(puzzle_hash, 0);
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(puzzle_hash, 0, vec![puzzle_hash.into()]),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(zero, inner_spend)])?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let child = zero.wrapped_child(puzzle_hash, 0);
+        assert!(sim.coin_state(child.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unbalanced_cat_ring() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            2,
+            Conditions::new().create_coin(puzzle_hash, 2, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 2);
+
+        // The outputs exceed the input, and nothing is spent.
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(puzzle_hash, 3, vec![puzzle_hash.into()]),
+        )?;
+        assert!(matches!(
+            Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)]),
+            Err(DriverError::UnbalancedCatRing(-1))
+        ));
+        assert_eq!(ctx.take().len(), 0);
+
+        // Outputs that would overflow a u64 are caught as well.
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .create_coin(puzzle_hash, u64::MAX, vec![puzzle_hash.into()])
+                .create_coin(puzzle_hash, u64::MAX, vec![puzzle_hash.into()]),
+        )?;
+        assert!(matches!(
+            Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)]),
+            Err(DriverError::UnbalancedCatRing(..))
+        ));
+
+        Ok(())
+    }
+
     #[rstest]
     #[case(1)]
     #[case(2)]
@@ -540,4 +833,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_cat_melt_ring() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(10000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 6000, vec![puzzle_hash.into()])
+            .create_coin(puzzle_hash, 4000, vec![puzzle_hash.into()]);
+        let (issue_cat, cat) = Cat::multi_issuance_eve(ctx, coin.coin_id(), pk, 10000, conditions)?;
+        p2.spend(ctx, coin, issue_cat)?;
+
+        let everything_with_signature_ptr = ctx.everything_with_signature_tail_puzzle()?;
+
+        let tail = ctx.alloc(&CurriedProgram {
+            program: everything_with_signature_ptr,
+            args: EverythingWithSignatureTailArgs::new(pk),
+        })?;
+
+        // The melted amount is taken from the first coin, so the subtotal of the second coin must account for it.
+        let melt = CatSpend::with_extra_delta(
+            cat.wrapped_child(puzzle_hash, 6000),
+            p2.spend_with_conditions(
+                ctx,
+                Conditions::new()
+                    .create_coin(puzzle_hash, 5000, vec![puzzle_hash.into()])
+                    .run_cat_tail(tail, NodePtr::NIL),
+            )?,
+            -3000,
+        );
+        let other = CatSpend::new(
+            cat.wrapped_child(puzzle_hash, 4000),
+            p2.spend_with_conditions(
+                ctx,
+                Conditions::new().create_coin(puzzle_hash, 2000, vec![puzzle_hash.into()]),
+            )?,
+        );
+
+        Cat::spend_all(ctx, &[melt, other])?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+
+use chia_protocol::{Bytes32, Coin};
+use chia_sdk_types::Conditions;
+use chia_sdk_utils::select_coins;
+
+use crate::{BatchItem, BatchStatus, BatchTracker, DriverError, SpendContext, SpendWithConditions};
+
+use super::{Cat, CatSpend};
+
+/// A recipient of a [`CatAirdrop`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatAirdropRecipient {
+    pub puzzle_hash: Bytes32,
+    pub amount: u64,
+}
+
+impl CatAirdropRecipient {
+    pub fn new(puzzle_hash: Bytes32, amount: u64) -> Self {
+        Self {
+            puzzle_hash,
+            amount,
+        }
+    }
+}
+
+impl BatchItem for CatAirdropRecipient {
+    /// Covers the hinted `CREATE_COIN` condition and the CAT layer's checks on it.
+    const COST_ESTIMATE: u64 = 6_000_000;
+
+    /// Covers spending up to [`CAT_AIRDROP_INPUT_ESTIMATE`] selected CAT coins, and a standard coin that pays the fee.
+    /// Batches funded by more coins than that should use a smaller batch size.
+    const BASE_COST_ESTIMATE: u64 = 750_000_000;
+}
+
+/// The number of selected CAT coins that [`CatAirdropRecipient::BASE_COST_ESTIMATE`] leaves room for.
+pub const CAT_AIRDROP_INPUT_ESTIMATE: usize = 10;
+
+/// The result of sending a batch of a [`CatAirdrop`].
+#[derive(Debug, Clone)]
+pub struct CatAirdropBatch {
+    /// The CATs that were sent, in the same order as the recipients.
+    pub cats: Vec<Cat>,
+    /// The change that was sent back, if there is any.
+    /// It can be used to fund a later batch once it has been confirmed.
+    pub change: Option<Cat>,
+}
+
+/// Plans and tracks sending a CAT to a large number of recipients.
+///
+/// The recipients are split into batches, each of which is a single spend bundle that selects
+/// enough CAT coins to pay every recipient in the batch. The first selected coin creates each output,
+/// hinted with the recipient's puzzle hash so that wallets can find it. Each recipient is confirmed
+/// by the id of the coin it was sent.
+///
+/// Identical outputs can't be created by the same coin, so a recipient that appears more than once
+/// with the same amount is paid in separate batches.
+pub type CatAirdrop = BatchTracker<CatAirdropRecipient>;
+
+impl BatchTracker<CatAirdropRecipient> {
+    /// The total amount that hasn't been included in a batch yet.
+    pub fn pending_amount(&self) -> u128 {
+        self.items()
+            .iter()
+            .filter(|recipient| recipient.status == BatchStatus::Pending)
+            .map(|recipient| u128::from(recipient.item.amount))
+            .sum()
+    }
+
+    /// Pays the next batch of pending recipients, and marks them as submitted.
+    /// Returns the CATs that were sent and the change, or [`None`] if there's nothing left to send.
+    ///
+    /// Enough coins are selected from the spendable CATs to pay for the batch, all of which
+    /// must have the same asset id and be spendable by the inner puzzle. The extra conditions are
+    /// output by the first selected coin, and the change is sent to the given puzzle hash.
+    ///
+    /// If the change would be identical to one of the outputs in the batch, the coin would be created
+    /// twice and the spend would be rejected, so [`DriverError::DuplicateOutput`] is returned instead.
+    /// A different change puzzle hash can be used to pay the batch.
+    pub fn send_batch<I>(
+        &mut self,
+        ctx: &mut SpendContext,
+        spendable_cats: &[Cat],
+        inner: &I,
+        change_puzzle_hash: Bytes32,
+        extra_conditions: Conditions,
+    ) -> Result<Option<CatAirdropBatch>, DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let mut outputs = HashSet::new();
+
+        let indices =
+            self.next_batch(|recipient| outputs.insert((recipient.puzzle_hash, recipient.amount)));
+
+        if indices.is_empty() {
+            return Ok(None);
+        }
+
+        let recipients: Vec<CatAirdropRecipient> = indices
+            .iter()
+            .map(|&index| self.items()[index].item)
+            .collect();
+
+        let total: u128 = recipients
+            .iter()
+            .map(|recipient| u128::from(recipient.amount))
+            .sum();
+
+        let coins: Vec<Coin> = spendable_cats.iter().map(|cat| cat.coin).collect();
+        let selected_coins = select_coins(coins, total)?;
+
+        let selected: Vec<Cat> = selected_coins
+            .iter()
+            .filter_map(|coin| spendable_cats.iter().find(|cat| cat.coin == *coin))
+            .copied()
+            .collect();
+
+        let selected_amount: u128 = selected.iter().map(|cat| u128::from(cat.coin.amount)).sum();
+        let change_amount = u64::try_from(selected_amount - total)?;
+
+        if change_amount > 0 && outputs.contains(&(change_puzzle_hash, change_amount)) {
+            return Err(DriverError::DuplicateOutput(
+                change_puzzle_hash,
+                change_amount,
+            ));
+        }
+
+        let mut conditions = extra_conditions;
+        let mut cats = Vec::with_capacity(recipients.len());
+
+        for recipient in recipients {
+            conditions = conditions.create_coin(
+                recipient.puzzle_hash,
+                recipient.amount,
+                vec![recipient.puzzle_hash.into()],
+            );
+            cats.push(selected[0].wrapped_child(recipient.puzzle_hash, recipient.amount));
+        }
+
+        let mut change = None;
+
+        if change_amount > 0 {
+            conditions = conditions.create_coin(
+                change_puzzle_hash,
+                change_amount,
+                vec![change_puzzle_hash.into()],
+            );
+            change = Some(selected[0].wrapped_child(change_puzzle_hash, change_amount));
+        }
+
+        let mut cat_spends = Vec::with_capacity(selected.len());
+
+        for (i, cat) in selected.iter().enumerate() {
+            let conditions = if i == 0 {
+                conditions.clone()
+            } else {
+                Conditions::new()
+            };
+
+            cat_spends.push(CatSpend::new(
+                *cat,
+                inner.spend_with_conditions(ctx, conditions)?,
+            ));
+        }
+
+        Cat::spend_all(ctx, &cat_spends)?;
+
+        for (&index, cat) in indices.iter().zip(&cats) {
+            self.submit(index, cat.coin.coin_id());
+        }
+
+        Ok(Some(CatAirdropBatch { cats, change }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::SpendBundle;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::{max_spend_bundle_cost, MAINNET_CONSTANTS, TESTNET11_CONSTANTS};
+    use chia_sdk_utils::CoinSelectionError;
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    #[test]
+    fn test_cat_airdrop() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1000,
+            Conditions::new()
+                .create_coin(puzzle_hash, 600, vec![puzzle_hash.into()])
+                .create_coin(puzzle_hash, 400, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let mut spendable_cats = vec![
+            cat.wrapped_child(puzzle_hash, 600),
+            cat.wrapped_child(puzzle_hash, 400),
+        ];
+
+        // The duplicate recipient has to be paid in a separate batch.
+        let recipients = vec![
+            CatAirdropRecipient::new(Bytes32::new([1; 32]), 100),
+            CatAirdropRecipient::new(Bytes32::new([1; 32]), 100),
+            CatAirdropRecipient::new(Bytes32::new([2; 32]), 250),
+            CatAirdropRecipient::new(Bytes32::new([3; 32]), 300),
+            CatAirdropRecipient::new(Bytes32::new([4; 32]), 50),
+        ];
+        let mut airdrop = CatAirdrop::new(recipients).with_batch_size(3);
+        assert_eq!(airdrop.pending_amount(), 800);
+
+        let mut batches = 0;
+
+        while let Some(batch) =
+            airdrop.send_batch(ctx, &spendable_cats, &p2, puzzle_hash, Conditions::new())?
+        {
+            batches += 1;
+            assert_eq!(airdrop.submitted_count(), batch.cats.len());
+
+            let coin_spends = ctx.take();
+            spendable_cats.retain(|cat| {
+                !coin_spends
+                    .iter()
+                    .any(|coin_spend| coin_spend.coin == cat.coin)
+            });
+            spendable_cats.extend(batch.change);
+
+            sim.spend_coins(coin_spends, &[sk.clone()])?;
+
+            for cat in batch.cats {
+                assert!(sim.coin_state(cat.coin.coin_id()).is_some());
+                assert!(!sim.hinted_coins(cat.p2_puzzle_hash).is_empty());
+                assert!(airdrop.confirm(cat.coin.coin_id()));
+            }
+        }
+
+        assert_eq!(batches, 2);
+        assert!(airdrop.is_complete());
+        assert_eq!(
+            spendable_cats
+                .iter()
+                .map(|cat| cat.coin.amount)
+                .sum::<u64>(),
+            200
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_airdrop_insufficient_balance() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (_sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (_, cat) = Cat::single_issuance_eve(ctx, coin.coin_id(), 1, Conditions::new())?;
+        let cat = cat.wrapped_child(puzzle_hash, 1);
+
+        let mut airdrop = CatAirdrop::new(vec![CatAirdropRecipient::new(puzzle_hash, 2)]);
+
+        assert!(matches!(
+            airdrop.send_batch(ctx, &[cat], &p2, puzzle_hash, Conditions::new()),
+            Err(DriverError::CoinSelection(
+                CoinSelectionError::InsufficientBalance(1)
+            ))
+        ));
+        assert_eq!(airdrop.pending_count(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cat_airdrop_duplicate_change() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (_sk, pk, puzzle_hash, coin) = sim.new_p2(200)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (_, cat) = Cat::single_issuance_eve(ctx, coin.coin_id(), 200, Conditions::new())?;
+        let cat = cat.wrapped_child(puzzle_hash, 200);
+
+        // The change of 100 would be identical to the output for the recipient.
+        let mut airdrop = CatAirdrop::new(vec![CatAirdropRecipient::new(puzzle_hash, 100)]);
+
+        assert!(matches!(
+            airdrop.send_batch(ctx, &[cat], &p2, puzzle_hash, Conditions::new()),
+            Err(DriverError::DuplicateOutput(change_puzzle_hash, 100)) if change_puzzle_hash == puzzle_hash
+        ));
+        assert_eq!(airdrop.pending_count(), 1);
+
+        // The batch can be paid with a different change puzzle hash.
+        let change_puzzle_hash = Bytes32::new([1; 32]);
+        let batch = airdrop
+            .send_batch(ctx, &[cat], &p2, change_puzzle_hash, Conditions::new())?
+            .expect("missing batch");
+        assert_eq!(batch.change.map(|cat| cat.coin.amount), Some(100));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_airdrop_cost_estimate() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1000,
+            Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+
+        let mut batch_cost = |count: u8| -> anyhow::Result<u64> {
+            let recipients = (1..=count)
+                .map(|i| CatAirdropRecipient::new(Bytes32::new([i; 32]), 100))
+                .collect();
+
+            CatAirdrop::new(recipients)
+                .send_batch(ctx, &[cat], &p2, puzzle_hash, Conditions::new())?
+                .expect("missing batch");
+
+            let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+            Ok(sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS)?.cost)
+        };
+
+        // The cost of spending the selected coin is shared by the batch, so only the difference is measured.
+        let measured = batch_cost(2)? - batch_cost(1)?;
+
+        assert!(
+            measured <= CatAirdropRecipient::COST_ESTIMATE,
+            "measured cost per output of {measured} exceeds the estimate"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_airdrop_full_default_batch() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let batch_size = CatAirdrop::new(Vec::new()).batch_size();
+        let total = u64::try_from(batch_size)?;
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(total)?;
+        let p2 = StandardLayer::new(pk);
+
+        // The CAT is split into as many coins as the base cost leaves room for, and all of them are needed.
+        // The amounts are distinct, since identical coins can't be created by the same spend.
+        let mut amounts: Vec<u64> = (1..u64::try_from(CAT_AIRDROP_INPUT_ESTIMATE)?).collect();
+        amounts.push(total - amounts.iter().sum::<u64>());
+
+        let mut conditions = Conditions::new();
+        for &amount in &amounts {
+            conditions = conditions.create_coin(puzzle_hash, amount, vec![puzzle_hash.into()]);
+        }
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(ctx, coin.coin_id(), total, conditions)?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let spendable_cats: Vec<Cat> = amounts
+            .iter()
+            .map(|&amount| cat.wrapped_child(puzzle_hash, amount))
+            .collect();
+
+        let recipients = (0..batch_size)
+            .map(|i| {
+                let mut puzzle_hash = [0; 32];
+                puzzle_hash[..8].copy_from_slice(&u64::try_from(i)?.to_be_bytes());
+                Ok(CatAirdropRecipient::new(puzzle_hash.into(), 1))
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        let mut airdrop = CatAirdrop::new(recipients);
+
+        let fee_coin = sim.new_coin(puzzle_hash, 1);
+        p2.spend(ctx, fee_coin, Conditions::new().reserve_fee(1))?;
+
+        let batch = airdrop
+            .send_batch(ctx, &spendable_cats, &p2, puzzle_hash, Conditions::new())?
+            .expect("missing batch");
+        assert_eq!(batch.cats.len(), batch_size);
+        assert!(batch.change.is_none());
+        assert_eq!(airdrop.pending_count(), 0);
+
+        let coin_spends = ctx.take();
+        assert_eq!(coin_spends.len(), CAT_AIRDROP_INPUT_ESTIMATE + 1);
+
+        let spend_bundle = SpendBundle::new(coin_spends, Signature::default());
+        let cost = sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS)?.cost;
+        assert!(
+            cost <= max_spend_bundle_cost(&MAINNET_CONSTANTS),
+            "a full default batch costs {cost}, which exceeds the spend bundle cost limit"
+        );
+
+        Ok(())
+    }
+}
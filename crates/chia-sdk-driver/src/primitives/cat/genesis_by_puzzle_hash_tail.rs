@@ -0,0 +1,76 @@
+use chia_protocol::Bytes32;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash};
+use hex_literal::hex;
+
+/// The curried arguments of the genesis by puzzle hash TAIL.
+///
+/// The TAIL hashes the parent coin info and amount revealed in the [`GenesisByPuzzleHashTailSolution`]
+/// together with the genesis puzzle hash, and fails unless the result is the parent coin id of the CAT being spent.
+/// It also fails if the extra delta is non-zero, so it can issue CATs but never melt them.
+/// This means that any coin with the genesis puzzle hash can issue more of the CAT, with no limit on how many times.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(curry)]
+pub struct GenesisByPuzzleHashTailArgs {
+    pub genesis_puzzle_hash: Bytes32,
+}
+
+impl GenesisByPuzzleHashTailArgs {
+    pub fn new(genesis_puzzle_hash: Bytes32) -> Self {
+        Self {
+            genesis_puzzle_hash,
+        }
+    }
+
+    pub fn curry_tree_hash(genesis_puzzle_hash: Bytes32) -> TreeHash {
+        CurriedProgram {
+            program: GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH,
+            args: GenesisByPuzzleHashTailArgs {
+                genesis_puzzle_hash,
+            },
+        }
+        .tree_hash()
+    }
+}
+
+/// The solution of the genesis by puzzle hash TAIL, which reveals the parent of the eve CAT's parent.
+/// Along with the genesis puzzle hash, this is used to compute and check the eve CAT's parent coin id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct GenesisByPuzzleHashTailSolution {
+    pub parent_parent_coin_info: Bytes32,
+    pub parent_amount: u64,
+}
+
+impl GenesisByPuzzleHashTailSolution {
+    pub fn new(parent_parent_coin_info: Bytes32, parent_amount: u64) -> Self {
+        Self {
+            parent_parent_coin_info,
+            parent_amount,
+        }
+    }
+}
+
+pub const GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE: [u8; 57] = hex!(
+    "
+    ff02ffff03ff2fffff01ff0880ffff01ff02ffff03ffff09ffff0bff82013fff
+    02ff8202bf80ff2d80ff80ffff01ff088080ff018080ff0180
+    "
+);
+
+pub const GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH: TreeHash = TreeHash::new(hex!(
+    "de5a6e06d41518be97ff6365694f4f89475dda773dede267caa33da63b434e36"
+));
+
+#[cfg(test)]
+mod tests {
+    use crate::assert_puzzle_hash;
+
+    use super::*;
+
+    #[test]
+    fn test_puzzle_hash() -> anyhow::Result<()> {
+        assert_puzzle_hash!(GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE => GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH);
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_puzzles::LineageProof;
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{Cat1Layer, DriverError, Layer, Puzzle};
+
+/// A legacy CAT1 coin. These can be parsed so that they show up in a wallet,
+/// but there is no support for spending them, since CAT1 is no longer in use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cat1 {
+    pub coin: Coin,
+    pub lineage_proof: Option<LineageProof>,
+    pub asset_id: Bytes32,
+    pub p2_puzzle_hash: Bytes32,
+}
+
+impl Cat1 {
+    pub fn new(
+        coin: Coin,
+        lineage_proof: Option<LineageProof>,
+        asset_id: Bytes32,
+        p2_puzzle_hash: Bytes32,
+    ) -> Self {
+        Self {
+            coin,
+            lineage_proof,
+            asset_id,
+            p2_puzzle_hash,
+        }
+    }
+
+    /// Parses the CAT1 children created by the spend of a CAT1 parent.
+    /// Returns [`None`] if the parent isn't a CAT1, including if it's a CAT2.
+    pub fn parse_children(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+    ) -> Result<Option<Vec<Self>>, DriverError> {
+        let Some(parent_layer) = Cat1Layer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)?
+        else {
+            return Ok(None);
+        };
+        let parent_solution = Cat1Layer::<Puzzle>::parse_solution(allocator, parent_solution)?;
+
+        let output = run_puzzle(
+            allocator,
+            parent_layer.inner_puzzle.ptr(),
+            parent_solution.inner_puzzle_solution,
+        )?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        let outputs = conditions
+            .into_iter()
+            .filter_map(Condition::into_create_coin)
+            .map(|create_coin| {
+                let wrapped_puzzle_hash = Cat1Layer::curry_tree_hash(
+                    parent_layer.asset_id,
+                    create_coin.puzzle_hash.into(),
+                );
+
+                Self {
+                    coin: Coin::new(
+                        parent_coin.coin_id(),
+                        wrapped_puzzle_hash.into(),
+                        create_coin.amount,
+                    ),
+                    lineage_proof: Some(LineageProof {
+                        parent_parent_coin_info: parent_coin.parent_coin_info,
+                        parent_inner_puzzle_hash: parent_layer
+                            .inner_puzzle
+                            .curried_puzzle_hash()
+                            .into(),
+                        parent_amount: parent_coin.amount,
+                    }),
+                    asset_id: parent_layer.asset_id,
+                    p2_puzzle_hash: create_coin.puzzle_hash,
+                }
+            })
+            .collect();
+
+        Ok(Some(outputs))
+    }
+
+    /// Parses the CAT1 with the given coin from its parent's spend, including its lineage proof.
+    /// Returns [`None`] if the parent isn't a CAT1, or if it didn't create the coin.
+    pub fn parse_child(
+        allocator: &mut Allocator,
+        parent_coin: Coin,
+        parent_puzzle: Puzzle,
+        parent_solution: NodePtr,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let Some(children) =
+            Self::parse_children(allocator, parent_coin, parent_puzzle, parent_solution)?
+        else {
+            return Ok(None);
+        };
+
+        Ok(children.into_iter().find(|child| child.coin == coin))
+    }
+
+    /// Parses the CAT1 with the given coin from its parent's coin spend, such as one fetched from a peer.
+    pub fn from_parent_spend(
+        allocator: &mut Allocator,
+        parent_spend: &CoinSpend,
+        coin: Coin,
+    ) -> Result<Option<Self>, DriverError> {
+        let parent_puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+        let parent_puzzle = Puzzle::parse(allocator, parent_puzzle);
+        let parent_solution = parent_spend.solution.to_clvm(allocator)?;
+
+        Self::parse_child(
+            allocator,
+            parent_spend.coin,
+            parent_puzzle,
+            parent_solution,
+            coin,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::{cat::CatSolution, standard::StandardArgs, CoinProof};
+    use chia_sdk_test::test_secret_key;
+    use chia_sdk_types::Conditions;
+    use clvm_utils::TreeHash;
+
+    use crate::{Cat, SpendContext, SpendWithConditions, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_cat1_children() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let pk = test_secret_key()?.public_key();
+        let p2 = StandardLayer::new(pk);
+        let puzzle_hash: Bytes32 = StandardArgs::curry_tree_hash(pk).into();
+        let asset_id = Bytes32::new([1; 32]);
+
+        let coin = Coin::new(
+            Bytes32::new([2; 32]),
+            Cat1Layer::curry_tree_hash(asset_id, puzzle_hash.into()).into(),
+            3,
+        );
+
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, Vec::new())
+                .create_coin(puzzle_hash, 2, Vec::new()),
+        )?;
+        let layer = Cat1Layer::new(asset_id, inner_spend.puzzle);
+        let puzzle = layer.construct_puzzle(ctx)?;
+        let solution = layer.construct_solution(
+            ctx,
+            CatSolution {
+                inner_puzzle_solution: inner_spend.solution,
+                lineage_proof: None,
+                prev_coin_id: coin.coin_id(),
+                this_coin_info: coin,
+                next_coin_proof: CoinProof {
+                    parent_coin_info: coin.parent_coin_info,
+                    inner_puzzle_hash: puzzle_hash,
+                    amount: coin.amount,
+                },
+                prev_subtotal: 0,
+                extra_delta: 0,
+            },
+        )?;
+        assert_eq!(ctx.tree_hash(puzzle), TreeHash::from(coin.puzzle_hash));
+
+        let parent_puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+        let children = Cat1::parse_children(&mut ctx.allocator, coin, parent_puzzle, solution)?
+            .expect("not a CAT1");
+
+        assert_eq!(children.len(), 2);
+        for (child, amount) in children.iter().zip([1, 2]) {
+            assert_eq!(child.asset_id, asset_id);
+            assert_eq!(child.p2_puzzle_hash, puzzle_hash);
+            assert_eq!(child.coin.amount, amount);
+            assert_eq!(child.coin.parent_coin_info, coin.coin_id());
+            assert_eq!(child.coin.puzzle_hash, coin.puzzle_hash);
+            assert_eq!(
+                child.lineage_proof,
+                Some(LineageProof {
+                    parent_parent_coin_info: coin.parent_coin_info,
+                    parent_inner_puzzle_hash: puzzle_hash,
+                    parent_amount: coin.amount,
+                })
+            );
+        }
+
+        // A CAT1 spend isn't parsed as a CAT2 spend.
+        assert!(Cat::parse_children(&mut ctx.allocator, coin, parent_puzzle, solution)?.is_none());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,157 @@
+use chia_protocol::Coin;
+use clvm_traits::ToClvm;
+use clvm_utils::{CurriedProgram, ToTreeHash, TreeHash, TreeHasher};
+use clvmr::{serde::node_from_bytes, Allocator, NodePtr};
+
+use crate::{DriverError, PuzzleInfo, PuzzleRegistry, Spend, SpendContext};
+
+/// A puzzle that doesn't have a [`Layer`](crate::Layer) implementation, such as one that's still being developed.
+///
+/// The uncurried program is kept along with its mod hash, so that it can be curried with typed arguments
+/// and spent directly. The arguments are typically a struct that derives `ToClvm` with `#[clvm(curry)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CustomPuzzle {
+    /// The uncurried program.
+    pub ptr: NodePtr,
+    /// The tree hash of the uncurried program.
+    pub mod_hash: TreeHash,
+}
+
+impl CustomPuzzle {
+    /// Uses a program that has already been allocated.
+    pub fn new(ctx: &SpendContext, ptr: NodePtr) -> Self {
+        Self {
+            ptr,
+            mod_hash: ctx.tree_hash(ptr),
+        }
+    }
+
+    /// Deserializes a compiled program, and caches it in the [`SpendContext`] by its mod hash.
+    pub fn from_bytes(ctx: &mut SpendContext, bytes: &[u8]) -> Result<Self, DriverError> {
+        let ptr = node_from_bytes(&mut ctx.allocator, bytes)?;
+        let puzzle = Self::new(ctx, ptr);
+        ctx.preload(puzzle.mod_hash, ptr);
+        Ok(puzzle)
+    }
+
+    /// Deserializes a compiled program from hex, with or without a `0x` prefix.
+    pub fn from_hex(ctx: &mut SpendContext, hex: &str) -> Result<Self, DriverError> {
+        let bytes = hex::decode(hex.strip_prefix("0x").unwrap_or(hex))?;
+        Self::from_bytes(ctx, &bytes)
+    }
+
+    /// Curries the program with the given arguments.
+    pub fn curry<A>(&self, ctx: &mut SpendContext, args: A) -> Result<NodePtr, DriverError>
+    where
+        A: ToClvm<Allocator>,
+    {
+        ctx.alloc(&CurriedProgram {
+            program: self.ptr,
+            args,
+        })
+    }
+
+    /// Computes the puzzle hash of the program curried with the given arguments, without allocating it.
+    pub fn curry_tree_hash<A>(&self, args: A) -> TreeHash
+    where
+        A: ToClvm<TreeHasher>,
+    {
+        CurriedProgram {
+            program: self.mod_hash,
+            args,
+        }
+        .tree_hash()
+    }
+
+    /// Constructs a [`Spend`] of the curried program with the given solution.
+    pub fn construct_spend<A, S>(
+        &self,
+        ctx: &mut SpendContext,
+        args: A,
+        solution: S,
+    ) -> Result<Spend, DriverError>
+    where
+        A: ToClvm<Allocator>,
+        S: ToClvm<Allocator>,
+    {
+        let puzzle = self.curry(ctx, args)?;
+        let solution = ctx.alloc(&solution)?;
+        Ok(Spend::new(puzzle, solution))
+    }
+
+    /// Spends a coin with the curried program and the given solution.
+    pub fn spend<A, S>(
+        &self,
+        ctx: &mut SpendContext,
+        coin: Coin,
+        args: A,
+        solution: S,
+    ) -> Result<(), DriverError>
+    where
+        A: ToClvm<Allocator>,
+        S: ToClvm<Allocator>,
+    {
+        let spend = self.construct_spend(ctx, args, solution)?;
+        ctx.spend(coin, spend)
+    }
+
+    /// Registers the program's mod hash, so that the registry can label it.
+    pub fn register(&self, registry: &mut PuzzleRegistry, info: PuzzleInfo) -> Option<PuzzleInfo> {
+        registry.register(self.mod_hash, info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_sdk_test::Simulator;
+    use clvm_traits::{FromClvm, ToClvm};
+    use clvmr::serde::node_to_bytes;
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, ToClvm, FromClvm)]
+    #[clvm(curry)]
+    struct PayToArgs {
+        puzzle_hash: Bytes32,
+    }
+
+    #[test]
+    fn test_custom_puzzle() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        // (c (c (q . 51) (c 2 (c 5 ()))) ()), which creates a coin with the curried
+        // puzzle hash and the amount in the solution.
+        let mut allocator = Allocator::new();
+        let create_coin = (4, ((1, 51), ((4, (2, ((4, (5, ((), ()))), ()))), ())));
+        let program = (4, (create_coin, ((), ()))).to_clvm(&mut allocator)?;
+        let hex = format!("0x{}", hex::encode(node_to_bytes(&allocator, program)?));
+
+        let puzzle = CustomPuzzle::from_hex(ctx, &hex)?;
+        assert_eq!(ctx.get_puzzle(&puzzle.mod_hash), Some(puzzle.ptr));
+
+        let args = PayToArgs {
+            puzzle_hash: Bytes32::new([1; 32]),
+        };
+        let puzzle_hash = puzzle.curry_tree_hash(args);
+        let curried = puzzle.curry(ctx, args)?;
+        assert_eq!(ctx.tree_hash(curried), puzzle_hash);
+
+        let coin = sim.new_coin(puzzle_hash.into(), 1);
+        puzzle.spend(ctx, coin, args, (1, ()))?;
+        sim.spend_coins(ctx.take(), &[])?;
+
+        let child = Coin::new(coin.coin_id(), args.puzzle_hash, 1);
+        assert!(sim.coin_state(child.coin_id()).is_some());
+
+        let mut registry = PuzzleRegistry::new();
+        puzzle.register(&mut registry, PuzzleInfo::new("pay_to", &["PUZZLE_HASH"]));
+        assert_eq!(
+            registry.describe(&ctx.allocator, curried),
+            format!("pay_to(PUZZLE_HASH: 0x{})", hex::encode([1; 32]))
+        );
+
+        Ok(())
+    }
+}
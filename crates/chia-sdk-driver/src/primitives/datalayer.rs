@@ -1,6 +1,8 @@
 mod datastore;
 mod datastore_info;
 mod datastore_launcher;
+mod datastore_proof;
 
 pub use datastore::*;
 pub use datastore_info::*;
+pub use datastore_proof::*;
@@ -1,3 +1,4 @@
+use chia_bls::PublicKey;
 use chia_protocol::{Bytes, Bytes32, Coin, CoinSpend};
 use chia_puzzles::{
     nft::{NftStateLayerArgs, NftStateLayerSolution, NFT_STATE_LAYER_PUZZLE_HASH},
@@ -6,25 +7,27 @@ use chia_puzzles::{
     },
     EveProof, LineageProof, Proof,
 };
-use chia_sdk_types::{run_puzzle, CreateCoin, NewMetadataInfo, NewMetadataOutput};
+use chia_sdk_types::{run_puzzle, Conditions, CreateCoin, NewMetadataInfo, NewMetadataOutput};
 use chia_sdk_types::{Condition, UpdateNftMetadata};
-use clvm_traits::{FromClvm, FromClvmError, ToClvm};
+use clvm_traits::{encode_number, FromClvm, FromClvmError, ToClvm};
 use clvm_utils::{tree_hash, CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::{Allocator, NodePtr};
-use num_bigint::BigInt;
+use tracing::instrument;
 
 use crate::{
     DelegationLayerArgs, DelegationLayerSolution, DriverError, Layer, NftStateLayer, Puzzle,
-    SingletonLayer, Spend, SpendContext, DELEGATION_LAYER_PUZZLE_HASH,
-    DL_METADATA_UPDATER_PUZZLE_HASH,
+    SingletonLayer, Spend, SpendContext, SpendWithConditions, StandardLayer, WriterLayer,
+    DELEGATION_LAYER_PUZZLE_HASH, DL_METADATA_UPDATER_PUZZLE_HASH,
 };
 
 use super::{
-    get_merkle_tree, DataStoreInfo, DataStoreMetadata, DelegatedPuzzle, HintType,
-    MetadataWithRootHash,
+    get_merkle_tree, unknown_hint_type_as_missing_memo, DataStoreInfo, DataStoreMetadata,
+    DelegatedPuzzle, HintRegistry, HintType, MetadataWithRootHash,
 };
 
 /// Everything that is required to spend a [`DataStore`] coin.
+///
+/// Custom metadata types should be defined like those of an [`Nft`](crate::Nft), and also implement [`MetadataWithRootHash`].
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DataStore<M = DataStoreMetadata> {
     /// The coin that holds this [`DataStore`].
@@ -44,6 +47,7 @@ where
     }
 
     /// Creates a coin spend for this [`DataStore`].
+    #[instrument(skip_all, fields(launcher_id = %self.info.launcher_id, coin_id = %self.coin.coin_id()))]
     pub fn spend(self, ctx: &mut SpendContext, inner_spend: Spend) -> Result<CoinSpend, DriverError>
     where
         M: Clone,
@@ -92,10 +96,7 @@ where
             (puzzle_ptr, solution_ptr)
         };
 
-        let puzzle = ctx.serialize(&puzzle_ptr)?;
-        let solution = ctx.serialize(&solution_ptr)?;
-
-        Ok(CoinSpend::new(self.coin, puzzle, solution))
+        ctx.coin_spend(self.coin, Spend::new(puzzle_ptr, solution_ptr))
     }
 
     /// Returns the lineage proof that would be used by the child.
@@ -108,6 +109,87 @@ where
     }
 }
 
+/// The key that authorizes an update to a [`DataStore`], along with the role it has.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataStoreAuthority {
+    /// The owner, which spends the store directly and re-creates it.
+    Owner(PublicKey),
+    /// An admin delegated puzzle, which can update the metadata and the delegated puzzles.
+    Admin(PublicKey),
+    /// A writer delegated puzzle, which can only update the metadata.
+    Writer(PublicKey),
+}
+
+impl<M> DataStore<M>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + MetadataWithRootHash + Clone,
+{
+    /// Spends the store to update its metadata, and returns the child.
+    ///
+    /// The inner spend is constructed for the role of the authority. The owner re-creates the store
+    /// with the same delegated puzzles, whereas an admin or writer relies on the delegation layer to do so.
+    pub fn update_metadata(
+        self,
+        ctx: &mut SpendContext,
+        new_metadata: M,
+        authority: DataStoreAuthority,
+    ) -> Result<DataStore<M>, DriverError> {
+        let new_metadata_condition = Self::new_metadata_condition(ctx, new_metadata)?;
+        let conditions = Conditions::new().with(new_metadata_condition);
+
+        if !self.info.is_authorized(authority) {
+            return Err(DriverError::UnauthorizedPuzzle);
+        }
+
+        let inner_spend = match authority {
+            DataStoreAuthority::Owner(public_key) => {
+                let create_coin = Self::owner_create_coin_condition(
+                    ctx,
+                    self.info.launcher_id,
+                    self.info.owner_puzzle_hash,
+                    self.info.delegated_puzzles.clone(),
+                    false,
+                )?;
+
+                StandardLayer::new(public_key)
+                    .spend_with_conditions(ctx, conditions.with(create_coin))?
+            }
+            DataStoreAuthority::Admin(public_key) => {
+                StandardLayer::new(public_key).spend_with_conditions(ctx, conditions)?
+            }
+            DataStoreAuthority::Writer(public_key) => {
+                WriterLayer::new(StandardLayer::new(public_key)).spend(ctx, conditions)?
+            }
+        };
+
+        let delegated_puzzles = self.info.delegated_puzzles.clone();
+        let coin_spend = self.spend(ctx, inner_spend)?;
+        let child = DataStore::from_spend(&mut ctx.allocator, &coin_spend, &delegated_puzzles)?
+            .ok_or(DriverError::MissingChild)?;
+        ctx.insert(coin_spend);
+
+        Ok(child)
+    }
+}
+
+impl DataStore<DataStoreMetadata> {
+    /// Spends the store to update its root hash, keeping the rest of the metadata the same.
+    /// See [`DataStore::update_metadata`] for how the authority is used.
+    pub fn update_root(
+        self,
+        ctx: &mut SpendContext,
+        new_root: Bytes32,
+        authority: DataStoreAuthority,
+    ) -> Result<Self, DriverError> {
+        let new_metadata = DataStoreMetadata {
+            root_hash: new_root,
+            ..self.info.metadata.clone()
+        };
+
+        self.update_metadata(ctx, new_metadata, authority)
+    }
+}
+
 #[derive(ToClvm, FromClvm, Debug, Clone, PartialEq, Eq)]
 #[clvm(list)]
 pub struct DlLauncherKvList<M = DataStoreMetadata, T = NodePtr> {
@@ -138,6 +220,28 @@ where
         metadata: M,
         fallback_owner_ph: Bytes32,
         memos: Vec<Bytes>,
+    ) -> Result<Self, DriverError> {
+        Self::build_datastore_with_hints(
+            coin,
+            launcher_id,
+            proof,
+            metadata,
+            fallback_owner_ph,
+            memos,
+            &mut HintRegistry::new(),
+        )
+        .map_err(unknown_hint_type_as_missing_memo)
+    }
+
+    /// Same as [`DataStore::build_datastore`], but custom delegated puzzles in the [`HintRegistry`] are parsed too.
+    pub fn build_datastore_with_hints(
+        coin: Coin,
+        launcher_id: Bytes32,
+        proof: Proof,
+        metadata: M,
+        fallback_owner_ph: Bytes32,
+        memos: Vec<Bytes>,
+        hints: &mut HintRegistry,
     ) -> Result<Self, DriverError> {
         let mut memos = memos;
 
@@ -195,7 +299,7 @@ where
 
         let mut delegated_puzzles = vec![];
         while memos.len() > 1 {
-            delegated_puzzles.push(DelegatedPuzzle::from_memos(&mut memos)?);
+            delegated_puzzles.push(DelegatedPuzzle::from_memos_with_hints(&mut memos, hints)?);
         }
 
         Ok(DataStore {
@@ -215,6 +319,25 @@ where
         cs: &CoinSpend,
         parent_delegated_puzzles: &[DelegatedPuzzle],
     ) -> Result<Option<Self>, DriverError>
+    where
+        Self: Sized,
+    {
+        Self::from_spend_with_hints(
+            allocator,
+            cs,
+            parent_delegated_puzzles,
+            &mut HintRegistry::new(),
+        )
+        .map_err(unknown_hint_type_as_missing_memo)
+    }
+
+    /// Same as [`DataStore::from_spend`], but custom delegated puzzles in the [`HintRegistry`] are parsed too.
+    pub fn from_spend_with_hints(
+        allocator: &mut Allocator,
+        cs: &CoinSpend,
+        parent_delegated_puzzles: &[DelegatedPuzzle],
+        hints: &mut HintRegistry,
+    ) -> Result<Option<Self>, DriverError>
     where
         Self: Sized,
     {
@@ -252,13 +375,14 @@ where
                     let mut memos: Vec<Bytes> = vec![launcher_id.into()];
                     memos.extend(solution.key_value_list.memos);
 
-                    Ok(Some(Self::build_datastore(
+                    Ok(Some(Self::build_datastore_with_hints(
                         new_coin,
                         launcher_id,
                         proof,
                         metadata,
                         solution.key_value_list.state_layer_inner_puzzle_hash,
                         memos,
+                        hints,
                     )?))
                 }
                 Err(err) => match err {
@@ -275,13 +399,14 @@ where
                             amount: solution.amount,
                         };
 
-                        Ok(Some(Self::build_datastore(
+                        Ok(Some(Self::build_datastore_with_hints(
                             coin,
                             launcher_id,
                             proof,
                             M::root_hash_only(solution.key_value_list.root_hash),
                             solution.key_value_list.state_layer_inner_puzzle_hash,
                             solution.key_value_list.memos,
+                            hints,
                         )?))
                     }
                     _ => Err(DriverError::FromClvm(err)),
@@ -378,13 +503,14 @@ where
         // and delegated puzzles have been updated (we can rebuild the list from memos)
         if inner_create_coin_condition.memos.len() > 1 {
             // keep in mind that there's always the launcher id memo being added
-            return Ok(Some(Self::build_datastore(
+            return Ok(Some(Self::build_datastore_with_hints(
                 new_coin,
                 singleton_layer.launcher_id,
                 Proof::Lineage(singleton_layer.lineage_proof(cs.coin)),
                 new_metadata,
                 state_layer.inner_puzzle.tree_hash().into(),
                 inner_create_coin_condition.memos,
+                hints,
             )?));
         }
 
@@ -480,11 +606,30 @@ where
 }
 
 impl<M> DataStore<M> {
+    /// Expiring oracles don't have a CHIP-0035 hint type, so they can't be written to memos
+    /// and fail with [`DriverError::UnhintedDelegatedPuzzle`].
+    /// Use [`DataStore::get_recreation_memos_with_hints`] to write them with a registered hint type instead.
     pub fn get_recreation_memos(
         launcher_id: Bytes32,
         owner_puzzle_hash: TreeHash,
         delegated_puzzles: Vec<DelegatedPuzzle>,
-    ) -> Vec<Bytes> {
+    ) -> Result<Vec<Bytes>, DriverError> {
+        Self::get_recreation_memos_with_hints(
+            launcher_id,
+            owner_puzzle_hash,
+            delegated_puzzles,
+            &HintRegistry::new(),
+        )
+    }
+
+    /// Same as [`DataStore::get_recreation_memos`], but the memos of custom delegated puzzles are taken from the [`HintRegistry`],
+    /// and expiring oracles are written with the hint type registered for them.
+    pub fn get_recreation_memos_with_hints(
+        launcher_id: Bytes32,
+        owner_puzzle_hash: TreeHash,
+        delegated_puzzles: Vec<DelegatedPuzzle>,
+        hints: &HintRegistry,
+    ) -> Result<Vec<Bytes>, DriverError> {
         let owner_puzzle_hash: Bytes32 = owner_puzzle_hash.into();
         let mut memos: Vec<Bytes> = vec![launcher_id.into(), owner_puzzle_hash.into()];
 
@@ -502,23 +647,36 @@ impl<M> DataStore<M> {
                     memos.push(Bytes::new([HintType::OraclePuzzle as u8].into()));
                     memos.push(oracle_puzzle_hash.into());
 
-                    let fee_bytes = BigInt::from(oracle_fee).to_signed_bytes_be();
-                    let mut fee_bytes = fee_bytes.as_slice();
-
-                    // https://github.com/Chia-Network/clvm_rs/blob/66a17f9576d26011321bb4c8c16eb1c63b169f1f/src/allocator.rs#L295
-                    while (!fee_bytes.is_empty()) && (fee_bytes[0] == 0) {
-                        if fee_bytes.len() > 1 && (fee_bytes[1] & 0x80 == 0x80) {
-                            break;
-                        }
-                        fee_bytes = &fee_bytes[1..];
-                    }
-
-                    memos.push(fee_bytes.into());
+                    // The fee uses the same encoding as a CLVM atom, so a fee of zero is empty.
+                    memos.push(encode_number(&oracle_fee.to_be_bytes(), false).into());
+                }
+                DelegatedPuzzle::ExpiringOracle(
+                    oracle_puzzle_hash,
+                    oracle_fee,
+                    expiration_height,
+                ) => {
+                    let hint_type = hints
+                        .expiring_oracle()
+                        .ok_or(DriverError::UnhintedDelegatedPuzzle)?;
+
+                    memos.push(Bytes::new([hint_type].into()));
+                    memos.push(oracle_puzzle_hash.into());
+                    memos.push(encode_number(&oracle_fee.to_be_bytes(), false).into());
+                    memos.push(encode_number(&expiration_height.to_be_bytes(), false).into());
+                }
+                DelegatedPuzzle::Custom(custom) => {
+                    memos.push(Bytes::new([custom.hint_type].into()));
+                    memos.push(Bytes32::from(custom.puzzle_hash).into());
+                    memos.extend_from_slice(
+                        hints
+                            .memos(custom)
+                            .ok_or(DriverError::MissingCustomMemos(custom.hint_type))?,
+                    );
                 }
             }
         }
 
-        memos
+        Ok(memos)
     }
 
     // As an owner use CREATE_COIN to:
@@ -530,6 +688,25 @@ impl<M> DataStore<M> {
         new_inner_puzzle_hash: Bytes32,
         new_delegated_puzzles: Vec<DelegatedPuzzle>,
         hint_delegated_puzzles: bool,
+    ) -> Result<Condition, DriverError> {
+        Self::owner_create_coin_condition_with_hints(
+            ctx,
+            launcher_id,
+            new_inner_puzzle_hash,
+            new_delegated_puzzles,
+            hint_delegated_puzzles,
+            &HintRegistry::new(),
+        )
+    }
+
+    /// Same as [`DataStore::owner_create_coin_condition`], but the delegated puzzles are hinted with the [`HintRegistry`].
+    pub fn owner_create_coin_condition_with_hints(
+        ctx: &mut SpendContext,
+        launcher_id: Bytes32,
+        new_inner_puzzle_hash: Bytes32,
+        new_delegated_puzzles: Vec<DelegatedPuzzle>,
+        hint_delegated_puzzles: bool,
+        hints: &HintRegistry,
     ) -> Result<Condition, DriverError> {
         let new_puzzle_hash = if new_delegated_puzzles.is_empty() {
             new_inner_puzzle_hash
@@ -547,11 +724,12 @@ impl<M> DataStore<M> {
             amount: 1,
             puzzle_hash: new_puzzle_hash,
             memos: if hint_delegated_puzzles {
-                Self::get_recreation_memos(
+                Self::get_recreation_memos_with_hints(
                     launcher_id,
                     new_inner_puzzle_hash.into(),
                     new_delegated_puzzles,
-                )
+                    hints,
+                )?
             } else {
                 vec![launcher_id.into()]
             },
@@ -588,15 +766,19 @@ impl<M> DataStore<M> {
 pub mod tests {
     use core::panic;
 
-    use chia_bls::{PublicKey, SecretKey};
+    use chia_bls::{PublicKey, SecretKey, Signature};
+    use chia_protocol::SpendBundle;
     use chia_puzzles::standard::StandardArgs;
     use chia_sdk_test::{test_secret_keys, Simulator};
-    use chia_sdk_types::{Conditions, MeltSingleton, UpdateDataStoreMerkleRoot};
+    use chia_sdk_types::{
+        Conditions, MeltSingleton, UpdateDataStoreMerkleRoot, TESTNET11_CONSTANTS,
+    };
     use clvmr::sha2::Sha256;
     use rstest::rstest;
 
     use crate::{
-        DelegationLayer, Launcher, OracleLayer, SpendWithConditions, StandardLayer, WriterLayer,
+        CustomDelegatedPuzzle, DelegationLayer, ExpiringOracleLayer, Launcher, OracleLayer,
+        SpendWithConditions, StandardLayer, WriterLayer,
     };
 
     use super::*;
@@ -829,7 +1011,7 @@ pub mod tests {
                 datastore.info.launcher_id,
                 owner_puzzle_hash.into(),
                 delegated_puzzles.clone(),
-            ),
+            )?,
         }
         .to_clvm(&mut ctx.allocator)?;
 
@@ -916,6 +1098,448 @@ pub mod tests {
         Ok(())
     }
 
+    #[allow(clippy::similar_names)]
+    #[test]
+    fn test_update_root() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let [owner_sk, admin_sk, writer_sk]: [SecretKey; 3] =
+            test_secret_keys(3)?.try_into().unwrap();
+
+        let owner_pk = owner_sk.public_key();
+        let admin_pk = admin_sk.public_key();
+        let writer_pk = writer_sk.public_key();
+
+        let owner_puzzle_hash = StandardArgs::curry_tree_hash(owner_pk);
+        let coin = sim.new_coin(owner_puzzle_hash.into(), 1);
+
+        let delegated_puzzles = vec![
+            DelegatedPuzzle::Admin(StandardArgs::curry_tree_hash(admin_pk)),
+            DelegatedPuzzle::Writer(StandardArgs::curry_tree_hash(writer_pk)),
+        ];
+
+        let (launch_singleton, mut datastore) = Launcher::new(coin.coin_id(), 1).mint_datastore(
+            ctx,
+            DataStoreMetadata {
+                label: Some("label".to_string()),
+                ..Default::default()
+            },
+            owner_puzzle_hash,
+            delegated_puzzles.clone(),
+        )?;
+        StandardLayer::new(owner_pk).spend(ctx, coin, launch_singleton)?;
+
+        for (new_root, authority) in [
+            ([1; 32], DataStoreAuthority::Writer(writer_pk)),
+            ([2; 32], DataStoreAuthority::Admin(admin_pk)),
+            ([3; 32], DataStoreAuthority::Owner(owner_pk)),
+        ] {
+            let new_root = Bytes32::new(new_root);
+            let parent_coin = datastore.coin;
+            datastore = datastore.update_root(ctx, new_root, authority)?;
+
+            assert_eq!(datastore.coin.parent_coin_info, parent_coin.coin_id());
+            assert_eq!(datastore.info.metadata.root_hash, new_root);
+            assert_eq!(datastore.info.metadata.label, Some("label".to_string()));
+            assert_eq!(datastore.info.owner_puzzle_hash, owner_puzzle_hash.into());
+            assert_eq!(datastore.info.delegated_puzzles, delegated_puzzles);
+        }
+
+        // The admin key isn't authorized as a writer.
+        assert!(matches!(
+            datastore.clone().update_root(
+                ctx,
+                Bytes32::default(),
+                DataStoreAuthority::Writer(admin_pk)
+            ),
+            Err(DriverError::UnauthorizedPuzzle)
+        ));
+
+        sim.spend_coins(ctx.take(), &[owner_sk, admin_sk, writer_sk])?;
+        assert!(sim.coin_state(datastore.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[allow(clippy::similar_names)]
+    #[test]
+    fn test_datastore_capabilities() -> anyhow::Result<()> {
+        let [owner_pk, admin_pk, writer_pk, other_pk]: [PublicKey; 4] = test_secret_keys(4)?
+            .iter()
+            .map(SecretKey::public_key)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        let oracle_puzzle_hash = Bytes32::new([1; 32]);
+
+        let mut info = DataStoreInfo::new(
+            Bytes32::default(),
+            DataStoreMetadata::default(),
+            StandardArgs::curry_tree_hash(owner_pk).into(),
+            vec![
+                DelegatedPuzzle::Admin(StandardArgs::curry_tree_hash(admin_pk)),
+                DelegatedPuzzle::Writer(StandardArgs::curry_tree_hash(writer_pk)),
+                DelegatedPuzzle::Oracle(oracle_puzzle_hash, 1000),
+            ],
+        );
+
+        assert_eq!(
+            info.authority(owner_pk),
+            Some(DataStoreAuthority::Owner(owner_pk))
+        );
+        assert_eq!(
+            info.authority(admin_pk),
+            Some(DataStoreAuthority::Admin(admin_pk))
+        );
+        assert_eq!(
+            info.authority(writer_pk),
+            Some(DataStoreAuthority::Writer(writer_pk))
+        );
+        assert_eq!(info.authority(other_pk), None);
+
+        assert!(info.can_write(writer_pk));
+        assert!(!info.can_write(other_pk));
+        assert!(info.can_update_delegated_puzzles(owner_pk));
+        assert!(info.can_update_delegated_puzzles(admin_pk));
+        assert!(!info.can_update_delegated_puzzles(writer_pk));
+        assert!(!info.is_authorized(DataStoreAuthority::Writer(admin_pk)));
+
+        assert_eq!(info.oracle(), Some((oracle_puzzle_hash, 1000)));
+        info.delegated_puzzles.pop();
+        assert_eq!(info.oracle(), None);
+
+        info.delegated_puzzles.push(DelegatedPuzzle::ExpiringOracle(
+            oracle_puzzle_hash,
+            1000,
+            500,
+        ));
+        assert_eq!(info.oracle(), Some((oracle_puzzle_hash, 1000)));
+
+        Ok(())
+    }
+
+    #[rstest]
+    fn test_oracle_memo_roundtrip(
+        #[values(0, 2, 0x7e, 0x80, 0xfe, 0xff00, u64::MAX - 1)] oracle_fee: u64,
+        #[values(Bytes32::default(), Bytes32::new([0xff; 32]))] oracle_puzzle_hash: Bytes32,
+    ) -> anyhow::Result<()> {
+        let delegated_puzzle = DelegatedPuzzle::Oracle(oracle_puzzle_hash, oracle_fee);
+
+        let mut memos = DataStore::<DataStoreMetadata>::get_recreation_memos(
+            Bytes32::default(),
+            Bytes32::default().into(),
+            vec![delegated_puzzle],
+        )?;
+
+        // The fee memo should be encoded the same way as a CLVM atom.
+        let mut allocator = Allocator::new();
+        let fee_ptr = oracle_fee.to_clvm(&mut allocator)?;
+        assert_eq!(
+            memos.last().map(Bytes::as_ref),
+            Some(allocator.atom(fee_ptr).as_ref())
+        );
+
+        let mut remaining_memos = memos.split_off(2);
+        assert_eq!(
+            DelegatedPuzzle::from_memos(&mut remaining_memos)?,
+            delegated_puzzle
+        );
+        assert!(remaining_memos.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_oracle_memo_too_large() {
+        let mut memos = vec![
+            Bytes::new(vec![HintType::OraclePuzzle as u8]),
+            Bytes32::default().into(),
+            Bytes::new(vec![1; 9]),
+        ];
+
+        assert!(matches!(
+            DelegatedPuzzle::from_memos(&mut memos),
+            Err(DriverError::InvalidMemo)
+        ));
+    }
+
+    #[test]
+    fn test_expiring_oracle_memo_roundtrip() -> anyhow::Result<()> {
+        let delegated_puzzle = DelegatedPuzzle::ExpiringOracle(Bytes32::new([7; 32]), 1000, 500);
+
+        // There's no CHIP-0035 hint type for expiring oracles, so one has to be registered.
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::get_recreation_memos_with_hints(
+                Bytes32::default(),
+                Bytes32::default().into(),
+                vec![delegated_puzzle],
+                &HintRegistry::new(),
+            ),
+            Err(DriverError::UnhintedDelegatedPuzzle)
+        ));
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::get_recreation_memos(
+                Bytes32::default(),
+                Bytes32::default().into(),
+                vec![delegated_puzzle],
+            ),
+            Err(DriverError::UnhintedDelegatedPuzzle)
+        ));
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::owner_create_coin_condition(
+                &mut SpendContext::new(),
+                Bytes32::default(),
+                Bytes32::default(),
+                vec![delegated_puzzle],
+                true,
+            ),
+            Err(DriverError::UnhintedDelegatedPuzzle)
+        ));
+
+        let mut hints = HintRegistry::new();
+        hints.register_expiring_oracle(0x84)?;
+
+        let mut memos = DataStore::<DataStoreMetadata>::get_recreation_memos_with_hints(
+            Bytes32::default(),
+            Bytes32::default().into(),
+            vec![delegated_puzzle],
+            &hints,
+        )?;
+
+        let mut remaining_memos = memos.split_off(2);
+        assert_eq!(remaining_memos.len(), 4);
+        assert_eq!(
+            DelegatedPuzzle::from_memos_with_hints(&mut remaining_memos.clone(), &mut hints)?,
+            delegated_puzzle
+        );
+        assert!(matches!(
+            DelegatedPuzzle::from_memos_with_hints(
+                &mut remaining_memos.clone(),
+                &mut HintRegistry::new()
+            ),
+            Err(DriverError::UnknownHintType(0x84))
+        ));
+        assert!(matches!(
+            DelegatedPuzzle::from_memos(&mut remaining_memos),
+            Err(DriverError::MissingMemo)
+        ));
+
+        // The expiration height is required.
+        let mut memos = vec![
+            Bytes::new(vec![0x84]),
+            Bytes32::default().into(),
+            Bytes::new(vec![2]),
+        ];
+        assert!(matches!(
+            DelegatedPuzzle::from_memos_with_hints(&mut memos, &mut hints),
+            Err(DriverError::MissingMemo)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expiring_oracle() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+
+        let [owner, dude]: [SecretKey; 2] = test_secret_keys(2)?.try_into().unwrap();
+        let owner_pk = owner.public_key();
+        let dude_pk = dude.public_key();
+
+        let oracle_puzzle_hash: Bytes32 = [7; 32].into();
+        let oracle_fee = 1000;
+        let expiration_height = sim.height() + 5;
+
+        let owner_puzzle_hash = StandardArgs::curry_tree_hash(owner_pk).into();
+        let coin = sim.new_coin(owner_puzzle_hash, 1);
+        let dude_puzzle_hash = StandardArgs::curry_tree_hash(dude_pk).into();
+
+        let ctx = &mut SpendContext::new();
+
+        let mut hints = HintRegistry::new();
+        hints.register_expiring_oracle(0x84)?;
+
+        // The expiring oracle is a different leaf than one without an expiration.
+        let delegated_puzzles = vec![DelegatedPuzzle::ExpiringOracle(
+            oracle_puzzle_hash,
+            oracle_fee,
+            expiration_height,
+        )];
+        assert_ne!(
+            get_merkle_tree(ctx, delegated_puzzles.clone())?.root,
+            get_merkle_tree(
+                ctx,
+                vec![DelegatedPuzzle::Oracle(oracle_puzzle_hash, oracle_fee)]
+            )?
+            .root
+        );
+
+        let (launch_singleton, datastore) = Launcher::new(coin.coin_id(), 1)
+            .mint_datastore_with_hints(
+                ctx,
+                DataStoreMetadata::default(),
+                owner_puzzle_hash.into(),
+                delegated_puzzles.clone(),
+                &hints,
+            )?;
+        StandardLayer::new(owner_pk).spend(ctx, coin, launch_singleton)?;
+        sim.spend_coins(ctx.take(), &[owner])?;
+
+        let oracle_layer =
+            ExpiringOracleLayer::new(oracle_puzzle_hash, oracle_fee, expiration_height).unwrap();
+        let oracle_puzzle = oracle_layer.construct_puzzle(ctx)?;
+        let oracle_puzzle = Puzzle::parse(&ctx.allocator, oracle_puzzle);
+        assert_eq!(
+            ExpiringOracleLayer::parse_puzzle(&ctx.allocator, oracle_puzzle)?,
+            Some(oracle_layer)
+        );
+        assert_eq!(
+            OracleLayer::parse_puzzle(&ctx.allocator, oracle_puzzle)?,
+            None
+        );
+
+        // The oracle can be spent before the expiration height.
+        let oracle_spend = oracle_layer.spend(ctx)?;
+        let new_spend = datastore.spend(ctx, oracle_spend)?;
+        let datastore = DataStore::<DataStoreMetadata>::from_spend_with_hints(
+            &mut ctx.allocator,
+            &new_spend,
+            &delegated_puzzles,
+            &mut hints,
+        )?
+        .unwrap();
+        assert_eq!(datastore.info.delegated_puzzles, delegated_puzzles);
+        assert_eq!(
+            datastore.info.oracle(),
+            Some((oracle_puzzle_hash, oracle_fee))
+        );
+        ctx.insert(new_spend);
+
+        let fee_coin = sim.new_coin(dude_puzzle_hash, oracle_fee);
+        StandardLayer::new(dude_pk).spend(ctx, fee_coin, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[dude])?;
+
+        // The simulator doesn't check timelocks, but the spend asserts that it's included before the expiration height.
+        let oracle_spend = oracle_layer.spend(ctx)?;
+        let new_spend = datastore.spend(ctx, oracle_spend)?;
+        ctx.insert(new_spend);
+
+        let fee_coin = sim.new_coin(dude_puzzle_hash, oracle_fee);
+        StandardLayer::new(dude_pk).spend(ctx, fee_coin, Conditions::new())?;
+        let dry_run = sim.dry_run(
+            &SpendBundle::new(ctx.take(), Signature::default()),
+            &TESTNET11_CONSTANTS,
+        )?;
+        assert_eq!(dry_run.before_height_absolute, Some(expiration_height));
+
+        Ok(())
+    }
+
+    fn parse_one_memo(memos: &mut Vec<Bytes>) -> Result<Vec<Bytes>, DriverError> {
+        if memos.is_empty() {
+            return Err(DriverError::MissingMemo);
+        }
+        Ok(vec![memos.remove(0)])
+    }
+
+    #[test]
+    fn test_custom_hint_type() -> anyhow::Result<()> {
+        let mut hints = HintRegistry::new();
+        assert!(hints.register(0x80, parse_one_memo)?.is_none());
+        assert!(matches!(
+            hints.register(HintType::OraclePuzzle as u8, parse_one_memo),
+            Err(DriverError::ReservedHintType(3))
+        ));
+
+        let custom_puzzle = CustomDelegatedPuzzle::new(0x80, TreeHash::new([1; 32]));
+        let custom_memos = vec![Bytes::new(vec![42])];
+        hints.insert_memos(custom_puzzle, custom_memos.clone());
+
+        let custom = DelegatedPuzzle::Custom(custom_puzzle);
+        let writer = DelegatedPuzzle::Writer(TreeHash::new([2; 32]));
+
+        // The memos of a custom delegated puzzle have to be known to write it out.
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::get_recreation_memos_with_hints(
+                Bytes32::default(),
+                TreeHash::new([3; 32]),
+                vec![custom],
+                &HintRegistry::new(),
+            ),
+            Err(DriverError::MissingCustomMemos(0x80))
+        ));
+
+        let ctx = &mut SpendContext::new();
+        let owner_puzzle_hash = TreeHash::new([3; 32]);
+        let launcher = Launcher::new(Bytes32::default(), 1);
+        let launcher_coin = launcher.coin();
+        let (_conditions, datastore) = launcher.mint_datastore_with_hints(
+            ctx,
+            DataStoreMetadata::default(),
+            owner_puzzle_hash,
+            vec![custom, writer],
+            &hints,
+        )?;
+        let launcher_spend = ctx
+            .take()
+            .into_iter()
+            .find(|coin_spend| coin_spend.coin == launcher_coin)
+            .expect("missing launcher spend");
+
+        // The custom delegated puzzle can only be parsed if its hint type is registered.
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::from_spend_with_hints(
+                &mut ctx.allocator,
+                &launcher_spend,
+                &[],
+                &mut HintRegistry::new(),
+            ),
+            Err(DriverError::UnknownHintType(0x80))
+        ));
+        assert!(matches!(
+            DataStore::<DataStoreMetadata>::from_spend(&mut ctx.allocator, &launcher_spend, &[]),
+            Err(DriverError::MissingMemo)
+        ));
+
+        let mut parsed_hints = HintRegistry::new();
+        parsed_hints.register(0x80, parse_one_memo)?;
+
+        let parsed = DataStore::<DataStoreMetadata>::from_spend_with_hints(
+            &mut ctx.allocator,
+            &launcher_spend,
+            &[],
+            &mut parsed_hints,
+        )?
+        .expect("not a datastore");
+
+        assert_eq!(parsed.info, datastore.info);
+        assert_eq!(parsed.info.delegated_puzzles, vec![custom, writer]);
+        assert_eq!(
+            parsed_hints.memos(custom_puzzle),
+            Some(custom_memos.as_slice())
+        );
+
+        // The parsed memos are written back out unchanged.
+        assert_eq!(
+            DataStore::<DataStoreMetadata>::get_recreation_memos_with_hints(
+                parsed.info.launcher_id,
+                owner_puzzle_hash,
+                parsed.info.delegated_puzzles.clone(),
+                &parsed_hints,
+            )?,
+            DataStore::<DataStoreMetadata>::get_recreation_memos_with_hints(
+                datastore.info.launcher_id,
+                owner_puzzle_hash,
+                datastore.info.delegated_puzzles,
+                &hints,
+            )?
+        );
+
+        Ok(())
+    }
+
     #[derive(PartialEq, Debug, Clone, Copy)]
     pub enum DstAdminLayer {
         None,
@@ -1043,7 +1667,7 @@ pub mod tests {
                     src_datastore.info.launcher_id,
                     owner_puzzle_hash.into(),
                     dst_delegated_puzzles.clone(),
-                ),
+                )?,
             }
             .to_clvm(&mut ctx.allocator)?;
 
@@ -1,14 +1,19 @@
 use crate::{
-    DelegationLayer, DelegationLayerArgs, DriverError, Layer, MerkleTree, NftStateLayer,
-    OracleLayer, SingletonLayer, SpendContext, WriterLayerArgs, DELEGATION_LAYER_PUZZLE_HASH,
-    DL_METADATA_UPDATER_PUZZLE_HASH,
+    DataStoreAuthority, DelegationLayer, DelegationLayerArgs, DriverError, ExpiringOracleLayer,
+    Layer, NftStateLayer, OracleLayer, SingletonLayer, SpendContext, WriterLayerArgs,
+    DELEGATION_LAYER_PUZZLE_HASH, DL_METADATA_UPDATER_PUZZLE_HASH,
 };
+use std::collections::HashMap;
+
+use chia_bls::PublicKey;
 use chia_protocol::{Bytes, Bytes32};
-use chia_puzzles::nft::NftStateLayerArgs;
-use clvm_traits::{ClvmDecoder, ClvmEncoder, FromClvm, FromClvmError, Raw, ToClvm, ToClvmError};
+use chia_puzzles::{nft::NftStateLayerArgs, standard::StandardArgs};
+use chia_sdk_types::MerkleTree;
+use clvm_traits::{
+    decode_number, ClvmDecoder, ClvmEncoder, FromClvm, FromClvmError, Raw, ToClvm, ToClvmError,
+};
 use clvm_utils::{tree_hash, CurriedProgram, ToTreeHash, TreeHash};
 use clvmr::Allocator;
-use num_bigint::BigInt;
 
 pub type StandardDataStoreLayers<M = DataStoreMetadata, I = DelegationLayer> =
     SingletonLayer<NftStateLayer<M, I>>;
@@ -34,15 +39,130 @@ impl HintType {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy)]
+/// Parses the memos of a custom delegated puzzle which come after its hint type and puzzle hash.
+/// It must remove exactly the memos that belong to the delegated puzzle from the front of the list,
+/// and return them so that they can be written back out by [`DataStore::get_recreation_memos_with_hints`](crate::DataStore::get_recreation_memos_with_hints).
+pub type HintParser = fn(&mut Vec<Bytes>) -> Result<Vec<Bytes>, DriverError>;
+
+/// A lookup table from custom hint type bytes to the [`HintParser`] for that kind of delegated puzzle.
+/// This allows delegated puzzles which aren't known to the driver to round-trip through memos.
+///
+/// The memos of each [`CustomDelegatedPuzzle`] are stored here rather than in the delegated puzzle itself,
+/// so that [`DelegatedPuzzle`] can stay [`Copy`]. They're added when the delegated puzzle is parsed,
+/// or with [`HintRegistry::insert_memos`] before it's written out.
+#[derive(Debug, Default, Clone)]
+pub struct HintRegistry {
+    parsers: HashMap<u8, HintParser>,
+    memos: HashMap<CustomDelegatedPuzzle, Vec<Bytes>>,
+    expiring_oracle: Option<u8>,
+}
+
+impl HintRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a custom hint type, returning the parser it previously had if any.
+    /// The hint types of the built-in [`HintType`] variants, as well as 0, can't be registered.
+    pub fn register(
+        &mut self,
+        hint_type: u8,
+        parser: HintParser,
+    ) -> Result<Option<HintParser>, DriverError> {
+        if hint_type == 0
+            || HintType::from_value(hint_type).is_some()
+            || self.expiring_oracle == Some(hint_type)
+        {
+            return Err(DriverError::ReservedHintType(hint_type));
+        }
+
+        Ok(self.parsers.insert(hint_type, parser))
+    }
+
+    /// Registers the hint type used for [`DelegatedPuzzle::ExpiringOracle`].
+    ///
+    /// CHIP-0035 only defines hint types for the admin, writer, and oracle puzzles, so an expiring oracle can
+    /// only be written to memos once the applications reading them have agreed on a hint type for it.
+    /// Its memos are the oracle puzzle hash, fee, and expiration height, in that order.
+    pub fn register_expiring_oracle(&mut self, hint_type: u8) -> Result<(), DriverError> {
+        if hint_type == 0
+            || HintType::from_value(hint_type).is_some()
+            || self.parsers.contains_key(&hint_type)
+        {
+            return Err(DriverError::ReservedHintType(hint_type));
+        }
+
+        self.expiring_oracle = Some(hint_type);
+        Ok(())
+    }
+
+    /// Returns the hint type registered for [`DelegatedPuzzle::ExpiringOracle`], if there is one.
+    pub fn expiring_oracle(&self) -> Option<u8> {
+        self.expiring_oracle
+    }
+
+    /// Returns the parser registered for the given hint type, if there is one.
+    pub fn get(&self, hint_type: u8) -> Option<HintParser> {
+        self.parsers.get(&hint_type).copied()
+    }
+
+    /// Sets the memos which come after the puzzle hash of a custom delegated puzzle, returning the previous ones if any.
+    /// A custom delegated puzzle without any extra memos still needs an empty list to be inserted before it's written out.
+    pub fn insert_memos(
+        &mut self,
+        delegated_puzzle: CustomDelegatedPuzzle,
+        memos: Vec<Bytes>,
+    ) -> Option<Vec<Bytes>> {
+        self.memos.insert(delegated_puzzle, memos)
+    }
+
+    /// Returns the memos of a custom delegated puzzle, if it has been parsed or they've been inserted.
+    pub fn memos(&self, delegated_puzzle: CustomDelegatedPuzzle) -> Option<&[Bytes]> {
+        self.memos.get(&delegated_puzzle).map(Vec::as_slice)
+    }
+}
+
+/// A delegated puzzle with a hint type registered in a [`HintRegistry`], rather than one known to the driver.
+/// Any memos after the puzzle hash are stored in the [`HintRegistry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CustomDelegatedPuzzle {
+    pub hint_type: u8,
+    /// The tree hash of the full delegated puzzle, which is used as its leaf in the merkle tree.
+    pub puzzle_hash: TreeHash,
+}
+
+impl CustomDelegatedPuzzle {
+    pub fn new(hint_type: u8, puzzle_hash: TreeHash) -> Self {
+        Self {
+            hint_type,
+            puzzle_hash,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DelegatedPuzzle {
-    Admin(TreeHash),      // puzzle hash
-    Writer(TreeHash),     // inner puzzle hash
-    Oracle(Bytes32, u64), // oracle fee puzzle hash, fee amount
+    Admin(TreeHash),                   // puzzle hash
+    Writer(TreeHash),                  // inner puzzle hash
+    Oracle(Bytes32, u64),              // oracle fee puzzle hash, fee amount
+    ExpiringOracle(Bytes32, u64, u32), // oracle fee puzzle hash, fee amount, expiration height
+    Custom(CustomDelegatedPuzzle),
 }
 
 impl DelegatedPuzzle {
+    /// Parses a delegated puzzle from memos. Hint types which aren't defined by CHIP-0035 are a [`DriverError::MissingMemo`].
     pub fn from_memos(remaining_memos: &mut Vec<Bytes>) -> Result<Self, DriverError> {
+        Self::from_memos_with_hints(remaining_memos, &mut HintRegistry::new())
+            .map_err(unknown_hint_type_as_missing_memo)
+    }
+
+    /// Parses a delegated puzzle from memos, including custom delegated puzzles registered in the [`HintRegistry`].
+    /// The memos of a custom delegated puzzle are stored in the registry.
+    pub fn from_memos_with_hints(
+        remaining_memos: &mut Vec<Bytes>,
+        hints: &mut HintRegistry,
+    ) -> Result<Self, DriverError> {
         if remaining_memos.len() < 2 {
             return Err(DriverError::MissingMemo);
         }
@@ -51,7 +171,8 @@ impl DelegatedPuzzle {
         if first_memo.len() != 1 {
             return Err(DriverError::InvalidMemo);
         }
-        let puzzle_type = HintType::from_value(first_memo[0]);
+        let hint_type = first_memo[0];
+        let puzzle_type = HintType::from_value(hint_type);
 
         // under current specs, first value will always be a puzzle hash
         let puzzle_hash: TreeHash = TreeHash::new(
@@ -71,17 +192,54 @@ impl DelegatedPuzzle {
                 }
 
                 // puzzle hash bech32m_decode(oracle_address), not puzzle hash of the whole oracle puzze!
-                let oracle_fee: u64 = BigInt::from_signed_bytes_be(&remaining_memos.remove(0))
-                    .to_u64_digits()
-                    .1[0];
+                let oracle_fee = decode_number(&remaining_memos.remove(0), false)
+                    .map(u64::from_be_bytes)
+                    .ok_or(DriverError::InvalidMemo)?;
 
                 Ok(DelegatedPuzzle::Oracle(puzzle_hash.into(), oracle_fee))
             }
-            None => Err(DriverError::MissingMemo),
+            None if hints.expiring_oracle() == Some(hint_type) => {
+                if remaining_memos.len() < 2 {
+                    return Err(DriverError::MissingMemo);
+                }
+
+                let oracle_fee = decode_number(&remaining_memos.remove(0), false)
+                    .map(u64::from_be_bytes)
+                    .ok_or(DriverError::InvalidMemo)?;
+                let expiration_height = decode_number(&remaining_memos.remove(0), false)
+                    .map(u32::from_be_bytes)
+                    .ok_or(DriverError::InvalidMemo)?;
+
+                Ok(DelegatedPuzzle::ExpiringOracle(
+                    puzzle_hash.into(),
+                    oracle_fee,
+                    expiration_height,
+                ))
+            }
+            None => {
+                let parser = hints
+                    .get(hint_type)
+                    .ok_or(DriverError::UnknownHintType(hint_type))?;
+                let memos = parser(remaining_memos)?;
+
+                let custom = CustomDelegatedPuzzle::new(hint_type, puzzle_hash);
+                hints.insert_memos(custom, memos);
+
+                Ok(DelegatedPuzzle::Custom(custom))
+            }
         }
     }
 }
 
+// Unknown hint types were a missing memo before they could be registered, so the functions
+// that don't take a [`HintRegistry`] keep returning that error.
+pub(crate) fn unknown_hint_type_as_missing_memo(error: DriverError) -> DriverError {
+    match error {
+        DriverError::UnknownHintType(_) => DriverError::MissingMemo,
+        error => error,
+    }
+}
+
 pub trait MetadataWithRootHash {
     fn root_hash(&self) -> Bytes32;
     fn root_hash_only(root_hash: Bytes32) -> Self;
@@ -257,6 +415,61 @@ impl<M> DataStoreInfo<M> {
             inner_ph_hash,
         ))
     }
+
+    /// Checks whether the authority's key has the role it claims, using the standard puzzle.
+    pub fn is_authorized(&self, authority: DataStoreAuthority) -> bool {
+        match authority {
+            DataStoreAuthority::Owner(public_key) => {
+                self.owner_puzzle_hash == StandardArgs::curry_tree_hash(public_key).into()
+            }
+            DataStoreAuthority::Admin(public_key) => self.delegated_puzzles.contains(
+                &DelegatedPuzzle::Admin(StandardArgs::curry_tree_hash(public_key)),
+            ),
+            DataStoreAuthority::Writer(public_key) => self.delegated_puzzles.contains(
+                &DelegatedPuzzle::Writer(StandardArgs::curry_tree_hash(public_key)),
+            ),
+        }
+    }
+
+    /// Returns the most privileged role that the key has, if any.
+    /// The owner takes precedence over an admin, which takes precedence over a writer.
+    pub fn authority(&self, public_key: PublicKey) -> Option<DataStoreAuthority> {
+        [
+            DataStoreAuthority::Owner(public_key),
+            DataStoreAuthority::Admin(public_key),
+            DataStoreAuthority::Writer(public_key),
+        ]
+        .into_iter()
+        .find(|authority| self.is_authorized(*authority))
+    }
+
+    /// Whether the key can update the metadata, which any role is allowed to do.
+    pub fn can_write(&self, public_key: PublicKey) -> bool {
+        self.authority(public_key).is_some()
+    }
+
+    /// Whether the key can change the delegated puzzles, which only the owner and admins can do.
+    pub fn can_update_delegated_puzzles(&self, public_key: PublicKey) -> bool {
+        matches!(
+            self.authority(public_key),
+            Some(DataStoreAuthority::Owner(_) | DataStoreAuthority::Admin(_))
+        )
+    }
+
+    /// Returns the oracle's payout puzzle hash and fee, if the store has an oracle.
+    ///
+    /// This includes an expiring oracle, even if it has expired, since the store doesn't know the current height.
+    pub fn oracle(&self) -> Option<(Bytes32, u64)> {
+        self.delegated_puzzles
+            .iter()
+            .find_map(|delegated_puzzle| match delegated_puzzle {
+                DelegatedPuzzle::Oracle(puzzle_hash, fee)
+                | DelegatedPuzzle::ExpiringOracle(puzzle_hash, fee, _) => {
+                    Some((*puzzle_hash, *fee))
+                }
+                _ => None,
+            })
+    }
 }
 
 pub fn get_merkle_tree(
@@ -280,6 +493,17 @@ pub fn get_merkle_tree(
 
                 leaves.push(tree_hash(&ctx.allocator, oracle_full_puzzle_ptr).into());
             }
+            DelegatedPuzzle::ExpiringOracle(oracle_puzzle_hash, oracle_fee, expiration_height) => {
+                let oracle_full_puzzle_ptr =
+                    ExpiringOracleLayer::new(oracle_puzzle_hash, oracle_fee, expiration_height)
+                        .ok_or(DriverError::OddOracleFee)?
+                        .construct_puzzle(ctx)?;
+
+                leaves.push(tree_hash(&ctx.allocator, oracle_full_puzzle_ptr).into());
+            }
+            DelegatedPuzzle::Custom(custom) => {
+                leaves.push(custom.puzzle_hash.into());
+            }
         }
     }
 
@@ -12,7 +12,9 @@ use crate::{
     DelegationLayerArgs, DriverError, Launcher, SpendContext, DL_METADATA_UPDATER_PUZZLE_HASH,
 };
 
-use super::{get_merkle_tree, DataStore, DataStoreInfo, DelegatedPuzzle, DlLauncherKvList};
+use super::{
+    get_merkle_tree, DataStore, DataStoreInfo, DelegatedPuzzle, DlLauncherKvList, HintRegistry,
+};
 
 impl Launcher {
     pub fn mint_datastore<M>(
@@ -22,6 +24,28 @@ impl Launcher {
         owner_puzzle_hash: TreeHash,
         delegated_puzzles: Vec<DelegatedPuzzle>,
     ) -> Result<(Conditions, DataStore<M>), DriverError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
+    {
+        self.mint_datastore_with_hints(
+            ctx,
+            metadata,
+            owner_puzzle_hash,
+            delegated_puzzles,
+            &HintRegistry::new(),
+        )
+    }
+
+    /// Same as [`Launcher::mint_datastore`], but the delegated puzzles are hinted with the [`HintRegistry`].
+    /// This is required to mint a store with an expiring oracle, since it doesn't have a CHIP-0035 hint type.
+    pub fn mint_datastore_with_hints<M>(
+        self,
+        ctx: &mut SpendContext,
+        metadata: M,
+        owner_puzzle_hash: TreeHash,
+        delegated_puzzles: Vec<DelegatedPuzzle>,
+        hints: &HintRegistry,
+    ) -> Result<(Conditions, DataStore<M>), DriverError>
     where
         M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
     {
@@ -51,11 +75,12 @@ impl Launcher {
         }
         .tree_hash();
 
-        let mut memos = DataStore::<M>::get_recreation_memos(
+        let mut memos = DataStore::<M>::get_recreation_memos_with_hints(
             Bytes32::default(),
             owner_puzzle_hash,
             delegated_puzzles.clone(),
-        )
+            hints,
+        )?
         .into_iter()
         .skip(1)
         .collect();
@@ -0,0 +1,174 @@
+use chia_protocol::{Bytes, Bytes32};
+use clvm_traits::ToClvm;
+use clvm_utils::{tree_hash_pair, ToTreeHash, TreeHash};
+use clvmr::Allocator;
+
+use super::{DataStore, MetadataWithRootHash};
+
+/// Which side of the combined hash the sibling hash is on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashSide {
+    Left,
+    Right,
+}
+
+/// A single step from a node up to its parent in a `DataLayer` merkle tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofOfInclusionLayer {
+    pub other_hash_side: HashSide,
+    pub other_hash: Bytes32,
+    pub combined_hash: Bytes32,
+}
+
+/// A proof that a key value pair is included in a `DataLayer` merkle tree, in the same format that
+/// the `DataLayer` service serves them in.
+///
+/// Leaves are hashed as the tree hash of `(key . value)`, and internal nodes as the tree hash of a pair of their children.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofOfInclusion {
+    pub key: Bytes,
+    pub value: Bytes,
+    pub layers: Vec<ProofOfInclusionLayer>,
+}
+
+impl ProofOfInclusion {
+    /// The hash of the leaf that contains the key value pair.
+    pub fn node_hash(&self) -> Bytes32 {
+        (self.key.clone(), self.value.clone()).tree_hash().into()
+    }
+
+    /// The root hash that the proof claims the leaf is included in.
+    pub fn root_hash(&self) -> Bytes32 {
+        self.layers
+            .last()
+            .map_or_else(|| self.node_hash(), |layer| layer.combined_hash)
+    }
+
+    /// Checks that each layer combines the hash below it with its sibling.
+    pub fn is_valid(&self) -> bool {
+        let mut hash = TreeHash::from(self.node_hash());
+
+        for layer in &self.layers {
+            let other_hash = TreeHash::from(layer.other_hash);
+
+            hash = match layer.other_hash_side {
+                HashSide::Left => tree_hash_pair(other_hash, hash),
+                HashSide::Right => tree_hash_pair(hash, other_hash),
+            };
+
+            if hash != layer.combined_hash.into() {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+impl<M> DataStore<M>
+where
+    M: ToClvm<Allocator> + MetadataWithRootHash,
+{
+    /// Checks data served by a `DataLayer` mirror against the root hash of this store,
+    /// without running a `DataLayer` service.
+    ///
+    /// Returns `true` if the claimed root hash is the one committed to on-chain, and every proof is valid for it.
+    /// This only verifies the data against this [`DataStore`], so the caller must make sure it has synced the
+    /// latest coin of the singleton, and that the coin is unspent.
+    pub fn verify_served_data(&self, root_hash: Bytes32, proofs: &[ProofOfInclusion]) -> bool {
+        if self.info.metadata.root_hash() != root_hash {
+            return false;
+        }
+
+        proofs
+            .iter()
+            .all(|proof| proof.root_hash() == root_hash && proof.is_valid())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::standard::StandardArgs;
+    use chia_sdk_test::{test_secret_keys, Simulator};
+
+    use crate::{DataStoreMetadata, Launcher, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_verify_served_data() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let sk = test_secret_keys(1)?.remove(0);
+        let pk = sk.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(pk).into();
+        let coin = sim.new_coin(puzzle_hash, 1);
+
+        let leaf = |key: &[u8], value: &[u8]| {
+            (Bytes::new(key.to_vec()), Bytes::new(value.to_vec())).tree_hash()
+        };
+
+        let first = leaf(b"a", b"1");
+        let second = leaf(b"b", b"2");
+        let third = leaf(b"c", b"3");
+        let left = tree_hash_pair(first, second);
+        let root = tree_hash_pair(left, third);
+
+        let first_proof = ProofOfInclusion {
+            key: Bytes::new(b"a".to_vec()),
+            value: Bytes::new(b"1".to_vec()),
+            layers: vec![
+                ProofOfInclusionLayer {
+                    other_hash_side: HashSide::Right,
+                    other_hash: second.into(),
+                    combined_hash: left.into(),
+                },
+                ProofOfInclusionLayer {
+                    other_hash_side: HashSide::Right,
+                    other_hash: third.into(),
+                    combined_hash: root.into(),
+                },
+            ],
+        };
+
+        let third_proof = ProofOfInclusion {
+            key: Bytes::new(b"c".to_vec()),
+            value: Bytes::new(b"3".to_vec()),
+            layers: vec![ProofOfInclusionLayer {
+                other_hash_side: HashSide::Left,
+                other_hash: left.into(),
+                combined_hash: root.into(),
+            }],
+        };
+
+        let (launch_singleton, datastore) = Launcher::new(coin.coin_id(), 1).mint_datastore(
+            ctx,
+            DataStoreMetadata::root_hash_only(root.into()),
+            puzzle_hash.into(),
+            vec![],
+        )?;
+        StandardLayer::new(pk).spend(ctx, coin, launch_singleton)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let proofs = [first_proof, third_proof];
+        assert!(datastore.verify_served_data(root.into(), &proofs));
+
+        // The root hash has to match the one on-chain.
+        assert!(!datastore.verify_served_data(left.into(), &proofs[..0]));
+
+        // The served value has to match the one that was committed to.
+        let mut tampered = proofs[0].clone();
+        tampered.value = Bytes::new(b"2".to_vec());
+        assert!(!tampered.is_valid());
+        assert!(!datastore.verify_served_data(root.into(), &[tampered]));
+
+        // A valid proof for a different root doesn't count.
+        let mut partial = proofs[0].clone();
+        partial.layers.pop();
+        assert!(partial.is_valid());
+        assert!(!datastore.verify_served_data(root.into(), &[partial]));
+
+        Ok(())
+    }
+}
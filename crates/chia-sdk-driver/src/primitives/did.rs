@@ -8,6 +8,7 @@ use chia_sdk_types::{run_puzzle, Condition, Conditions};
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{tree_hash, ToTreeHash};
 use clvmr::{Allocator, NodePtr};
+use tracing::instrument;
 
 use crate::{
     DidLayer, DriverError, Layer, Puzzle, SingletonLayer, Spend, SpendContext, SpendWithConditions,
@@ -15,8 +16,10 @@ use crate::{
 
 mod did_info;
 mod did_launcher;
+mod did_profile;
 
 pub use did_info::*;
+pub use did_profile::*;
 
 #[must_use]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -84,6 +87,7 @@ where
     M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
 {
     /// Creates a coin spend for this DID.
+    #[instrument(skip_all, fields(launcher_id = %self.info.launcher_id, coin_id = %self.coin.coin_id()))]
     pub fn spend(&self, ctx: &mut SpendContext, inner_spend: Spend) -> Result<(), DriverError> {
         let layers = self.info.clone().into_layers(inner_spend.puzzle);
 
@@ -348,6 +352,57 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_create_did_with_backups() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let backup_ids = [Bytes32::new([1; 32]), Bytes32::new([2; 32])];
+
+        assert!(matches!(
+            Launcher::new(coin.coin_id(), 1).create_did_with_backups(ctx, &backup_ids, 3, (), &p2),
+            Err(DriverError::TooManyVerifications(3, 2))
+        ));
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_did_with_backups(
+            ctx,
+            &backup_ids,
+            2,
+            (),
+            &p2,
+        )?;
+        p2.spend(ctx, coin, create_did)?;
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        // The recovery settings can be read back from the parent spend.
+        let parent_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == did.coin.parent_coin_info)
+            .expect("missing parent spend");
+        let parent_puzzle = parent_spend.puzzle_reveal.to_clvm(&mut ctx.allocator)?;
+        let parent_puzzle = Puzzle::parse(&ctx.allocator, parent_puzzle);
+        let parent_solution = parent_spend.solution.to_clvm(&mut ctx.allocator)?;
+
+        let parsed = Did::<()>::parse_child(
+            &mut ctx.allocator,
+            parent_spend.coin,
+            parent_puzzle,
+            parent_solution,
+            did.coin,
+        )?
+        .expect("not a DID");
+
+        assert_eq!(parsed, did);
+        assert_eq!(parsed.info.num_verifications_required, 2);
+        assert!(parsed.info.has_backup_ids(&backup_ids));
+        assert!(!parsed.info.has_backup_ids(&backup_ids[..1]));
+
+        Ok(())
+    }
+
     #[test]
     fn test_transfer_did() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
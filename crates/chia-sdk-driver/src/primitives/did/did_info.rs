@@ -8,6 +8,12 @@ use crate::{DidLayer, DriverError, Layer, Puzzle, SingletonLayer};
 
 pub type StandardDidLayers<M, I> = SingletonLayer<DidLayer<M, I>>;
 
+/// Computes the recovery list hash of a DID from the launcher ids of its backup DIDs.
+/// This is the tree hash of the list, which is the same value that the reference wallet curries in.
+pub fn did_recovery_list_hash(backup_ids: &[Bytes32]) -> Bytes32 {
+    backup_ids.to_vec().tree_hash().into()
+}
+
 #[must_use]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct DidInfo<M> {
@@ -99,6 +105,13 @@ impl<M> DidInfo<M> {
         }
     }
 
+    /// Whether the recovery list of this DID consists of exactly the given backup DID launcher ids.
+    /// Only the hash of the recovery list is curried into the puzzle, so the ids can't be parsed directly,
+    /// and must be checked against it instead.
+    pub fn has_backup_ids(&self, backup_ids: &[Bytes32]) -> bool {
+        self.recovery_list_hash == Some(did_recovery_list_hash(backup_ids))
+    }
+
     pub fn inner_puzzle_hash(&self) -> TreeHash
     where
         M: ToTreeHash,
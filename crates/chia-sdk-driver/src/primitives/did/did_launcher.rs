@@ -7,7 +7,7 @@ use clvmr::Allocator;
 
 use crate::{DriverError, Launcher, SpendContext, SpendWithConditions};
 
-use super::{Did, DidInfo};
+use super::{did_recovery_list_hash, Did, DidInfo};
 
 impl Launcher {
     pub fn create_eve_did<M>(
@@ -68,6 +68,36 @@ impl Launcher {
         Ok((create_eve, did))
     }
 
+    /// Creates a DID that can be recovered by the given backup DIDs, once the required number of them
+    /// have approved the recovery.
+    pub fn create_did_with_backups<M, I>(
+        self,
+        ctx: &mut SpendContext,
+        backup_ids: &[Bytes32],
+        num_verifications_required: u64,
+        metadata: M,
+        inner: &I,
+    ) -> Result<(Conditions, Did<M>), DriverError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+        I: SpendWithConditions + ToTreeHash,
+    {
+        if num_verifications_required > backup_ids.len() as u64 {
+            return Err(DriverError::TooManyVerifications(
+                num_verifications_required,
+                backup_ids.len(),
+            ));
+        }
+
+        self.create_did(
+            ctx,
+            Some(did_recovery_list_hash(backup_ids)),
+            num_verifications_required,
+            metadata,
+            inner,
+        )
+    }
+
     pub fn create_simple_did<I>(
         self,
         ctx: &mut SpendContext,
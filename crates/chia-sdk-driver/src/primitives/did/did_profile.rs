@@ -0,0 +1,387 @@
+use chia_protocol::{Bytes, Program};
+use chia_sdk_types::Conditions;
+use clvm_traits::{FromClvm, FromClvmError, ToClvm, ToClvmError};
+use clvm_utils::{tree_hash_atom, tree_hash_from_bytes, tree_hash_pair, ToTreeHash, TreeHash};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, SpendContext, SpendWithConditions};
+
+use super::Did;
+
+/// The key used for the display name of a DID.
+pub const DID_PROFILE_NAME_KEY: &str = "name";
+
+/// The key used for the avatar URI of a DID.
+pub const DID_PROFILE_AVATAR_KEY: &str = "avatar";
+
+/// A typed view of the key value list that is commonly stored in DID metadata.
+///
+/// Entries are kept in their original order, and values are stored as serialized CLVM,
+/// so unknown entries (including nested lists, raw bytes, and numbers) round-trip exactly.
+///
+/// If the same key appears more than once, [`DidProfile::get`] returns the first value,
+/// while [`DidProfile::insert`] and [`DidProfile::remove`] act on every matching entry,
+/// so that no stale duplicates are left behind after an update.
+///
+/// Values are checked to be valid serialized CLVM when they are added, so that the
+/// tree hash of the profile can always be computed.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DidProfile {
+    entries: Vec<(Bytes, Program, TreeHash)>,
+}
+
+impl DidProfile {
+    /// Creates an empty profile.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the value for a given key, if it's present.
+    pub fn get(&self, key: &str) -> Option<&Program> {
+        self.entries
+            .iter()
+            .find(|(k, ..)| k.as_ref() == key.as_bytes())
+            .map(|(_, value, _)| value)
+    }
+
+    /// Returns the value for a given key, if it's present and is a string.
+    pub fn get_string(&self, key: &str) -> Option<String> {
+        program_to_string(self.get(key)?)
+    }
+
+    /// Sets the value for a given key, replacing the first matching entry in place
+    /// and removing any duplicates. Returns the previous value, if there was one.
+    ///
+    /// Fails if the value isn't valid serialized CLVM.
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: Program,
+    ) -> Result<Option<Program>, DriverError> {
+        let key = Bytes::new(key.into().into_bytes());
+        let hash = tree_hash_from_bytes(value.as_ref())?;
+
+        let Some(index) = self.entries.iter().position(|(k, ..)| *k == key) else {
+            self.entries.push((key, value, hash));
+            return Ok(None);
+        };
+
+        let previous = std::mem::replace(&mut self.entries[index].1, value);
+        self.entries[index].2 = hash;
+
+        let mut position = 0;
+        self.entries.retain(|(k, ..)| {
+            let keep = position <= index || *k != key;
+            position += 1;
+            keep
+        });
+
+        Ok(Some(previous))
+    }
+
+    /// Sets the value for a given key to a string.
+    /// Returns the previous value, if there was one.
+    pub fn insert_string(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<String>,
+    ) -> Result<Option<Program>, DriverError> {
+        self.insert(key, string_to_program(&value.into())?)
+    }
+
+    /// Removes every entry with the given key, returning the first value if it was present.
+    pub fn remove(&mut self, key: &str) -> Option<Program> {
+        let index = self
+            .entries
+            .iter()
+            .position(|(k, ..)| k.as_ref() == key.as_bytes())?;
+
+        let previous = self.entries.remove(index).1;
+        self.entries.retain(|(k, ..)| k.as_ref() != key.as_bytes());
+
+        Some(previous)
+    }
+
+    /// Iterates over all of the entries in the profile, including custom fields.
+    pub fn iter(&self) -> impl Iterator<Item = (&Bytes, &Program)> {
+        self.entries.iter().map(|(key, value, _)| (key, value))
+    }
+
+    /// Returns the number of entries in the profile.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the profile has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the display name, if it's set to a string.
+    pub fn name(&self) -> Option<String> {
+        self.get_string(DID_PROFILE_NAME_KEY)
+    }
+
+    /// Sets the display name. Use [`DidProfile::remove`] with [`DID_PROFILE_NAME_KEY`] to clear it.
+    pub fn set_name(&mut self, name: impl Into<String>) -> Result<(), DriverError> {
+        self.insert_string(DID_PROFILE_NAME_KEY, name)?;
+        Ok(())
+    }
+
+    /// Returns the avatar URI, if it's set to a string.
+    pub fn avatar(&self) -> Option<String> {
+        self.get_string(DID_PROFILE_AVATAR_KEY)
+    }
+
+    /// Sets the avatar URI. Use [`DidProfile::remove`] with [`DID_PROFILE_AVATAR_KEY`] to clear it.
+    pub fn set_avatar(&mut self, avatar: impl Into<String>) -> Result<(), DriverError> {
+        self.insert_string(DID_PROFILE_AVATAR_KEY, avatar)?;
+        Ok(())
+    }
+
+    /// Creates a profile from a list of entries, in order.
+    ///
+    /// Fails if any of the values isn't valid serialized CLVM.
+    pub fn from_entries(
+        entries: impl IntoIterator<Item = (Bytes, Program)>,
+    ) -> Result<Self, DriverError> {
+        let entries = entries
+            .into_iter()
+            .map(|(key, value)| {
+                let hash = tree_hash_from_bytes(value.as_ref())?;
+                Ok::<_, DriverError>((key, value, hash))
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self { entries })
+    }
+}
+
+impl FromClvm<Allocator> for DidProfile {
+    fn from_clvm(decoder: &Allocator, node: NodePtr) -> Result<Self, FromClvmError> {
+        let entries = Vec::<(Bytes, Program)>::from_clvm(decoder, node)?;
+        Self::from_entries(entries).map_err(|error| FromClvmError::Custom(error.to_string()))
+    }
+}
+
+impl ToClvm<Allocator> for DidProfile {
+    fn to_clvm(&self, encoder: &mut Allocator) -> Result<NodePtr, ToClvmError> {
+        self.iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect::<Vec<_>>()
+            .to_clvm(encoder)
+    }
+}
+
+impl ToTreeHash for DidProfile {
+    fn tree_hash(&self) -> TreeHash {
+        self.entries
+            .iter()
+            .rev()
+            .fold(tree_hash_atom(&[]), |rest, (key, _, value)| {
+                tree_hash_pair(tree_hash_pair(tree_hash_atom(key), *value), rest)
+            })
+    }
+}
+
+fn string_to_program(value: &str) -> Result<Program, DriverError> {
+    let mut allocator = Allocator::new();
+    let ptr = value.to_clvm(&mut allocator)?;
+    Ok(Program::from_clvm(&allocator, ptr)?)
+}
+
+fn program_to_string(program: &Program) -> Option<String> {
+    let mut allocator = Allocator::new();
+    let ptr = program.to_clvm(&mut allocator).ok()?;
+    String::from_clvm(&allocator, ptr).ok()
+}
+
+impl Did<DidProfile> {
+    /// Recreates this DID with an updated profile.
+    ///
+    /// The closure receives a copy of the current profile, so any entries it doesn't change are kept.
+    pub fn update_profile<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        update: impl FnOnce(&mut DidProfile) -> Result<(), DriverError>,
+        extra_conditions: Conditions,
+    ) -> Result<Did<DidProfile>, DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let mut profile = self.info.metadata.clone();
+        update(&mut profile)?;
+        self.update_with_metadata(ctx, inner, profile, extra_conditions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use clvm_traits::clvm_list;
+    use clvm_utils::tree_hash;
+
+    use crate::{Launcher, Puzzle, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_update_did_profile() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let mut profile = DidProfile::new();
+        profile.set_name("Alice")?;
+        profile.insert_string("website", "example.com")?;
+
+        let (create_did, did) =
+            Launcher::new(coin.coin_id(), 1).create_did(ctx, None, 1, profile, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let parent_did = did.update_profile(
+            ctx,
+            &p2,
+            |profile| {
+                profile.set_name("Bob")?;
+                profile.set_avatar("example.com/avatar.png")
+            },
+            Conditions::new(),
+        )?;
+
+        assert_eq!(parent_did.info.metadata.name().as_deref(), Some("Bob"));
+        assert_eq!(
+            parent_did.info.metadata.avatar().as_deref(),
+            Some("example.com/avatar.png")
+        );
+        assert_eq!(
+            parent_did.info.metadata.get_string("website").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(parent_did.info.metadata.len(), 3);
+
+        let parent_coin = parent_did.coin;
+        let expected_did = parent_did.update(ctx, &p2, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let mut allocator = Allocator::new();
+
+        let puzzle_reveal = sim
+            .puzzle_reveal(parent_coin.coin_id())
+            .expect("missing puzzle")
+            .to_clvm(&mut allocator)?;
+
+        let solution = sim
+            .solution(parent_coin.coin_id())
+            .expect("missing solution")
+            .to_clvm(&mut allocator)?;
+
+        let puzzle = Puzzle::parse(&allocator, puzzle_reveal);
+
+        let did = Did::<DidProfile>::parse_child(
+            &mut allocator,
+            parent_coin,
+            puzzle,
+            solution,
+            expected_did.coin,
+        )?
+        .expect("could not parse did");
+
+        assert_eq!(did, expected_did);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_preserves_unknown_entries() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let original = clvm_list!(
+            ("list", clvm_list!(1, 2, clvm_list!(3))),
+            ("number", 42),
+            (Bytes::new(vec![0xff, 0x00]), Bytes::new(vec![0x80, 0x01]))
+        )
+        .to_clvm(&mut ctx.allocator)?;
+        let original_hash = tree_hash(&ctx.allocator, original);
+        let profile = DidProfile::from_clvm(&ctx.allocator, original)?;
+
+        assert_eq!(profile.tree_hash(), original_hash);
+        assert_eq!(profile.get_string("list"), None);
+        assert_eq!(profile.len(), 3);
+
+        let (create_did, did) =
+            Launcher::new(coin.coin_id(), 1).create_did(ctx, None, 1, profile, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let did = did.update_profile(
+            ctx,
+            &p2,
+            |profile| profile.set_name("Bob"),
+            Conditions::new(),
+        )?;
+        let did = did.update_profile(
+            ctx,
+            &p2,
+            |profile| {
+                profile.remove(DID_PROFILE_NAME_KEY);
+                Ok(())
+            },
+            Conditions::new(),
+        )?;
+
+        let ptr = ctx.alloc(&did.info.metadata)?;
+        assert_eq!(ctx.tree_hash(ptr), original_hash);
+        assert_eq!(did.info.metadata.tree_hash(), original_hash);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_did_profile_duplicate_keys() -> anyhow::Result<()> {
+        let value = |value: &str| string_to_program(value);
+
+        let mut profile = DidProfile::from_entries([
+            (Bytes::new(b"custom".to_vec()), value("first")?),
+            (Bytes::new(b"other".to_vec()), value("kept")?),
+            (Bytes::new(b"custom".to_vec()), value("second")?),
+        ])?;
+
+        assert_eq!(profile.get_string("custom").as_deref(), Some("first"));
+        assert_eq!(
+            profile.insert_string("custom", "new")?,
+            Some(value("first")?)
+        );
+        assert_eq!(profile.len(), 2);
+        assert_eq!(profile.get_string("custom").as_deref(), Some("new"));
+
+        profile.set_name("Name")?;
+        assert_eq!(profile.remove(DID_PROFILE_NAME_KEY), Some(value("Name")?));
+        assert_eq!(profile.name(), None);
+
+        assert_eq!(profile.remove("custom"), Some(value("new")?));
+        assert_eq!(profile.get_string("other").as_deref(), Some("kept"));
+        assert_eq!(profile.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_did_profile_rejects_invalid_program() {
+        let mut profile = DidProfile::new();
+        assert!(profile.insert("custom", Program::from(vec![0xff])).is_err());
+        assert!(DidProfile::from_entries([(
+            Bytes::new(b"custom".to_vec()),
+            Program::from(vec![0xff])
+        )])
+        .is_err());
+        assert!(profile.is_empty());
+    }
+}
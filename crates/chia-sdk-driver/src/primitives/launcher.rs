@@ -1,5 +1,12 @@
 #![allow(clippy::missing_const_for_fn)]
 
+use std::{
+    num::NonZeroUsize,
+    ops::Range,
+    sync::atomic::{AtomicU64, Ordering},
+    thread,
+};
+
 use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_puzzles::singleton::{
     LauncherSolution, SingletonArgs, SINGLETON_LAUNCHER_PUZZLE, SINGLETON_LAUNCHER_PUZZLE_HASH,
@@ -7,9 +14,13 @@ use chia_puzzles::singleton::{
 use chia_sdk_types::{announcement_id, Conditions};
 use clvm_traits::ToClvm;
 use clvmr::Allocator;
+use tracing::instrument;
 
 use crate::{DriverError, SpendContext};
 
+/// The exclusive upper bound of the amounts searched by [`Launcher::create_early_vanity`], which is a millionth of an XCH.
+pub const MAX_VANITY_LAUNCHER_AMOUNT: u64 = 1_000_000;
+
 /// A singleton launcher is a coin that is spent within the same block to create a singleton.
 /// The first coin that is created is known as an "eve" singleton.
 /// The [`Launcher`] type allows you to get the launcher id before committing to creating the singleton.
@@ -115,6 +126,69 @@ impl Launcher {
         )
     }
 
+    /// Searches the odd amounts in the given range for a launcher id that matches the predicate,
+    /// then creates the launcher early in the same way as [`Launcher::create_early`].
+    /// Only odd amounts are tried, so that the launcher can be spent into a singleton of the same amount.
+    ///
+    /// The launcher id can only be varied by the amount, since the parent and puzzle hash are fixed.
+    /// The amount that is found is paid by the parent coin and stays locked in the singleton,
+    /// so the range can't end above [`MAX_VANITY_LAUNCHER_AMOUNT`] mojos.
+    ///
+    /// The search is split across the available threads, and the smallest matching amount is used.
+    /// Each attempt is a single hash, so matching a one byte prefix takes around 256 attempts,
+    /// and each additional byte multiplies that by 256. With the bounded range, a prefix of two bytes is
+    /// about as much as can be expected to match. Returns [`None`] if nothing in the range matches.
+    pub fn create_early_vanity(
+        parent_coin_id: Bytes32,
+        amounts: Range<u64>,
+        predicate: impl Fn(Bytes32) -> bool + Sync,
+    ) -> Result<Option<(Conditions, Self)>, DriverError> {
+        if amounts.end > MAX_VANITY_LAUNCHER_AMOUNT {
+            return Err(DriverError::VanityAmountTooLarge(amounts.end));
+        }
+
+        let first = amounts.start | 1;
+        let count = amounts.end.saturating_sub(first).div_ceil(2);
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get) as u64;
+        let chunk_size = count.div_ceil(threads).max(1);
+        let best = AtomicU64::new(u64::MAX);
+
+        thread::scope(|scope| {
+            for chunk in 0..count.div_ceil(chunk_size) {
+                let (predicate, best) = (&predicate, &best);
+
+                scope.spawn(move || {
+                    let start = chunk * chunk_size;
+                    let end = (start + chunk_size).min(count);
+
+                    for amount in (start..end).map(|index| first + index * 2) {
+                        // A match was already found at a smaller amount by another thread.
+                        if amount >= best.load(Ordering::Relaxed) {
+                            return;
+                        }
+
+                        let launcher_id = Coin::new(
+                            parent_coin_id,
+                            SINGLETON_LAUNCHER_PUZZLE_HASH.into(),
+                            amount,
+                        )
+                        .coin_id();
+
+                        if predicate(launcher_id) {
+                            best.fetch_min(amount, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                });
+            }
+        });
+
+        Ok(match best.into_inner() {
+            u64::MAX => None,
+            amount => Some(Self::create_early(parent_coin_id, amount)),
+        })
+    }
+
     /// Changes the singleton amount to differ from the launcher amount.
     /// This is useful in situations where the launcher amount is 0 and the singleton amount is 1, for example.
     pub fn with_singleton_amount(mut self, singleton_amount: u64) -> Self {
@@ -129,6 +203,9 @@ impl Launcher {
 
     /// Spends the launcher coin to create the eve singleton.
     /// Includes an optional metadata value that is traditionally a list of key value pairs.
+    ///
+    /// The singleton amount must be odd, since the singleton top layer only recreates odd outputs.
+    #[instrument(skip_all, fields(launcher_id = %self.coin.coin_id()))]
     pub fn spend<T>(
         self,
         ctx: &mut SpendContext,
@@ -138,6 +215,10 @@ impl Launcher {
     where
         T: ToClvm<Allocator>,
     {
+        if self.singleton_amount % 2 == 0 {
+            return Err(DriverError::EvenSingletonAmount(self.singleton_amount));
+        }
+
         let singleton_puzzle_hash =
             SingletonArgs::curry_tree_hash(self.coin.coin_id(), singleton_inner_puzzle_hash.into())
                 .into();
@@ -218,4 +299,66 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_vanity_launcher() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(10_000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_launcher, launcher) =
+            Launcher::create_early_vanity(coin.coin_id(), 0..10_000, |launcher_id| {
+                launcher_id[0] == 0
+            })?
+            .expect("no vanity launcher found");
+
+        let launcher_id = launcher.coin().coin_id();
+        assert_eq!(launcher_id[0], 0);
+        assert_eq!(launcher.coin().amount % 2, 1);
+
+        // It's the first match, so no smaller amounts could have been used.
+        for amount in (1..launcher.coin().amount).step_by(2) {
+            assert_ne!(Launcher::new(coin.coin_id(), amount).coin().coin_id()[0], 0);
+        }
+
+        let ctx = &mut SpendContext::new();
+        let (create_did, did) = launcher.create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_launcher.extend(create_did))?;
+        assert_eq!(did.info.launcher_id, launcher_id);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(Launcher::create_early_vanity(coin.coin_id(), 0..1, |_| true)?.is_none());
+        assert!(Launcher::create_early_vanity(coin.coin_id(), 0..100, |_| false)?.is_none());
+
+        // The amount is paid into the singleton, so the search is bounded.
+        assert!(Launcher::create_early_vanity(
+            coin.coin_id(),
+            0..MAX_VANITY_LAUNCHER_AMOUNT,
+            |_| false
+        )?
+        .is_none());
+        assert!(matches!(
+            Launcher::create_early_vanity(
+                coin.coin_id(),
+                0..MAX_VANITY_LAUNCHER_AMOUNT + 1,
+                |_| true
+            ),
+            Err(DriverError::VanityAmountTooLarge(1_000_001))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_even_singleton_amount() {
+        let ctx = &mut SpendContext::new();
+
+        let launcher = Launcher::new(Bytes32::default(), 2);
+        assert!(matches!(
+            launcher.spend(ctx, Bytes32::default(), ()),
+            Err(DriverError::EvenSingletonAmount(2))
+        ));
+        assert_eq!(ctx.take().len(), 0);
+    }
 }
@@ -8,24 +8,37 @@ use chia_sdk_types::{run_puzzle, Condition, Conditions, NewMetadataOutput, Trans
 use clvm_traits::{clvm_list, FromClvm, ToClvm};
 use clvm_utils::{tree_hash, ToTreeHash};
 use clvmr::{sha2::Sha256, Allocator, NodePtr};
+use tracing::instrument;
 
 use crate::{
-    DriverError, Layer, NftOwnershipLayer, NftStateLayer, Puzzle, RoyaltyTransferLayer,
+    Did, DriverError, Layer, NftOwnershipLayer, NftStateLayer, Puzzle, RoyaltyTransferLayer,
     SingletonLayer, Spend, SpendContext, SpendWithConditions,
 };
 
 mod did_owner;
 mod metadata_update;
+mod nft_batch_transfer;
+mod nft_collection_audit;
+mod nft_collection_mint;
 mod nft_info;
 mod nft_launcher;
 mod nft_mint;
+mod nft_provenance;
 
 pub use did_owner::*;
 pub use metadata_update::*;
+pub use nft_batch_transfer::*;
+pub use nft_collection_audit::*;
+pub use nft_collection_mint::*;
 pub use nft_info::*;
 pub use nft_mint::*;
+pub use nft_provenance::*;
 
 /// Everything that is required to spend an NFT coin.
+///
+/// Custom metadata types should derive [`ToClvm`] and [`FromClvm`], rather than implementing them
+/// for [`Allocator`] only. The derived implementation works with any encoder, so [`ToTreeHash`] is
+/// provided by a blanket implementation, and the hash always matches the allocated metadata.
 #[must_use]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Nft<M> {
@@ -101,6 +114,7 @@ where
     M: ToClvm<Allocator> + FromClvm<Allocator> + Clone,
 {
     /// Creates a coin spend for this NFT.
+    #[instrument(skip_all, fields(launcher_id = %self.info.launcher_id, coin_id = %self.coin.coin_id()))]
     pub fn spend(&self, ctx: &mut SpendContext, inner_spend: Spend) -> Result<(), DriverError> {
         let layers = self.info.clone().into_layers(inner_spend.puzzle);
 
@@ -218,6 +232,9 @@ where
     /// Transfers this NFT to a new p2 puzzle hash and updates the DID owner.
     /// Returns a list of conditions to be used in the DID spend.
     ///
+    /// If there is a new owner, its DID must also announce the NFT's launcher id in the same spend bundle,
+    /// which isn't included in the returned conditions. [`Self::assign_did`] does this for you.
+    ///
     /// Note: This does not update the metadata. If you update the metadata manually, the child will be incorrect.
     ///
     /// You can construct a spend manually with [`Self::spend`] or [`Self::spend_with`] if you need to update metadata
@@ -267,6 +284,57 @@ where
 
         Ok((did_conditions, child))
     }
+
+    /// Assigns this NFT to a DID, without changing its p2 puzzle hash.
+    /// Both the NFT and the DID are spent, so that the DID can authorize the assignment.
+    pub fn assign_did<I, D, J>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        did: Did<D>,
+        did_inner: &J,
+        extra_conditions: Conditions,
+    ) -> Result<(Did<D>, Nft<M>), DriverError>
+    where
+        M: ToTreeHash,
+        I: SpendWithConditions,
+        D: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+        J: SpendWithConditions,
+    {
+        let p2_puzzle_hash = self.info.p2_puzzle_hash;
+        let (did_conditions, nft) = self.transfer_to_did(
+            ctx,
+            inner,
+            p2_puzzle_hash,
+            Some(DidOwner::from_did_info(&did.info)),
+            extra_conditions,
+        )?;
+
+        // The new owner has to authorize the assignment by announcing the NFT's launcher id.
+        let did_conditions =
+            did_conditions.create_puzzle_announcement(nft.info.launcher_id.to_vec().into());
+
+        let did = did.update(ctx, did_inner, did_conditions)?;
+
+        Ok((did, nft))
+    }
+
+    /// Removes the DID owner from this NFT, without changing its p2 puzzle hash.
+    /// Returns a list of conditions that can be used in a spend of the previous owner DID,
+    /// although a DID spend isn't required to remove the owner.
+    pub fn remove_did<I>(
+        self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        extra_conditions: Conditions,
+    ) -> Result<(Conditions, Nft<M>), DriverError>
+    where
+        M: ToTreeHash,
+        I: SpendWithConditions,
+    {
+        let p2_puzzle_hash = self.info.p2_puzzle_hash;
+        self.transfer_to_did(ctx, inner, p2_puzzle_hash, None, extra_conditions)
+    }
 }
 
 impl<M> Nft<M>
@@ -396,6 +464,41 @@ mod tests {
     use chia_puzzles::nft::NftMetadata;
     use chia_sdk_test::Simulator;
 
+    // Deriving `ToClvm` is generic over the encoder, so `ToTreeHash` is implemented automatically.
+    #[derive(Debug, Clone, PartialEq, Eq, ToClvm, FromClvm)]
+    #[clvm(list)]
+    struct CustomMetadata {
+        name: String,
+        edition: u32,
+    }
+
+    #[test]
+    fn test_nft_custom_metadata() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let metadata = CustomMetadata {
+            name: "Custom".to_string(),
+            edition: 1,
+        };
+        let metadata_ptr = ctx.alloc(&metadata)?;
+        assert_eq!(metadata.tree_hash(), ctx.tree_hash(metadata_ptr));
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1)
+            .mint_nft(ctx, NftMint::new(metadata.clone(), puzzle_hash, 0, None))?;
+        p2.spend(ctx, coin, mint_nft)?;
+
+        let nft = nft.transfer(ctx, &p2, puzzle_hash, Conditions::new())?;
+        assert_eq!(nft.info.metadata, metadata);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
     #[test]
     fn test_nft_transfer() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
@@ -469,6 +572,50 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_assign_and_remove_did() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(5)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, first_did) =
+            Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        let (create_second_did, second_did) =
+            Launcher::new(coin.coin_id(), 3).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did.extend(create_second_did))?;
+
+        let mint = NftMint::new(
+            NftMetadata::default(),
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&first_did.info)),
+        );
+
+        let (mint_nft, nft) = IntermediateLauncher::new(first_did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(ctx, mint)?;
+        let _first_did = first_did.update(ctx, &p2, mint_nft)?;
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        // The assignment happens in a separate bundle, so only the new DID can authorize it.
+        let (_second_did, nft) = nft.assign_did(ctx, &p2, second_did, &p2, Conditions::new())?;
+        assert_eq!(nft.info.current_owner, Some(second_did.info.launcher_id));
+        assert_eq!(nft.info.p2_puzzle_hash, puzzle_hash);
+
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let (_did_conditions, nft) = nft.remove_did(ctx, &p2, Conditions::new())?;
+        assert_eq!(nft.info.current_owner, None);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+        assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
     #[test]
     fn test_nft_metadata_update() -> anyhow::Result<()> {
         let mut sim = Simulator::new();
@@ -0,0 +1,244 @@
+use chia_protocol::Bytes32;
+use chia_sdk_types::Conditions;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::ToTreeHash;
+use clvmr::Allocator;
+
+use crate::{BatchItem, BatchTracker, DriverError, SpendContext, SpendWithConditions};
+
+use super::Nft;
+
+/// An NFT to transfer in a [`NftBatchTransfer`], along with its recipient.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftTransfer<M> {
+    pub nft: Nft<M>,
+    pub p2_puzzle_hash: Bytes32,
+}
+
+impl<M> NftTransfer<M> {
+    pub fn new(nft: Nft<M>, p2_puzzle_hash: Bytes32) -> Self {
+        Self {
+            nft,
+            p2_puzzle_hash,
+        }
+    }
+}
+
+impl<M> BatchItem for NftTransfer<M> {
+    /// Covers the singleton, state, and ownership layers, as well as the transfer program.
+    const COST_ESTIMATE: u64 = 150_000_000;
+}
+
+/// Plans and tracks the transfer of many NFTs to their recipients, such as marketplace sales or giveaways.
+///
+/// The NFTs are split into batches, each of which is a single spend bundle, so that no bundle
+/// exceeds the cost limit. Each NFT is transferred on its own, and the DID owner of any NFT
+/// that has one is reset, since the recipient doesn't control the DID. Each item is confirmed
+/// by the id of the NFT's new coin.
+pub type NftBatchTransfer<M> = BatchTracker<NftTransfer<M>>;
+
+impl<M> BatchTracker<NftTransfer<M>>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+{
+    /// Transfers the next batch of pending NFTs, and marks them as submitted.
+    /// Returns the transferred NFTs, or [`None`] if there's nothing left to transfer.
+    ///
+    /// Every NFT must be owned by the inner puzzle. The extra conditions are output by the first
+    /// NFT in the batch, which is where a fee can be reserved.
+    pub fn transfer_batch<I>(
+        &mut self,
+        ctx: &mut SpendContext,
+        inner: &I,
+        extra_conditions: Conditions,
+    ) -> Result<Option<Vec<Nft<M>>>, DriverError>
+    where
+        I: SpendWithConditions,
+    {
+        let indices = self.next_batch(|_| true);
+
+        if indices.is_empty() {
+            return Ok(None);
+        }
+
+        let mut extra_conditions = Some(extra_conditions);
+        let mut nfts = Vec::with_capacity(indices.len());
+
+        for &index in &indices {
+            let NftTransfer {
+                nft,
+                p2_puzzle_hash,
+            } = self.items()[index].item.clone();
+            let conditions = extra_conditions.take().unwrap_or_default();
+
+            // The DID owner has to be reset with a transfer condition, but that doesn't require a DID spend.
+            let nft = if nft.info.current_owner.is_some() {
+                nft.transfer_to_did(ctx, inner, p2_puzzle_hash, None, conditions)?
+                    .1
+            } else {
+                nft.transfer(ctx, inner, p2_puzzle_hash, conditions)?
+            };
+
+            nfts.push(nft);
+        }
+
+        for (&index, nft) in indices.iter().zip(&nfts) {
+            self.submit(index, nft.coin.coin_id());
+        }
+
+        Ok(Some(nfts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::SpendBundle;
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::TESTNET11_CONSTANTS;
+
+    use crate::{DidOwner, Launcher, NftCollectionMint, NftMint, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_batch_transfer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        // Only some of the NFTs are assigned to the DID.
+        let owner = DidOwner::from_did_info(&did.info);
+        let manifest = (0..3)
+            .map(|i| {
+                let owner = if i == 1 { None } else { Some(owner) };
+                NftMint::new(NftMetadata::default(), puzzle_hash, 300, owner)
+            })
+            .collect();
+
+        let nfts = NftCollectionMint::new(manifest)
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .expect("missing batch")
+            .nfts;
+
+        let funding = sim.new_coin(puzzle_hash, 3);
+        p2.spend(ctx, funding, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let transfers = nfts
+            .into_iter()
+            .zip(1..)
+            .map(|(nft, i)| NftTransfer::new(nft, Bytes32::new([i; 32])))
+            .collect();
+        let mut batch_transfer = NftBatchTransfer::new(transfers).with_batch_size(2);
+
+        let mut batches = 0;
+
+        while let Some(nfts) = batch_transfer.transfer_batch(ctx, &p2, Conditions::new())? {
+            batches += 1;
+            assert_eq!(batch_transfer.submitted_count(), nfts.len());
+
+            sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+            for nft in nfts {
+                assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+                assert_eq!(nft.info.current_owner, None);
+                assert!(batch_transfer.confirm(nft.coin.coin_id()));
+            }
+        }
+
+        assert_eq!(batches, 2);
+        assert!(batch_transfer.is_complete());
+
+        for (item, i) in batch_transfer.items().iter().zip(1..) {
+            assert_eq!(item.item.p2_puzzle_hash, Bytes32::new([i; 32]));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_batch_transfer_reset() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 0, None),
+        )?;
+        p2.spend(ctx, coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let recipient = Bytes32::new([1; 32]);
+        let mut batch_transfer = NftBatchTransfer::new(vec![NftTransfer::new(nft, recipient)]);
+
+        // The batch is discarded rather than submitted, so it has to be transferred again.
+        batch_transfer.transfer_batch(ctx, &p2, Conditions::new())?;
+        ctx.take();
+        batch_transfer.reset_submitted();
+        assert_eq!(batch_transfer.pending_count(), 1);
+
+        let nfts = batch_transfer
+            .transfer_batch(ctx, &p2, Conditions::new())?
+            .expect("missing batch");
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert_eq!(nfts[0].info.p2_puzzle_hash, recipient);
+        assert!(batch_transfer.confirm(nfts[0].coin.coin_id()));
+        assert!(batch_transfer
+            .transfer_batch(ctx, &p2, Conditions::new())?
+            .is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_transfer_cost_estimate() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        // Resetting the DID owner is the most expensive kind of transfer.
+        let owner = DidOwner::from_did_info(&did.info);
+        let mint = NftMint::new(NftMetadata::default(), puzzle_hash, 300, Some(owner));
+
+        let nfts = NftCollectionMint::new(vec![mint])
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .expect("missing batch")
+            .nfts;
+
+        let funding = sim.new_coin(puzzle_hash, 1);
+        p2.spend(ctx, funding, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let transfers = nfts
+            .into_iter()
+            .map(|nft| NftTransfer::new(nft, Bytes32::new([1; 32])))
+            .collect();
+
+        NftBatchTransfer::new(transfers)
+            .transfer_batch(ctx, &p2, Conditions::new())?
+            .expect("missing batch");
+
+        let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let measured = sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS)?.cost;
+
+        assert!(
+            measured <= NftTransfer::<NftMetadata>::COST_ESTIMATE,
+            "measured cost per transfer of {measured} exceeds the estimate"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use chia_protocol::Bytes32;
+
+use super::{NftInfo, NftProvenance};
+
+/// A way in which an NFT differs from the rest of its collection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NftAuditIssue {
+    /// The NFT uses a different metadata updater puzzle hash.
+    MetadataUpdater(Bytes32),
+    /// The NFT pays royalties to a different puzzle hash.
+    RoyaltyPuzzleHash(Bytes32),
+    /// The NFT has a different royalty percentage, in ten thousandths.
+    RoyaltyPercentage(u16),
+    /// There is no verified provenance showing that the NFT was minted by the collection's DID.
+    UnverifiedCreator,
+}
+
+/// An NFT in the collection that has one or more issues.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NftAuditOutlier {
+    pub launcher_id: Bytes32,
+    pub issues: Vec<NftAuditIssue>,
+}
+
+/// The settings that every NFT in a collection is expected to share.
+///
+/// This is useful to check a collection after a bulk mint, especially if it was performed by a third party.
+/// The NFT info and provenance of each item can be parsed from its eve spend with [`Nft::parse_child`](crate::Nft::parse_child)
+/// and [`NftProvenance::verify`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftCollectionAudit {
+    pub metadata_updater_puzzle_hash: Bytes32,
+    pub royalty_puzzle_hash: Bytes32,
+    pub royalty_ten_thousandths: u16,
+    /// The DID that must have minted each NFT, if any.
+    pub did_id: Option<Bytes32>,
+}
+
+impl NftCollectionAudit {
+    /// Checks a single NFT against the expected settings.
+    /// The provenance is only used if a DID is expected, and must belong to the same NFT.
+    pub fn check<M>(
+        &self,
+        info: &NftInfo<M>,
+        provenance: Option<&NftProvenance>,
+    ) -> Vec<NftAuditIssue> {
+        let mut issues = Vec::new();
+
+        if info.metadata_updater_puzzle_hash != self.metadata_updater_puzzle_hash {
+            issues.push(NftAuditIssue::MetadataUpdater(
+                info.metadata_updater_puzzle_hash,
+            ));
+        }
+
+        if info.royalty_puzzle_hash != self.royalty_puzzle_hash {
+            issues.push(NftAuditIssue::RoyaltyPuzzleHash(info.royalty_puzzle_hash));
+        }
+
+        if info.royalty_ten_thousandths != self.royalty_ten_thousandths {
+            issues.push(NftAuditIssue::RoyaltyPercentage(
+                info.royalty_ten_thousandths,
+            ));
+        }
+
+        if let Some(did_id) = self.did_id {
+            let verified = provenance.is_some_and(|provenance| {
+                provenance.launcher_id == info.launcher_id && provenance.did_id == did_id
+            });
+
+            if !verified {
+                issues.push(NftAuditIssue::UnverifiedCreator);
+            }
+        }
+
+        issues
+    }
+
+    /// Checks each NFT in the collection, and returns the ones that have issues, in order.
+    pub fn audit<'a, M: 'a>(
+        &self,
+        nfts: impl IntoIterator<Item = (&'a NftInfo<M>, Option<&'a NftProvenance>)>,
+    ) -> Vec<NftAuditOutlier> {
+        nfts.into_iter()
+            .filter_map(|(info, provenance)| {
+                let issues = self.check(info, provenance);
+
+                if issues.is_empty() {
+                    return None;
+                }
+
+                Some(NftAuditOutlier {
+                    launcher_id: info.launcher_id,
+                    issues,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::nft::{NftMetadata, NFT_METADATA_UPDATER_PUZZLE_HASH};
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{DidOwner, Launcher, NftCollectionMint, NftMint, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_collection_audit() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let owner = DidOwner::from_did_info(&did.info);
+
+        let manifest = [300, 300, 500]
+            .into_iter()
+            .map(|royalty| {
+                NftMint::new(NftMetadata::default(), puzzle_hash, royalty, Some(owner))
+                    .with_royalty_puzzle_hash(puzzle_hash)
+            })
+            .collect();
+
+        let mut collection = NftCollectionMint::new(manifest);
+        let did_coin = did.coin;
+        let batch = collection
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .expect("missing batch");
+
+        let funding = sim.new_coin(puzzle_hash, 3);
+        p2.spend(ctx, funding, Conditions::new())?;
+
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        let did_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin == did_coin)
+            .expect("missing did spend");
+
+        let mut provenances = Vec::new();
+
+        for nft in &batch.nfts {
+            let eve_spend = coin_spends
+                .iter()
+                .find(|coin_spend| coin_spend.coin.coin_id() == nft.coin.parent_coin_info)
+                .expect("missing eve spend");
+
+            provenances.push(NftProvenance::verify(
+                &mut ctx.allocator,
+                eve_spend,
+                did_spend,
+                owner.did_id,
+            )?);
+        }
+
+        let audit = NftCollectionAudit {
+            metadata_updater_puzzle_hash: NFT_METADATA_UPDATER_PUZZLE_HASH.into(),
+            royalty_puzzle_hash: puzzle_hash,
+            royalty_ten_thousandths: 300,
+            did_id: Some(owner.did_id),
+        };
+
+        // The first NFT is missing its provenance, and the last has a different royalty.
+        let outliers = audit.audit(
+            batch
+                .nfts
+                .iter()
+                .zip(&provenances)
+                .enumerate()
+                .map(|(i, (nft, provenance))| (&nft.info, provenance.as_ref().filter(|_| i != 0))),
+        );
+
+        assert_eq!(
+            outliers,
+            [
+                NftAuditOutlier {
+                    launcher_id: batch.nfts[0].info.launcher_id,
+                    issues: vec![NftAuditIssue::UnverifiedCreator],
+                },
+                NftAuditOutlier {
+                    launcher_id: batch.nfts[2].info.launcher_id,
+                    issues: vec![NftAuditIssue::RoyaltyPercentage(500)],
+                },
+            ]
+        );
+
+        // Provenance for another NFT in the collection doesn't count.
+        assert_eq!(
+            audit.check(&batch.nfts[0].info, provenances[1].as_ref()),
+            [NftAuditIssue::UnverifiedCreator]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,256 @@
+use chia_sdk_types::Conditions;
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::ToTreeHash;
+use clvmr::Allocator;
+
+use crate::{
+    BatchItem, BatchTracker, Did, DriverError, IntermediateLauncher, SpendContext,
+    SpendWithConditions,
+};
+
+use super::{Nft, NftMint};
+
+/// The result of minting a batch of NFTs from a [`NftCollectionMint`].
+#[derive(Debug, Clone)]
+pub struct NftMintBatch<D, M> {
+    /// The DID after it has been spent to authorize the batch.
+    pub did: Did<D>,
+    /// The NFTs that were minted, in manifest order.
+    pub nfts: Vec<Nft<M>>,
+}
+
+/// Plans and tracks the minting of an entire NFT collection from a DID.
+///
+/// Each NFT is minted from its own [`IntermediateLauncher`], using its index in the manifest
+/// as the mint number. The items are split into batches, each of which is a single DID spend,
+/// so that no individual spend bundle exceeds the cost limit. Each item is confirmed by its launcher id.
+pub type NftCollectionMint<M> = BatchTracker<NftMint<M>>;
+
+impl<M> BatchItem for NftMint<M> {
+    /// Covers the intermediate launcher, singleton launcher, and eve NFT spends,
+    /// as well as the conditions that the DID outputs for each of them.
+    const COST_ESTIMATE: u64 = 200_000_000;
+}
+
+impl<M> BatchTracker<NftMint<M>>
+where
+    M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+{
+    /// Mints the next batch of pending NFTs by spending the DID, and marks them as submitted.
+    /// Returns the recreated DID and the minted NFTs, or [`None`] if there's nothing left to mint.
+    ///
+    /// The DID coin must be the current (unspent) coin at the time the batch is submitted,
+    /// since it's the parent of each intermediate launcher. Each NFT requires a mojo, which must be
+    /// provided by another coin spent in the same bundle.
+    pub fn mint_batch<D, I>(
+        &mut self,
+        ctx: &mut SpendContext,
+        did: Did<D>,
+        inner: &I,
+        extra_conditions: Conditions,
+    ) -> Result<Option<NftMintBatch<D, M>>, DriverError>
+    where
+        D: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
+        I: SpendWithConditions,
+    {
+        let mint_total = self.items().len();
+        let indices = self.next_batch(|_| true);
+
+        if indices.is_empty() {
+            return Ok(None);
+        }
+
+        let mut conditions = extra_conditions;
+        let mut nfts = Vec::with_capacity(indices.len());
+
+        for &index in &indices {
+            let mint = self.items()[index].item.clone();
+
+            let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), index, mint_total)
+                .create(ctx)?
+                .mint_nft(ctx, mint)?;
+
+            conditions = conditions.extend(mint_nft);
+            nfts.push(nft);
+        }
+
+        let did = did.update(ctx, inner, conditions)?;
+
+        for (&index, nft) in indices.iter().zip(&nfts) {
+            self.submit(index, nft.info.launcher_id);
+        }
+
+        Ok(Some(NftMintBatch { did, nfts }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::{Bytes, Bytes32, Coin, SpendBundle};
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::TESTNET11_CONSTANTS;
+
+    use crate::{DidOwner, Launcher, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_collection_mint() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let owner = DidOwner::from_did_info(&did.info);
+
+        let manifest = (0..5u8)
+            .map(|i| {
+                let metadata = NftMetadata {
+                    data_uris: vec![format!("https://example.com/{i}.png")],
+                    data_hash: Some(Bytes32::new([i; 32])),
+                    ..Default::default()
+                };
+                let recipient = Bytes32::new([i + 1; 32]);
+                NftMint::new(metadata, recipient, 300, Some(owner))
+                    .with_royalty_puzzle_hash(puzzle_hash)
+            })
+            .collect();
+
+        let mut collection = NftCollectionMint::new(manifest).with_batch_size(2);
+        let mut did = did;
+        let mut batches = 0;
+
+        while let Some(batch) = collection.mint_batch(ctx, did, &p2, Conditions::new())? {
+            did = batch.did;
+            let nfts = batch.nfts;
+            batches += 1;
+
+            assert!(nfts.len() <= 2);
+            assert_eq!(collection.submitted_count(), nfts.len());
+
+            let funding = sim.new_coin(puzzle_hash, nfts.len().try_into()?);
+            p2.spend(ctx, funding, Conditions::new())?;
+
+            sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+            for nft in nfts {
+                assert!(sim.coin_state(nft.coin.coin_id()).is_some());
+                assert_eq!(nft.info.current_owner, Some(owner.did_id));
+                assert!(collection.confirm(nft.info.launcher_id));
+            }
+        }
+
+        assert_eq!(batches, 3);
+        assert!(collection.is_complete());
+        assert_eq!(collection.pending_count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_collection_mint_reset() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(4)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(
+            ctx,
+            coin,
+            create_did.create_coin(puzzle_hash, 3, Vec::new()),
+        )?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let manifest = vec![NftMint::new(Bytes::default(), puzzle_hash, 0, None); 3];
+        let mut collection = NftCollectionMint::new(manifest);
+
+        let nfts = collection
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .expect("missing batch")
+            .nfts;
+        assert_eq!(nfts.len(), 3);
+        assert_eq!(collection.submitted_count(), 3);
+
+        // The batch is discarded rather than submitted, so it has to be minted again.
+        ctx.take();
+        collection.reset_submitted();
+        assert_eq!(collection.pending_count(), 3);
+
+        let nfts = collection
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .expect("missing batch")
+            .nfts;
+
+        let funding = Coin::new(coin.coin_id(), puzzle_hash, 3);
+        p2.spend(ctx, funding, Conditions::new())?;
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        for nft in nfts {
+            assert!(collection.confirm(nft.info.launcher_id));
+        }
+
+        assert!(collection.is_complete());
+        assert!(collection
+            .mint_batch(ctx, did, &p2, Conditions::new())?
+            .is_none());
+
+        Ok(())
+    }
+
+    fn mint_batch_cost(
+        sim: &mut Simulator,
+        ctx: &mut SpendContext,
+        did: Did<()>,
+        p2: &StandardLayer,
+        size: usize,
+    ) -> anyhow::Result<u64> {
+        let puzzle_hash = did.info.p2_puzzle_hash;
+        let mint = NftMint::new(
+            NftMetadata::default(),
+            puzzle_hash,
+            300,
+            Some(DidOwner::from_did_info(&did.info)),
+        );
+
+        NftCollectionMint::new(vec![mint; size])
+            .mint_batch(ctx, did, p2, Conditions::new())?
+            .expect("missing batch");
+
+        let funding = sim.new_coin(puzzle_hash, size.try_into()?);
+        p2.spend(ctx, funding, Conditions::new())?;
+
+        let spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        Ok(sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS)?.cost)
+    }
+
+    #[test]
+    fn test_mint_cost_estimate() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        // The cost of the DID spend is shared by the batch, so only the difference is measured.
+        let single = mint_batch_cost(&mut sim, ctx, did, &p2, 1)?;
+        let double = mint_batch_cost(&mut sim, ctx, did, &p2, 2)?;
+        let measured = double - single;
+
+        assert!(
+            measured <= NftMint::<NftMetadata>::COST_ESTIMATE,
+            "measured cost per mint of {measured} exceeds the estimate"
+        );
+
+        Ok(())
+    }
+}
@@ -136,6 +136,20 @@ impl<M> NftInfo<M> {
         }
     }
 
+    /// Calculates the royalty paid to the royalty puzzle hash when this NFT is traded for the given price.
+    /// This rounds down in the same way as the royalty transfer program, so small enough trades pay nothing.
+    pub fn royalty_amount(&self, trade_price: u64) -> Result<u64, DriverError> {
+        if self.royalty_ten_thousandths > 10_000 {
+            return Err(DriverError::InvalidRoyalty(self.royalty_ten_thousandths));
+        }
+
+        let royalty = u128::from(trade_price) * u128::from(self.royalty_ten_thousandths) / 10_000;
+
+        Ok(royalty
+            .try_into()
+            .expect("royalty can't exceed the trade price"))
+    }
+
     pub fn inner_puzzle_hash(&self) -> TreeHash
     where
         M: ToTreeHash,
@@ -219,4 +233,50 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_royalty_amount() {
+        let info = NftInfo::new(
+            Bytes32::default(),
+            (),
+            Bytes32::default(),
+            None,
+            Bytes32::default(),
+            300,
+            Bytes32::default(),
+        );
+
+        assert_eq!(info.royalty_amount(1000).unwrap(), 30);
+        assert_eq!(info.royalty_amount(0).unwrap(), 0);
+
+        // Tiny trades round down to no royalty at all, like the transfer program.
+        assert_eq!(info.royalty_amount(33).unwrap(), 0);
+        assert_eq!(info.royalty_amount(34).unwrap(), 1);
+        assert_eq!(
+            info.royalty_amount(u64::MAX).unwrap(),
+            553_402_322_211_286_548
+        );
+
+        let free = NftInfo {
+            royalty_ten_thousandths: 0,
+            ..info
+        };
+        assert_eq!(free.royalty_amount(u64::MAX).unwrap(), 0);
+
+        let full = NftInfo {
+            royalty_ten_thousandths: 10_000,
+            ..info
+        };
+        assert_eq!(full.royalty_amount(u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(full.royalty_amount(1).unwrap(), 1);
+
+        let invalid = NftInfo {
+            royalty_ten_thousandths: 10_001,
+            ..info
+        };
+        assert!(matches!(
+            invalid.royalty_amount(1000),
+            Err(DriverError::InvalidRoyalty(10_001))
+        ));
+    }
 }
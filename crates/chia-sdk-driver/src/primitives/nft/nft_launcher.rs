@@ -1,9 +1,9 @@
 use chia_protocol::Bytes32;
 use chia_puzzles::{EveProof, Proof};
 use chia_sdk_types::{Conditions, TransferNft};
-use clvm_traits::{clvm_quote, FromClvm, ToClvm};
+use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::ToTreeHash;
-use clvmr::{Allocator, NodePtr};
+use clvmr::Allocator;
 
 use crate::{did_puzzle_assertion, DriverError, Launcher, Spend, SpendContext};
 
@@ -22,6 +22,10 @@ impl Launcher {
     where
         M: ToClvm<Allocator> + FromClvm<Allocator> + ToTreeHash + Clone,
     {
+        if royalty_ten_thousandths > 10_000 {
+            return Err(DriverError::InvalidRoyalty(royalty_ten_thousandths));
+        }
+
         let launcher_coin = self.coin();
 
         let nft_info = NftInfo::new(
@@ -68,9 +72,8 @@ impl Launcher {
             .create_coin(mint.p2_puzzle_hash, 1, vec![mint.p2_puzzle_hash.into()])
             .extend(transfer_condition.clone());
 
-        let inner_puzzle = ctx.alloc(&clvm_quote!(conditions))?;
-        let p2_puzzle_hash = ctx.tree_hash(inner_puzzle).into();
-        let inner_spend = Spend::new(inner_puzzle, NodePtr::NIL);
+        let inner_spend = Spend::conditions(ctx, conditions)?;
+        let p2_puzzle_hash = ctx.tree_hash(inner_spend.puzzle).into();
 
         let (mint_eve_nft, eve_nft) = self.mint_eve_nft(
             ctx,
@@ -291,4 +294,25 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_invalid_royalty() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let launcher = Launcher::new(Bytes32::default(), 1);
+        let result = launcher.mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), Bytes32::default(), 10_001, None),
+        );
+        assert!(matches!(result, Err(DriverError::InvalidRoyalty(10_001))));
+
+        let launcher = Launcher::new(Bytes32::default(), 1);
+        let (_mint_nft, nft) = launcher.mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), Bytes32::default(), 10_000, None),
+        )?;
+        assert_eq!(nft.info.royalty_ten_thousandths, 10_000);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,275 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_sdk_types::{run_puzzle, Condition, Conditions};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{puzzle_announcement_ids, DidInfo, DriverError, HashedPtr, Nft, NftInfo, Puzzle};
+
+/// Proof that an NFT was assigned to a DID when it was minted, with the DID's approval.
+///
+/// The proof only depends on the eve spend of the NFT and the DID spend in the same bundle,
+/// so later transfers of the NFT don't affect it. This is what marketplaces usually mean by
+/// a "verified creator".
+///
+/// The history of the NFT after the eve spend isn't walked, and the state of the DID at the time
+/// of the mint (such as its recovery list or metadata) isn't checked beyond its launcher id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NftProvenance {
+    /// The launcher id of the NFT.
+    pub launcher_id: Bytes32,
+    /// The launcher id of the DID that minted the NFT.
+    pub did_id: Bytes32,
+    /// The eve coin of the NFT, which was spent to assign it to the DID.
+    pub eve_coin: Coin,
+    /// The DID coin that was spent to approve the assignment.
+    pub did_coin: Coin,
+    /// The puzzle announcement created by the NFT and asserted by the DID.
+    pub announcement_id: Bytes32,
+}
+
+impl NftProvenance {
+    /// Verifies that the eve spend of an NFT assigned it to the claimed DID, and that the DID spend approved it.
+    ///
+    /// Returns [`None`] if either spend isn't what it claims to be, or the two spends don't reference each other.
+    ///
+    /// The puzzle reveals are checked against the puzzle hashes of the coins, but the coins themselves are
+    /// taken as given. Both spends must come from the chain (for example, from the coin states and puzzle
+    /// solutions returned by a trusted peer) and both coins must be confirmed as spent, otherwise the proof
+    /// can be forged by making up the coins.
+    pub fn verify(
+        allocator: &mut Allocator,
+        eve_spend: &CoinSpend,
+        did_spend: &CoinSpend,
+        did_id: Bytes32,
+    ) -> Result<Option<Self>, DriverError> {
+        let eve_puzzle = eve_spend.puzzle_reveal.to_clvm(allocator)?;
+        let eve_puzzle = Puzzle::parse(allocator, eve_puzzle);
+        let eve_solution = eve_spend.solution.to_clvm(allocator)?;
+
+        if Bytes32::from(eve_puzzle.curried_puzzle_hash()) != eve_spend.coin.puzzle_hash {
+            return Ok(None);
+        }
+
+        let Some((nft_info, _)) = NftInfo::<NodePtr>::parse(allocator, eve_puzzle)? else {
+            return Ok(None);
+        };
+
+        // Only the eve coin is created directly by the launcher.
+        if eve_spend.coin.parent_coin_info != nft_info.launcher_id {
+            return Ok(None);
+        }
+
+        let did_puzzle = did_spend.puzzle_reveal.to_clvm(allocator)?;
+        let did_puzzle = Puzzle::parse(allocator, did_puzzle);
+        let did_solution = did_spend.solution.to_clvm(allocator)?;
+
+        if Bytes32::from(did_puzzle.curried_puzzle_hash()) != did_spend.coin.puzzle_hash {
+            return Ok(None);
+        }
+
+        let Some((did_info, _)) = DidInfo::<NodePtr>::parse(allocator, did_puzzle)? else {
+            return Ok(None);
+        };
+
+        if did_info.launcher_id != did_id {
+            return Ok(None);
+        }
+
+        let Some(child) =
+            Nft::<HashedPtr>::parse_child(allocator, eve_spend.coin, eve_puzzle, eve_solution)?
+        else {
+            return Ok(None);
+        };
+
+        if child.info.current_owner != Some(did_id) {
+            return Ok(None);
+        }
+
+        let output = run_puzzle(allocator, eve_puzzle.ptr(), eve_solution)?;
+        let nft_conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        let output = run_puzzle(allocator, did_puzzle.ptr(), did_solution)?;
+        let did_conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        // The DID approves the assignment by asserting the NFT's announcement.
+        let nft_announcements =
+            puzzle_announcement_ids(eve_spend.coin.puzzle_hash, &nft_conditions);
+        let Some(announcement_id) = asserted_announcements(&did_conditions)
+            .find(|announcement_id| nft_announcements.contains(announcement_id))
+        else {
+            return Ok(None);
+        };
+
+        // And the NFT's transfer program asserts an announcement from the DID in return.
+        let did_announcements =
+            puzzle_announcement_ids(did_spend.coin.puzzle_hash, &did_conditions);
+        if !asserted_announcements(&nft_conditions)
+            .any(|announcement_id| did_announcements.contains(&announcement_id))
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            launcher_id: nft_info.launcher_id,
+            did_id,
+            eve_coin: eve_spend.coin,
+            did_coin: did_spend.coin,
+            announcement_id,
+        }))
+    }
+}
+
+fn asserted_announcements(conditions: &Conditions<NodePtr>) -> impl Iterator<Item = Bytes32> + '_ {
+    conditions
+        .as_ref()
+        .iter()
+        .filter_map(|condition| match condition {
+            Condition::AssertPuzzleAnnouncement(assertion) => Some(assertion.announcement_id),
+            _ => None,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_puzzles::nft::NftMetadata;
+    use chia_sdk_test::Simulator;
+
+    use crate::{DidOwner, IntermediateLauncher, Launcher, NftMint, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_nft_provenance() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(
+                ctx,
+                NftMint::new(
+                    NftMetadata::default(),
+                    puzzle_hash,
+                    300,
+                    Some(DidOwner::from_did_info(&did.info)),
+                ),
+            )?;
+        let _ = did.update(ctx, &p2, mint_nft)?;
+
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        let eve_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == nft.coin.parent_coin_info)
+            .expect("missing eve spend");
+        let did_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin == did.coin)
+            .expect("missing did spend");
+
+        let provenance = NftProvenance::verify(
+            &mut ctx.allocator,
+            eve_spend,
+            did_spend,
+            did.info.launcher_id,
+        )?
+        .expect("provenance not verified");
+
+        assert_eq!(provenance.launcher_id, nft.info.launcher_id);
+        assert_eq!(provenance.did_id, did.info.launcher_id);
+        assert_eq!(provenance.eve_coin, eve_spend.coin);
+        assert_eq!(provenance.did_coin, did.coin);
+
+        // A different creator can't be claimed.
+        assert_eq!(
+            NftProvenance::verify(&mut ctx.allocator, eve_spend, did_spend, Bytes32::default())?,
+            None
+        );
+
+        // The spends have to be given in the right order.
+        assert_eq!(
+            NftProvenance::verify(
+                &mut ctx.allocator,
+                did_spend,
+                eve_spend,
+                did.info.launcher_id
+            )?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nft_provenance_tampered_reveal() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(2)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+
+        let (mint_nft, nft) = IntermediateLauncher::new(did.coin.coin_id(), 0, 1)
+            .create(ctx)?
+            .mint_nft(
+                ctx,
+                NftMint::new(
+                    NftMetadata::default(),
+                    puzzle_hash,
+                    300,
+                    Some(DidOwner::from_did_info(&did.info)),
+                ),
+            )?;
+        let _ = did.update(ctx, &p2, mint_nft)?;
+
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        let eve_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin.coin_id() == nft.coin.parent_coin_info)
+            .expect("missing eve spend");
+        let did_spend = coin_spends
+            .iter()
+            .find(|coin_spend| coin_spend.coin == did.coin)
+            .expect("missing did spend");
+
+        // The reveals are otherwise valid, but they don't belong to the coins being spent.
+        let mut tampered_eve = eve_spend.clone();
+        tampered_eve.coin.puzzle_hash = puzzle_hash;
+
+        let mut tampered_did = did_spend.clone();
+        tampered_did.coin.puzzle_hash = puzzle_hash;
+
+        assert_eq!(
+            NftProvenance::verify(
+                &mut ctx.allocator,
+                &tampered_eve,
+                did_spend,
+                did.info.launcher_id
+            )?,
+            None
+        );
+
+        assert_eq!(
+            NftProvenance::verify(
+                &mut ctx.allocator,
+                eve_spend,
+                &tampered_did,
+                did.info.launcher_id
+            )?,
+            None
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,183 @@
+use chia_protocol::{Bytes, Bytes32};
+use chia_sdk_types::{Conditions, CreateCoin};
+
+use crate::{DriverError, MemoPolicy};
+
+/// A coin to be created, along with the memos that will be attached to it.
+///
+/// By default, the payment is hinted with its own puzzle hash, which is what wallets use to find
+/// coins that they have received. This is the first memo. Any additional memos (for example, a deposit
+/// reference used by an exchange) come after the hint.
+///
+/// Note that the first memo is treated as a hint by full nodes if it's 32 bytes or shorter,
+/// regardless of whether it was intended as one. So if hinting is disabled, the first memo
+/// must be longer than that to avoid being mistaken for a hint.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[must_use]
+pub struct Payment {
+    pub puzzle_hash: Bytes32,
+    pub amount: u64,
+    pub hinted: bool,
+    pub memos: Vec<Bytes>,
+}
+
+impl Payment {
+    /// Creates a hinted payment with no additional memos.
+    pub fn new(puzzle_hash: Bytes32, amount: u64) -> Self {
+        Self {
+            puzzle_hash,
+            amount,
+            hinted: true,
+            memos: Vec::new(),
+        }
+    }
+
+    /// Creates a payment that isn't hinted, and has no memos.
+    pub fn unhinted(puzzle_hash: Bytes32, amount: u64) -> Self {
+        Self::new(puzzle_hash, amount).with_hint(false)
+    }
+
+    /// Sets whether the payment is hinted with its puzzle hash.
+    pub fn with_hint(mut self, hinted: bool) -> Self {
+        self.hinted = hinted;
+        self
+    }
+
+    /// Appends a memo after the hint (if there is one) and any existing memos.
+    pub fn with_memo(mut self, memo: impl Into<Bytes>) -> Self {
+        self.memos.push(memo.into());
+        self
+    }
+
+    /// Appends multiple memos, in order.
+    pub fn with_memos(mut self, memos: impl IntoIterator<Item = impl Into<Bytes>>) -> Self {
+        self.memos.extend(memos.into_iter().map(Into::into));
+        self
+    }
+
+    /// Checks that the hint is unambiguous, and that the memos are within the limits of the policy.
+    /// The default [`MemoPolicy`] has no limits, since consensus doesn't have any.
+    pub fn validate(&self, policy: &MemoPolicy) -> Result<(), DriverError> {
+        policy.validate_memos(&self.all_memos())?;
+
+        if !self.hinted && self.memos.first().is_some_and(|memo| memo.len() <= 32) {
+            return Err(DriverError::AmbiguousHint);
+        }
+
+        Ok(())
+    }
+
+    /// The full list of memos, including the hint, as they will appear in the condition.
+    pub fn all_memos(&self) -> Vec<Bytes> {
+        let mut memos = Vec::with_capacity(self.memos.len() + 1);
+        if self.hinted {
+            memos.push(self.puzzle_hash.into());
+        }
+        memos.extend(self.memos.iter().cloned());
+        memos
+    }
+
+    /// Checks that the hint is unambiguous, and converts the payment into a [`CreateCoin`] condition.
+    /// Use [`MemoPolicy::apply`] on the conditions to enforce limits on the memos.
+    pub fn create_coin(&self) -> Result<CreateCoin, DriverError> {
+        self.validate(&MemoPolicy::default())?;
+        Ok(CreateCoin::new(
+            self.puzzle_hash,
+            self.amount,
+            self.all_memos(),
+        ))
+    }
+
+    /// Validates each of the payments and appends them to the conditions, in order.
+    pub fn extend_conditions(
+        conditions: Conditions,
+        payments: &[Payment],
+    ) -> Result<Conditions, DriverError> {
+        let create_coins = payments
+            .iter()
+            .map(Payment::create_coin)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(conditions.extend(create_coins))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+
+    use crate::{SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_payment_memos() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(6)?;
+        let p2 = StandardLayer::new(pk);
+
+        let deposit_id = Bytes::new(b"deposit-1234".to_vec());
+
+        let payments = [
+            Payment::new(puzzle_hash, 1).with_memo(deposit_id.clone()),
+            Payment::new(puzzle_hash, 2),
+            Payment::unhinted(puzzle_hash, 3),
+        ];
+
+        let conditions = Payment::extend_conditions(Conditions::new(), &payments)?;
+        p2.spend(ctx, coin, conditions)?;
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert_eq!(
+            payments[0].all_memos(),
+            vec![Bytes::from(puzzle_hash), deposit_id]
+        );
+        assert_eq!(payments[1].all_memos(), vec![Bytes::from(puzzle_hash)]);
+        assert!(payments[2].all_memos().is_empty());
+
+        let hinted = sim.hinted_coins(puzzle_hash);
+        assert_eq!(hinted.len(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_payment_validation() {
+        let puzzle_hash = Bytes32::new([1; 32]);
+        let policy = MemoPolicy::default()
+            .with_max_memos(2)
+            .with_max_memo_length(64);
+
+        assert!(Payment::new(puzzle_hash, 1)
+            .with_memos(vec![vec![0; 65]; 2])
+            .validate(&MemoPolicy::default())
+            .is_ok());
+
+        assert!(matches!(
+            Payment::new(puzzle_hash, 1)
+                .with_memo(vec![0; 65])
+                .validate(&policy),
+            Err(DriverError::MemoTooLarge(65))
+        ));
+
+        // The hint counts towards the maximum number of memos.
+        assert!(matches!(
+            Payment::new(puzzle_hash, 1)
+                .with_memos(vec![Bytes::default(); 2])
+                .validate(&policy),
+            Err(DriverError::TooManyMemos(3))
+        ));
+
+        assert!(matches!(
+            Payment::unhinted(puzzle_hash, 1)
+                .with_memo(b"short".to_vec())
+                .validate(&policy),
+            Err(DriverError::AmbiguousHint)
+        ));
+
+        assert!(Payment::unhinted(puzzle_hash, 1)
+            .with_memo(vec![0; 33])
+            .validate(&policy)
+            .is_ok());
+    }
+}
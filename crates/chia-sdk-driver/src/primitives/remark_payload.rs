@@ -0,0 +1,125 @@
+use chia_protocol::Bytes;
+use chia_sdk_types::{Condition, Remark};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, SpendContext};
+
+/// Structured application data carried in a `REMARK` condition.
+///
+/// `REMARK` is ignored by consensus, so it can carry arbitrary data without affecting the spend.
+/// The payload is encoded as `(1 tag data)`, where the tag identifies the application (for example
+/// `b"myapp/v1"`) so that unrelated remarks can be told apart. Consensus doesn't limit the size
+/// of either, other than through the cost per byte of the spend.
+#[derive(Debug, Clone, PartialEq, Eq, ToClvm, FromClvm)]
+#[clvm(list)]
+pub struct RemarkPayload {
+    pub tag: Bytes,
+    pub data: Bytes,
+}
+
+impl RemarkPayload {
+    pub fn new(tag: impl Into<Bytes>, data: impl Into<Bytes>) -> Self {
+        Self {
+            tag: tag.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Checks that the tag is present.
+    pub fn validate(&self) -> Result<(), DriverError> {
+        if self.tag.is_empty() {
+            return Err(DriverError::MissingRemarkTag);
+        }
+
+        Ok(())
+    }
+
+    /// Validates the payload and converts it into a [`Remark`] condition.
+    pub fn remark(&self, ctx: &mut SpendContext) -> Result<Remark<NodePtr>, DriverError> {
+        self.validate()?;
+        Ok(Remark::new(ctx.alloc(self)?))
+    }
+
+    /// Parses the payload from a [`Remark`] condition.
+    ///
+    /// Returns [`None`] if the remark isn't a structured payload, or it's missing a tag.
+    pub fn parse(allocator: &Allocator, remark: &Remark<NodePtr>) -> Option<Self> {
+        let payload = Self::from_clvm(allocator, remark.rest).ok()?;
+        payload.validate().ok()?;
+        Some(payload)
+    }
+
+    /// Parses the payloads of each of the structured `REMARK` conditions, in order.
+    pub fn parse_all(allocator: &Allocator, conditions: &[Condition<NodePtr>]) -> Vec<Self> {
+        conditions
+            .iter()
+            .filter_map(|condition| match condition {
+                Condition::Remark(remark) => Self::parse(allocator, remark),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    #[test]
+    fn test_remark_payload() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let payload = RemarkPayload::new(b"myapp/v1".to_vec(), b"order 1234".to_vec());
+        let unstructured = ctx.alloc(&"Hello, world!")?;
+
+        let conditions = Conditions::new()
+            .with(payload.remark(ctx)?)
+            .remark(unstructured)
+            .create_coin(puzzle_hash, 1, Vec::new());
+
+        p2.spend(ctx, coin, conditions)?;
+
+        let plan = ctx.plan()?;
+        assert_eq!(plan[0].remark_payloads, [payload]);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_remark_payload_validation() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        assert!(matches!(
+            RemarkPayload::new(Vec::new(), Vec::new()).validate(),
+            Err(DriverError::MissingRemarkTag)
+        ));
+
+        let untagged = RemarkPayload::new(Vec::new(), b"data".to_vec());
+
+        assert!(matches!(
+            untagged.remark(ctx),
+            Err(DriverError::MissingRemarkTag)
+        ));
+
+        // Untagged payloads that were built by hand aren't surfaced when parsing.
+        let remark = Remark::new(ctx.alloc(&untagged)?);
+        assert_eq!(RemarkPayload::parse(&ctx.allocator, &remark), None);
+
+        let large = RemarkPayload::new(b"tag".to_vec(), vec![1; 4096]);
+        let remark = large.remark(ctx)?;
+        assert_eq!(RemarkPayload::parse(&ctx.allocator, &remark), Some(large));
+
+        Ok(())
+    }
+}
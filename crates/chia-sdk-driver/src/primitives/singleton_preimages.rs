@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_puzzles::{
+    singleton::{LauncherSolution, SingletonArgs, SINGLETON_LAUNCHER_PUZZLE_HASH},
+    EveProof, LineageProof, Proof,
+};
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{DriverError, Layer, Puzzle, SingletonLayer};
+
+/// The values that a singleton puzzle hash is curried from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SingletonPreimage {
+    pub launcher_id: Bytes32,
+    pub inner_puzzle_hash: Bytes32,
+}
+
+impl SingletonPreimage {
+    pub fn new(launcher_id: Bytes32, inner_puzzle_hash: Bytes32) -> Self {
+        Self {
+            launcher_id,
+            inner_puzzle_hash,
+        }
+    }
+
+    /// Computes the full puzzle hash of the singleton.
+    pub fn puzzle_hash(&self) -> Bytes32 {
+        SingletonArgs::curry_tree_hash(self.launcher_id, self.inner_puzzle_hash.into()).into()
+    }
+}
+
+/// Keeps track of the inner puzzle hashes behind singleton puzzle hashes, and the proofs of singleton coins,
+/// as their spends are observed.
+///
+/// This way, a singleton can be spent later, or its lineage proven to someone else,
+/// without parsing its parent spend again.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SingletonPreimages {
+    preimages: HashMap<Bytes32, SingletonPreimage>,
+    proofs: HashMap<Bytes32, Proof>,
+}
+
+impl SingletonPreimages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts a preimage that's already known, and returns the singleton puzzle hash it's for.
+    pub fn insert(&mut self, preimage: SingletonPreimage) -> Bytes32 {
+        let puzzle_hash = preimage.puzzle_hash();
+        self.preimages.insert(puzzle_hash, preimage);
+        puzzle_hash
+    }
+
+    /// Returns the preimage of the given singleton puzzle hash, if it's known.
+    pub fn get(&self, puzzle_hash: Bytes32) -> Option<SingletonPreimage> {
+        self.preimages.get(&puzzle_hash).copied()
+    }
+
+    /// Returns the proof of the singleton coin with the given id, if its parent spend has been observed.
+    pub fn proof(&self, coin_id: Bytes32) -> Option<Proof> {
+        self.proofs.get(&coin_id).copied()
+    }
+
+    /// Records the preimages and proofs revealed by a coin spend.
+    ///
+    /// A launcher spend reveals the proof of the eve singleton. A singleton spend reveals its own preimage,
+    /// along with the preimage and proof of its child. Other spends are ignored.
+    pub fn observe(
+        &mut self,
+        allocator: &mut Allocator,
+        coin_spend: &CoinSpend,
+    ) -> Result<(), DriverError> {
+        let coin = coin_spend.coin;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+
+        if coin.puzzle_hash == SINGLETON_LAUNCHER_PUZZLE_HASH.into() {
+            let solution = LauncherSolution::<NodePtr>::from_clvm(allocator, solution)?;
+            let eve = Coin::new(
+                coin.coin_id(),
+                solution.singleton_puzzle_hash,
+                solution.amount,
+            );
+
+            self.proofs.insert(
+                eve.coin_id(),
+                Proof::Eve(EveProof {
+                    parent_parent_coin_info: coin.parent_coin_info,
+                    parent_amount: coin.amount,
+                }),
+            );
+
+            return Ok(());
+        }
+
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let puzzle = Puzzle::parse(allocator, puzzle);
+
+        let Some(singleton_layer) = SingletonLayer::<Puzzle>::parse_puzzle(allocator, puzzle)?
+        else {
+            return Ok(());
+        };
+
+        let launcher_id = singleton_layer.launcher_id;
+        let inner_puzzle_hash = singleton_layer.inner_puzzle.curried_puzzle_hash().into();
+        self.insert(SingletonPreimage::new(launcher_id, inner_puzzle_hash));
+
+        let solution = SingletonLayer::<NodePtr>::parse_solution(allocator, solution)?;
+        let output = run_puzzle(
+            allocator,
+            singleton_layer.inner_puzzle.ptr(),
+            solution.inner_solution,
+        )?;
+        let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+        // The singleton is melted if there's no odd output, in which case there's no child to track.
+        let Some(create_coin) = conditions
+            .into_iter()
+            .filter_map(Condition::into_create_coin)
+            .find(|create_coin| create_coin.amount % 2 == 1)
+        else {
+            return Ok(());
+        };
+
+        let child_puzzle_hash =
+            self.insert(SingletonPreimage::new(launcher_id, create_coin.puzzle_hash));
+        let child = Coin::new(coin.coin_id(), child_puzzle_hash, create_coin.amount);
+
+        self.proofs.insert(
+            child.coin_id(),
+            Proof::Lineage(LineageProof {
+                parent_parent_coin_info: coin.parent_coin_info,
+                parent_inner_puzzle_hash: inner_puzzle_hash,
+                parent_amount: coin.amount,
+            }),
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{Launcher, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_singleton_preimages() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, _puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let did = did.update(ctx, &p2, Conditions::new())?;
+        let did = did.update(ctx, &p2, Conditions::new())?;
+        let coin_spends = ctx.take();
+        sim.spend_coins(coin_spends.clone(), &[sk])?;
+
+        let mut preimages = SingletonPreimages::new();
+        let launcher_spend = CoinSpend::new(
+            sim.coin_state(did.info.launcher_id)
+                .expect("missing launcher")
+                .coin,
+            sim.puzzle_reveal(did.info.launcher_id)
+                .expect("missing puzzle"),
+            sim.solution(did.info.launcher_id)
+                .expect("missing solution"),
+        );
+
+        for coin_spend in [launcher_spend].iter().chain(&coin_spends) {
+            preimages.observe(&mut ctx.allocator, coin_spend)?;
+        }
+
+        let expected =
+            SingletonPreimage::new(did.info.launcher_id, did.info.inner_puzzle_hash().into());
+        assert_eq!(preimages.get(did.coin.puzzle_hash), Some(expected));
+        assert_eq!(preimages.proof(did.coin.coin_id()), Some(did.proof));
+
+        // The eve singleton was spent when the DID was created, so its proof comes from the launcher.
+        let eve_id = coin_spends[0].coin.parent_coin_info;
+        assert!(matches!(preimages.proof(eve_id), Some(Proof::Eve(..))));
+
+        // Spends of other coins are ignored.
+        let mut other = SingletonPreimages::new();
+        p2.spend(ctx, coin, Conditions::new())?;
+        let coin_spend = ctx.take().remove(0);
+        other.observe(&mut ctx.allocator, &coin_spend)?;
+        assert_eq!(other, SingletonPreimages::new());
+
+        Ok(())
+    }
+}
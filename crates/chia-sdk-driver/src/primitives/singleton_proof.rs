@@ -0,0 +1,100 @@
+use chia_protocol::{Coin, CoinSpend};
+use chia_puzzles::{singleton::SINGLETON_LAUNCHER_PUZZLE_HASH, EveProof, LineageProof, Proof};
+use clvm_traits::ToClvm;
+use clvmr::Allocator;
+
+use crate::{DriverError, Layer, Puzzle, SingletonLayer};
+
+/// Computes the [`Proof`] needed to spend a singleton coin, given the coin spend of its parent.
+///
+/// If the parent is the launcher, the coin is the eve singleton and an [`EveProof`] is returned.
+/// Otherwise, the parent must be a singleton itself, and a [`LineageProof`] is derived from its inner puzzle.
+/// This works for any singleton, including [`Did`](crate::Did), [`Nft`](crate::Nft), and `DataStore`.
+///
+/// Returns [`None`] if the coin isn't a child of the parent, or if the parent isn't a launcher or singleton.
+pub fn singleton_proof(
+    allocator: &mut Allocator,
+    coin: Coin,
+    parent_spend: &CoinSpend,
+) -> Result<Option<Proof>, DriverError> {
+    let parent_coin = parent_spend.coin;
+
+    if coin.parent_coin_info != parent_coin.coin_id() {
+        return Ok(None);
+    }
+
+    if parent_coin.puzzle_hash == SINGLETON_LAUNCHER_PUZZLE_HASH.into() {
+        return Ok(Some(Proof::Eve(EveProof {
+            parent_parent_coin_info: parent_coin.parent_coin_info,
+            parent_amount: parent_coin.amount,
+        })));
+    }
+
+    let parent_puzzle = parent_spend.puzzle_reveal.to_clvm(allocator)?;
+    let parent_puzzle = Puzzle::parse(allocator, parent_puzzle);
+
+    let Some(singleton_layer) = SingletonLayer::<Puzzle>::parse_puzzle(allocator, parent_puzzle)?
+    else {
+        return Ok(None);
+    };
+
+    Ok(Some(Proof::Lineage(LineageProof {
+        parent_parent_coin_info: parent_coin.parent_coin_info,
+        parent_inner_puzzle_hash: singleton_layer.inner_puzzle.curried_puzzle_hash().into(),
+        parent_amount: parent_coin.amount,
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::{Launcher, SpendContext, StandardLayer};
+
+    use super::*;
+
+    fn parent_spend(sim: &Simulator, coin: Coin) -> CoinSpend {
+        CoinSpend::new(
+            coin,
+            sim.puzzle_reveal(coin.coin_id()).expect("missing puzzle"),
+            sim.solution(coin.coin_id()).expect("missing solution"),
+        )
+    }
+
+    #[test]
+    fn test_singleton_proof() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (create_did, did) = Launcher::new(coin.coin_id(), 1).create_simple_did(ctx, &p2)?;
+        p2.spend(ctx, coin, create_did)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let launcher = sim
+            .coin_state(did.coin.parent_coin_info)
+            .expect("missing launcher")
+            .coin;
+        let proof = singleton_proof(&mut ctx.allocator, did.coin, &parent_spend(&sim, launcher))?;
+        assert_eq!(proof, Some(did.proof));
+
+        let parent_coin = did.coin;
+        let did = did.update(ctx, &p2, Conditions::new())?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let parent_spend = parent_spend(&sim, parent_coin);
+        let proof = singleton_proof(&mut ctx.allocator, did.coin, &parent_spend)?;
+        assert_eq!(proof, Some(did.proof));
+
+        let unrelated = Coin::new(Bytes32::default(), puzzle_hash, 1);
+        assert_eq!(
+            singleton_proof(&mut ctx.allocator, unrelated, &parent_spend)?,
+            None
+        );
+
+        Ok(())
+    }
+}
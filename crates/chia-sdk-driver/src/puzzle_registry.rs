@@ -0,0 +1,457 @@
+use std::collections::HashMap;
+
+use chia_puzzles::{
+    cat::{
+        CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+        GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+    },
+    did::DID_INNER_PUZZLE_HASH,
+    nft::{
+        NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH, NFT_METADATA_UPDATER_PUZZLE_HASH,
+        NFT_OWNERSHIP_LAYER_PUZZLE_HASH, NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+        NFT_STATE_LAYER_PUZZLE_HASH,
+    },
+    offer::{SETTLEMENT_PAYMENTS_PUZZLE_HASH, SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1},
+    singleton::{SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE_HASH},
+    standard::{DEFAULT_HIDDEN_PUZZLE_HASH, STANDARD_PUZZLE_HASH},
+};
+use clvm_utils::{tree_hash, TreeHash};
+use clvmr::{Allocator, NodePtr, SExp};
+
+use crate::{
+    Puzzle, GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+    P2_DELEGATED_SINGLETON_PUZZLE_HASH, P2_ONE_OF_MANY_PUZZLE_HASH, P2_SINGLETON_PUZZLE_HASH,
+};
+
+/// Puzzles nested deeper than this in [`PuzzleRegistry::describe`] are shown by their tree hash,
+/// so that adversarial puzzles can't overflow the stack.
+const MAX_DESCRIBE_DEPTH: usize = 16;
+
+/// The name and curried argument names of a known puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PuzzleInfo {
+    /// A human readable name for the puzzle, typically the name of its source file.
+    pub name: String,
+    /// The names of the curried arguments, in the order they are curried.
+    /// This is empty for puzzles that aren't curried.
+    pub args: Vec<String>,
+}
+
+impl PuzzleInfo {
+    pub fn new(name: impl Into<String>, args: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            args: args.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
+
+/// A lookup table from mod hashes to [`PuzzleInfo`], used to label puzzles that
+/// would otherwise be opaque. Applications can register their own puzzles at runtime.
+#[derive(Debug, Default, Clone)]
+pub struct PuzzleRegistry {
+    puzzles: HashMap<TreeHash, PuzzleInfo>,
+}
+
+impl PuzzleRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a registry containing every puzzle known to the driver.
+    pub fn standard() -> Self {
+        let mut registry = Self::new();
+
+        for (mod_hash, name, args) in [
+            (
+                STANDARD_PUZZLE_HASH,
+                "p2_delegated_puzzle_or_hidden_puzzle",
+                &["SYNTHETIC_PUBLIC_KEY"][..],
+            ),
+            (DEFAULT_HIDDEN_PUZZLE_HASH, "default_hidden_puzzle", &[]),
+            (
+                CAT_PUZZLE_HASH,
+                "cat_v2",
+                &["MOD_HASH", "TAIL_PROGRAM_HASH", "INNER_PUZZLE"],
+            ),
+            (
+                CAT_PUZZLE_HASH_V1,
+                "cat_v1",
+                &["MOD_HASH", "TAIL_PROGRAM_HASH", "INNER_PUZZLE"],
+            ),
+            (
+                EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+                "everything_with_signature",
+                &["PUBKEY"],
+            ),
+            (
+                GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+                "genesis_by_coin_id",
+                &["GENESIS_ID"],
+            ),
+            (
+                GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH,
+                "genesis_by_puzzle_hash",
+                &["GENESIS_PUZZLE_HASH"],
+            ),
+            (
+                DID_INNER_PUZZLE_HASH,
+                "did_innerpuz",
+                &[
+                    "INNER_PUZZLE",
+                    "RECOVERY_DID_LIST_HASH",
+                    "NUM_VERIFICATIONS_REQUIRED",
+                    "SINGLETON_STRUCT",
+                    "METADATA",
+                ],
+            ),
+            (
+                NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH,
+                "nft_intermediate_launcher",
+                &["LAUNCHER_PUZZLE_HASH", "MINT_NUMBER", "MINT_TOTAL"],
+            ),
+            (
+                NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+                "nft_ownership_transfer_program_one_way_claim_with_royalties",
+                &[
+                    "SINGLETON_STRUCT",
+                    "ROYALTY_ADDRESS",
+                    "TRADE_PRICE_PERCENTAGE",
+                ],
+            ),
+            (
+                NFT_METADATA_UPDATER_PUZZLE_HASH,
+                "nft_metadata_updater_default",
+                &[],
+            ),
+            (
+                NFT_OWNERSHIP_LAYER_PUZZLE_HASH,
+                "nft_ownership_layer",
+                &[
+                    "MOD_HASH",
+                    "CURRENT_OWNER",
+                    "TRANSFER_PROGRAM",
+                    "INNER_PUZZLE",
+                ],
+            ),
+            (
+                NFT_STATE_LAYER_PUZZLE_HASH,
+                "nft_state_layer",
+                &[
+                    "MOD_HASH",
+                    "METADATA",
+                    "METADATA_UPDATER_PUZZLE_HASH",
+                    "INNER_PUZZLE",
+                ],
+            ),
+            (
+                SINGLETON_TOP_LAYER_PUZZLE_HASH,
+                "singleton_top_layer_v1_1",
+                &["SINGLETON_STRUCT", "INNER_PUZZLE"],
+            ),
+            (SINGLETON_LAUNCHER_PUZZLE_HASH, "singleton_launcher", &[]),
+            (SETTLEMENT_PAYMENTS_PUZZLE_HASH, "settlement_payments", &[]),
+            (
+                SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1,
+                "settlement_payments_v1",
+                &[],
+            ),
+            (
+                P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+                "p2_delegated_conditions",
+                &["PUBLIC_KEY"],
+            ),
+            (P2_ONE_OF_MANY_PUZZLE_HASH, "p2_1_of_n", &["MERKLE_ROOT"]),
+            (
+                P2_SINGLETON_PUZZLE_HASH,
+                "p2_singleton",
+                &["SINGLETON_MOD_HASH", "LAUNCHER_ID", "LAUNCHER_PUZZLE_HASH"],
+            ),
+            (
+                P2_DELEGATED_SINGLETON_PUZZLE_HASH,
+                "p2_delegated_singleton",
+                &["SINGLETON_MOD_HASH", "LAUNCHER_ID", "LAUNCHER_PUZZLE_HASH"],
+            ),
+        ] {
+            registry.register(mod_hash, PuzzleInfo::new(name, args));
+        }
+
+        #[cfg(feature = "chip-0035")]
+        {
+            use crate::{
+                DELEGATION_LAYER_PUZZLE_HASH, DL_METADATA_UPDATER_PUZZLE_HASH,
+                WRITER_FILTER_PUZZLE_HASH,
+            };
+
+            registry.register(
+                DELEGATION_LAYER_PUZZLE_HASH,
+                PuzzleInfo::new(
+                    "delegation_layer",
+                    &[
+                        "MOD_HASH",
+                        "LAUNCHER_ID",
+                        "OWNER_PUZZLE_HASH",
+                        "MERKLE_ROOT",
+                    ],
+                ),
+            );
+            registry.register(
+                WRITER_FILTER_PUZZLE_HASH,
+                PuzzleInfo::new("writer_filter", &["INNER_PUZZLE"]),
+            );
+            registry.register(
+                DL_METADATA_UPDATER_PUZZLE_HASH,
+                PuzzleInfo::new("dl_metadata_updater", &[]),
+            );
+        }
+
+        registry
+    }
+
+    /// Registers a puzzle by its mod hash, replacing any existing entry.
+    pub fn register(&mut self, mod_hash: TreeHash, info: PuzzleInfo) -> Option<PuzzleInfo> {
+        self.puzzles.insert(mod_hash, info)
+    }
+
+    /// Returns the info for a given mod hash, if it has been registered.
+    pub fn get(&self, mod_hash: TreeHash) -> Option<&PuzzleInfo> {
+        self.puzzles.get(&mod_hash)
+    }
+
+    /// Identifies a puzzle by its exact puzzle hash, or by its mod hash if it's curried.
+    ///
+    /// Some uncurried puzzles (such as the singleton launcher) are compiled into a form that
+    /// looks curried, so the full puzzle hash is checked first.
+    pub fn identify(&self, puzzle: &Puzzle) -> Option<&PuzzleInfo> {
+        self.get(puzzle.curried_puzzle_hash())
+            .or_else(|| self.get(puzzle.mod_hash()))
+    }
+
+    /// Formats a puzzle as a nested call expression, labeling each known layer and its curried arguments.
+    /// Unknown puzzles and non-puzzle arguments are shown by their tree hash, and short atoms as hex.
+    pub fn describe(&self, allocator: &Allocator, puzzle: NodePtr) -> String {
+        self.describe_nested(allocator, puzzle, 0)
+    }
+
+    fn describe_nested(&self, allocator: &Allocator, puzzle: NodePtr, depth: usize) -> String {
+        let parsed = Puzzle::parse(allocator, puzzle);
+
+        if let Some(info) = self.get(parsed.curried_puzzle_hash()) {
+            return info.name.clone();
+        }
+
+        let Some(info) = self.get(parsed.mod_hash()) else {
+            return format!("unknown({})", tree_hash(allocator, puzzle));
+        };
+
+        let Some(curried) = parsed.as_curried() else {
+            return info.name.clone();
+        };
+
+        let Some(args) = curried_args(allocator, curried.args) else {
+            return info.name.clone();
+        };
+
+        let args: Vec<String> = args
+            .into_iter()
+            .enumerate()
+            .map(|(index, arg)| {
+                let value = self.describe_value(allocator, arg, depth);
+                match info.args.get(index) {
+                    Some(name) => format!("{name}: {value}"),
+                    None => value,
+                }
+            })
+            .collect();
+
+        format!("{}({})", info.name, args.join(", "))
+    }
+
+    fn describe_value(&self, allocator: &Allocator, value: NodePtr, depth: usize) -> String {
+        match allocator.sexp(value) {
+            SExp::Atom if allocator.atom_len(value) <= 48 => {
+                format!("0x{}", hex::encode(allocator.atom(value)))
+            }
+            SExp::Pair(..)
+                if depth < MAX_DESCRIBE_DEPTH
+                    && self.identify(&Puzzle::parse(allocator, value)).is_some() =>
+            {
+                self.describe_nested(allocator, value, depth + 1)
+            }
+            _ => tree_hash(allocator, value).to_string(),
+        }
+    }
+}
+
+/// Decodes curried arguments in the form `(c (q . arg) rest)`, terminated by `1`.
+fn curried_args(allocator: &Allocator, mut args: NodePtr) -> Option<Vec<NodePtr>> {
+    let mut result = Vec::new();
+
+    loop {
+        match allocator.sexp(args) {
+            SExp::Atom => {
+                return (allocator.atom(args).as_ref() == [1]).then_some(result);
+            }
+            SExp::Pair(op, rest) => {
+                if !is_atom(allocator, op, &[4]) {
+                    return None;
+                }
+
+                let SExp::Pair(quoted, rest) = allocator.sexp(rest) else {
+                    return None;
+                };
+                let SExp::Pair(next, _) = allocator.sexp(rest) else {
+                    return None;
+                };
+                let SExp::Pair(q, value) = allocator.sexp(quoted) else {
+                    return None;
+                };
+
+                if !is_atom(allocator, q, &[1]) {
+                    return None;
+                }
+
+                result.push(value);
+                args = next;
+            }
+        }
+    }
+}
+
+/// Whether the node is an atom with the given value. Pairs are checked for first,
+/// since reading a pair as an atom panics.
+fn is_atom(allocator: &Allocator, node: NodePtr, value: &[u8]) -> bool {
+    match allocator.sexp(node) {
+        SExp::Atom => allocator.atom(node).as_ref() == value,
+        SExp::Pair(..) => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_sdk_test::test_secret_key;
+    use clvm_traits::{clvm_curried_args, ToClvm};
+    use clvm_utils::CurriedProgram;
+
+    use crate::{CatLayer, Layer, SpendContext, StandardLayer};
+
+    use super::*;
+
+    #[test]
+    fn test_describe_cat() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let pk = test_secret_key()?.public_key();
+
+        let layer = CatLayer::new(Bytes32::new([1; 32]), StandardLayer::new(pk));
+        let puzzle = layer.construct_puzzle(ctx)?;
+
+        let registry = PuzzleRegistry::standard();
+        let description = registry.describe(&ctx.allocator, puzzle);
+
+        assert_eq!(
+            description,
+            format!(
+                "cat_v2(MOD_HASH: 0x{}, TAIL_PROGRAM_HASH: 0x{}, INNER_PUZZLE: \
+                 p2_delegated_puzzle_or_hidden_puzzle(SYNTHETIC_PUBLIC_KEY: 0x{}))",
+                hex::encode(CAT_PUZZLE_HASH),
+                hex::encode([1; 32]),
+                hex::encode(pk.to_bytes())
+            )
+        );
+
+        let parsed = Puzzle::parse(&ctx.allocator, puzzle);
+        assert_eq!(
+            registry.identify(&parsed).map(|info| info.name.as_str()),
+            Some("cat_v2")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_uncurried() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+        let launcher = ctx.singleton_launcher()?;
+
+        let registry = PuzzleRegistry::standard();
+        assert_eq!(
+            registry.describe(&ctx.allocator, launcher),
+            "singleton_launcher"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_register_custom_puzzle() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+        let puzzle = (1, "custom").to_clvm(&mut allocator)?;
+        let puzzle_hash = tree_hash(&allocator, puzzle);
+
+        let mut registry = PuzzleRegistry::standard();
+        assert_eq!(
+            registry.describe(&allocator, puzzle),
+            format!("unknown({puzzle_hash})")
+        );
+
+        registry.register(puzzle_hash, PuzzleInfo::new("custom", &[]));
+        assert_eq!(registry.describe(&allocator, puzzle), "custom");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_malformed_curried_args() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+        let custom_mod = (1, "custom").to_clvm(&mut allocator)?;
+
+        let mut registry = PuzzleRegistry::new();
+        registry.register(
+            tree_hash(&allocator, custom_mod),
+            PuzzleInfo::new("custom", &["VALUE"]),
+        );
+
+        // A pair in place of the `c` operator, and then in place of the quote.
+        let pair_operator = ((1, 2), 1).to_clvm(&mut allocator)?;
+        let pair_quote = (4, (((1, 2), 5), (1, ()))).to_clvm(&mut allocator)?;
+
+        for args in [pair_operator, pair_quote] {
+            let puzzle = (2, ((1, custom_mod), (args, ()))).to_clvm(&mut allocator)?;
+            assert_eq!(registry.describe(&allocator, puzzle), "custom");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_deeply_nested() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+        let custom_mod = (1, "custom").to_clvm(&mut allocator)?;
+
+        let mut registry = PuzzleRegistry::new();
+        registry.register(
+            tree_hash(&allocator, custom_mod),
+            PuzzleInfo::new("custom", &["INNER"]),
+        );
+
+        let mut puzzle = NodePtr::NIL;
+
+        for _ in 0..10_000 {
+            puzzle = CurriedProgram {
+                program: custom_mod,
+                args: clvm_curried_args!(puzzle),
+            }
+            .to_clvm(&mut allocator)?;
+        }
+
+        let description = registry.describe(&allocator, puzzle);
+        assert_eq!(
+            description.matches("custom(").count(),
+            MAX_DESCRIBE_DEPTH + 1
+        );
+
+        Ok(())
+    }
+}
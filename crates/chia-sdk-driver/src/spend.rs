@@ -1,5 +1,10 @@
+use chia_sdk_types::Conditions;
+use clvm_traits::clvm_quote;
 use clvmr::NodePtr;
 
+use crate::{DriverError, SpendContext};
+
+/// A puzzle and solution pair, used both for coin spends and for inner puzzle spends.
 #[derive(Debug, Clone, Copy)]
 #[must_use]
 pub struct Spend {
@@ -11,4 +16,55 @@ impl Spend {
     pub fn new(puzzle: NodePtr, solution: NodePtr) -> Self {
         Self { puzzle, solution }
     }
+
+    /// Creates a spend whose puzzle is the quoted list of conditions, with an empty solution.
+    /// When run, it outputs the conditions as-is.
+    pub fn conditions(ctx: &mut SpendContext, conditions: Conditions) -> Result<Self, DriverError> {
+        Ok(Self::new(
+            ctx.alloc(&clvm_quote!(conditions))?,
+            NodePtr::NIL,
+        ))
+    }
+}
+
+impl From<(NodePtr, NodePtr)> for Spend {
+    fn from((puzzle, solution): (NodePtr, NodePtr)) -> Self {
+        Self::new(puzzle, solution)
+    }
+}
+
+impl From<Spend> for (NodePtr, NodePtr) {
+    fn from(spend: Spend) -> Self {
+        (spend.puzzle, spend.solution)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes32;
+    use chia_sdk_types::Condition;
+
+    use super::*;
+
+    #[test]
+    fn test_conditions_spend() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let conditions = Conditions::new()
+            .create_coin(Bytes32::default(), 1, Vec::new())
+            .reserve_fee(1);
+
+        let spend = Spend::conditions(ctx, conditions)?;
+        let output = ctx.run(spend.puzzle, spend.solution)?;
+        let output = ctx.extract::<Vec<Condition>>(output)?;
+
+        assert_eq!(output.len(), 2);
+        assert!(matches!(output[0], Condition::CreateCoin(..)));
+        assert!(matches!(output[1], Condition::ReserveFee(..)));
+
+        let (puzzle, solution) = spend.into();
+        assert_eq!(Spend::from((puzzle, solution)).puzzle, spend.puzzle);
+
+        Ok(())
+    }
 }
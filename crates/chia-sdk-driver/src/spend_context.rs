@@ -1,11 +1,11 @@
 use std::collections::HashMap;
 
-use chia_protocol::{Coin, CoinSpend, Program};
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
 use chia_puzzles::{
     cat::{
-        CAT_PUZZLE, CAT_PUZZLE_HASH, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE,
-        EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH, GENESIS_BY_COIN_ID_TAIL_PUZZLE,
-        GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+        CAT_PUZZLE, CAT_PUZZLE_HASH, CAT_PUZZLE_HASH_V1, CAT_PUZZLE_V1,
+        EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+        GENESIS_BY_COIN_ID_TAIL_PUZZLE, GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
     },
     did::{DID_INNER_PUZZLE, DID_INNER_PUZZLE_HASH},
     nft::{
@@ -14,24 +14,104 @@ use chia_puzzles::{
         NFT_OWNERSHIP_LAYER_PUZZLE_HASH, NFT_ROYALTY_TRANSFER_PUZZLE,
         NFT_ROYALTY_TRANSFER_PUZZLE_HASH, NFT_STATE_LAYER_PUZZLE, NFT_STATE_LAYER_PUZZLE_HASH,
     },
-    offer::{SETTLEMENT_PAYMENTS_PUZZLE, SETTLEMENT_PAYMENTS_PUZZLE_HASH},
+    offer::{
+        SETTLEMENT_PAYMENTS_PUZZLE, SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+        SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1, SETTLEMENT_PAYMENTS_PUZZLE_V1,
+    },
     singleton::{
         SINGLETON_LAUNCHER_PUZZLE, SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE,
         SINGLETON_TOP_LAYER_PUZZLE_HASH,
     },
-    standard::{STANDARD_PUZZLE, STANDARD_PUZZLE_HASH},
+    standard::{
+        DEFAULT_HIDDEN_PUZZLE, DEFAULT_HIDDEN_PUZZLE_HASH, STANDARD_PUZZLE, STANDARD_PUZZLE_HASH,
+    },
 };
-use chia_sdk_types::run_puzzle;
+use chia_sdk_types::{run_puzzle, run_puzzle_with_options, RunPuzzleOptions};
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{tree_hash, TreeHash};
-use clvmr::{serde::node_from_bytes, Allocator, NodePtr};
+use clvmr::{
+    serde::{node_from_bytes, node_to_bytes},
+    Allocator, NodePtr,
+};
+use tracing::debug;
 
 use crate::{
-    DriverError, Spend, P2_DELEGATED_CONDITIONS_PUZZLE, P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
-    P2_DELEGATED_SINGLETON_PUZZLE, P2_DELEGATED_SINGLETON_PUZZLE_HASH, P2_ONE_OF_MANY_PUZZLE,
-    P2_ONE_OF_MANY_PUZZLE_HASH, P2_SINGLETON_PUZZLE, P2_SINGLETON_PUZZLE_HASH,
+    deduplicate_coin_spends, DriverError, Spend, GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE,
+    GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH, P2_DELEGATED_CONDITIONS_PUZZLE,
+    P2_DELEGATED_CONDITIONS_PUZZLE_HASH, P2_DELEGATED_SINGLETON_PUZZLE,
+    P2_DELEGATED_SINGLETON_PUZZLE_HASH, P2_ONE_OF_MANY_PUZZLE, P2_ONE_OF_MANY_PUZZLE_HASH,
+    P2_SINGLETON_PUZZLE, P2_SINGLETON_PUZZLE_HASH,
 };
 
+/// Generates a cached accessor on [`SpendContext`] for each puzzle in the list.
+///
+/// The list covers the puzzles that the drivers in this crate use, and is maintained by hand,
+/// since chia-puzzles doesn't expose a list of the puzzles it bundles to generate it from.
+macro_rules! puzzle_accessors {
+    ( $( $( #[doc = $doc:literal] )* $name:ident => $puzzle:ident, $puzzle_hash:ident; )* ) => {
+        impl SpendContext {
+            $(
+                $( #[doc = $doc] )*
+                pub fn $name(&mut self) -> Result<NodePtr, DriverError> {
+                    self.puzzle($puzzle_hash, &$puzzle)
+                }
+            )*
+        }
+    };
+}
+
+puzzle_accessors! {
+    /// Allocate the standard puzzle and return its pointer.
+    standard_puzzle => STANDARD_PUZZLE, STANDARD_PUZZLE_HASH;
+    /// Allocate the default hidden puzzle of the standard puzzle and return its pointer.
+    default_hidden_puzzle => DEFAULT_HIDDEN_PUZZLE, DEFAULT_HIDDEN_PUZZLE_HASH;
+    /// Allocate the CAT puzzle and return its pointer.
+    cat_puzzle => CAT_PUZZLE, CAT_PUZZLE_HASH;
+    /// Allocate the legacy CAT1 puzzle and return its pointer.
+    cat_puzzle_v1 => CAT_PUZZLE_V1, CAT_PUZZLE_HASH_V1;
+    /// Allocate the DID inner puzzle and return its pointer.
+    did_inner_puzzle => DID_INNER_PUZZLE, DID_INNER_PUZZLE_HASH;
+    /// Allocate the NFT intermediate launcher puzzle and return its pointer.
+    nft_intermediate_launcher =>
+        NFT_INTERMEDIATE_LAUNCHER_PUZZLE, NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH;
+    /// Allocate the NFT royalty transfer puzzle and return its pointer.
+    nft_royalty_transfer => NFT_ROYALTY_TRANSFER_PUZZLE, NFT_ROYALTY_TRANSFER_PUZZLE_HASH;
+    /// Allocate the NFT metadata updater puzzle and return its pointer.
+    nft_metadata_updater => NFT_METADATA_UPDATER_PUZZLE, NFT_METADATA_UPDATER_PUZZLE_HASH;
+    /// Allocate the NFT ownership layer puzzle and return its pointer.
+    nft_ownership_layer => NFT_OWNERSHIP_LAYER_PUZZLE, NFT_OWNERSHIP_LAYER_PUZZLE_HASH;
+    /// Allocate the NFT state layer puzzle and return its pointer.
+    nft_state_layer => NFT_STATE_LAYER_PUZZLE, NFT_STATE_LAYER_PUZZLE_HASH;
+    /// Allocate the singleton top layer puzzle and return its pointer.
+    singleton_top_layer => SINGLETON_TOP_LAYER_PUZZLE, SINGLETON_TOP_LAYER_PUZZLE_HASH;
+    /// Allocate the singleton launcher puzzle and return its pointer.
+    singleton_launcher => SINGLETON_LAUNCHER_PUZZLE, SINGLETON_LAUNCHER_PUZZLE_HASH;
+    /// Allocate the multi-issuance TAIL puzzle and return its pointer.
+    everything_with_signature_tail_puzzle =>
+        EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE, EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH;
+    /// Allocate the single-issuance TAIL puzzle and return its pointer.
+    genesis_by_coin_id_tail_puzzle =>
+        GENESIS_BY_COIN_ID_TAIL_PUZZLE, GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH;
+    /// Allocate the genesis by puzzle hash TAIL puzzle and return its pointer.
+    genesis_by_puzzle_hash_tail_puzzle =>
+        GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE, GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH;
+    /// Allocate the settlement payments puzzle and return its pointer.
+    settlement_payments_puzzle => SETTLEMENT_PAYMENTS_PUZZLE, SETTLEMENT_PAYMENTS_PUZZLE_HASH;
+    /// Allocate the legacy settlement payments puzzle (used by CAT1 offers) and return its pointer.
+    settlement_payments_puzzle_v1 =>
+        SETTLEMENT_PAYMENTS_PUZZLE_V1, SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1;
+    /// Allocate the p2 delegated conditions puzzle and return its pointer.
+    p2_delegated_conditions_puzzle =>
+        P2_DELEGATED_CONDITIONS_PUZZLE, P2_DELEGATED_CONDITIONS_PUZZLE_HASH;
+    /// Allocate the p2 one of many puzzle and return its pointer.
+    p2_one_of_many_puzzle => P2_ONE_OF_MANY_PUZZLE, P2_ONE_OF_MANY_PUZZLE_HASH;
+    /// Allocate the p2 singleton puzzle and return its pointer.
+    p2_singleton_puzzle => P2_SINGLETON_PUZZLE, P2_SINGLETON_PUZZLE_HASH;
+    /// Allocate the p2 delegated singleton puzzle and return its pointer.
+    p2_delegated_singleton_puzzle =>
+        P2_DELEGATED_SINGLETON_PUZZLE, P2_DELEGATED_SINGLETON_PUZZLE_HASH;
+}
+
 /// A wrapper around [`Allocator`] that caches puzzles and keeps track of a list of [`CoinSpend`].
 /// It's used to construct spend bundles in an easy and efficient way.
 #[derive(Debug, Default)]
@@ -57,15 +137,60 @@ impl SpendContext {
 
     /// Adds a [`CoinSpend`] to the collection.
     pub fn insert(&mut self, coin_spend: CoinSpend) {
+        debug!(coin_id = %coin_spend.coin.coin_id(), "inserting coin spend");
         self.coin_spends.push(coin_spend);
     }
 
+    /// Moves the pending [`CoinSpend`] of another context into this one, so that spends which were
+    /// built separately can be combined into a single spend bundle.
+    ///
+    /// Spends that are already in this context are only included once, but conflicting spends of
+    /// the same coin are an error, and in that case nothing is added. Any other pointers into the
+    /// other context's allocator must be copied over with [`SpendContext::import`] first.
+    pub fn extend(&mut self, other: SpendContext) -> Result<(), DriverError> {
+        let existing: HashMap<Bytes32, &CoinSpend> = self
+            .coin_spends
+            .iter()
+            .map(|coin_spend| (coin_spend.coin.coin_id(), coin_spend))
+            .collect();
+
+        let mut coin_spends = Vec::new();
+
+        for coin_spend in deduplicate_coin_spends(other.coin_spends)? {
+            let coin_id = coin_spend.coin.coin_id();
+
+            match existing.get(&coin_id) {
+                Some(&spend) if *spend == coin_spend => {}
+                Some(_) => return Err(DriverError::ConflictingSpend(coin_id)),
+                None => coin_spends.push(coin_spend),
+            }
+        }
+
+        for coin_spend in coin_spends {
+            self.insert(coin_spend);
+        }
+
+        Ok(())
+    }
+
+    /// Copies a value from another allocator into this context, and returns its pointer.
+    pub fn import(&mut self, allocator: &Allocator, ptr: NodePtr) -> Result<NodePtr, DriverError> {
+        let bytes = node_to_bytes(allocator, ptr)?;
+        Ok(node_from_bytes(&mut self.allocator, &bytes)?)
+    }
+
     /// Serializes a [`Spend`] and adds it to the list of [`CoinSpend`].
     pub fn spend(&mut self, coin: Coin, spend: Spend) -> Result<(), DriverError> {
+        let coin_spend = self.coin_spend(coin, spend)?;
+        self.insert(coin_spend);
+        Ok(())
+    }
+
+    /// Serializes a [`Spend`] into a [`CoinSpend`], without adding it to the list.
+    pub fn coin_spend(&mut self, coin: Coin, spend: Spend) -> Result<CoinSpend, DriverError> {
         let puzzle_reveal = self.serialize(&spend.puzzle)?;
         let solution = self.serialize(&spend.solution)?;
-        self.insert(CoinSpend::new(coin, puzzle_reveal, solution));
-        Ok(())
+        Ok(CoinSpend::new(coin, puzzle_reveal, solution))
     }
 
     /// Allocate a new node and return its pointer.
@@ -94,6 +219,21 @@ impl SpendContext {
         Ok(run_puzzle(&mut self.allocator, puzzle, solution)?)
     }
 
+    /// Run a puzzle with a solution using the given cost limit and dialect flags, and return the result.
+    pub fn run_with_options(
+        &mut self,
+        puzzle: NodePtr,
+        solution: NodePtr,
+        options: RunPuzzleOptions,
+    ) -> Result<NodePtr, DriverError> {
+        Ok(run_puzzle_with_options(
+            &mut self.allocator,
+            puzzle,
+            solution,
+            options,
+        )?)
+    }
+
     /// Serialize a value and return a `Program`.
     pub fn serialize<T>(&mut self, value: &T) -> Result<Program, DriverError>
     where
@@ -103,112 +243,6 @@ impl SpendContext {
         Ok(Program::from_clvm(&self.allocator, ptr)?)
     }
 
-    /// Allocate the standard puzzle and return its pointer.
-    pub fn standard_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(STANDARD_PUZZLE_HASH, &STANDARD_PUZZLE)
-    }
-
-    /// Allocate the CAT puzzle and return its pointer.
-    pub fn cat_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(CAT_PUZZLE_HASH, &CAT_PUZZLE)
-    }
-
-    /// Allocate the DID inner puzzle and return its pointer.
-    pub fn did_inner_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(DID_INNER_PUZZLE_HASH, &DID_INNER_PUZZLE)
-    }
-
-    /// Allocate the NFT intermediate launcher puzzle and return its pointer.
-    pub fn nft_intermediate_launcher(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH,
-            &NFT_INTERMEDIATE_LAUNCHER_PUZZLE,
-        )
-    }
-
-    /// Allocate the NFT royalty transfer puzzle and return its pointer.
-    pub fn nft_royalty_transfer(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
-            &NFT_ROYALTY_TRANSFER_PUZZLE,
-        )
-    }
-
-    /// Allocate the NFT metadata updater puzzle and return its pointer.
-    pub fn nft_metadata_updater(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            NFT_METADATA_UPDATER_PUZZLE_HASH,
-            &NFT_METADATA_UPDATER_PUZZLE,
-        )
-    }
-
-    /// Allocate the NFT ownership layer puzzle and return its pointer.
-    pub fn nft_ownership_layer(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(NFT_OWNERSHIP_LAYER_PUZZLE_HASH, &NFT_OWNERSHIP_LAYER_PUZZLE)
-    }
-
-    /// Allocate the NFT state layer puzzle and return its pointer.
-    pub fn nft_state_layer(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(NFT_STATE_LAYER_PUZZLE_HASH, &NFT_STATE_LAYER_PUZZLE)
-    }
-
-    /// Allocate the singleton top layer puzzle and return its pointer.
-    pub fn singleton_top_layer(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(SINGLETON_TOP_LAYER_PUZZLE_HASH, &SINGLETON_TOP_LAYER_PUZZLE)
-    }
-
-    /// Allocate the singleton launcher puzzle and return its pointer.
-    pub fn singleton_launcher(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(SINGLETON_LAUNCHER_PUZZLE_HASH, &SINGLETON_LAUNCHER_PUZZLE)
-    }
-
-    /// Allocate the multi-issuance TAIL puzzle and return its pointer.
-    pub fn everything_with_signature_tail_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
-            &EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE,
-        )
-    }
-
-    /// Allocate the single-issuance TAIL puzzle and return its pointer.
-    pub fn genesis_by_coin_id_tail_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
-            &GENESIS_BY_COIN_ID_TAIL_PUZZLE,
-        )
-    }
-
-    /// Allocate the settlement payments puzzle and return its pointer.
-    pub fn settlement_payments_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(SETTLEMENT_PAYMENTS_PUZZLE_HASH, &SETTLEMENT_PAYMENTS_PUZZLE)
-    }
-
-    /// Allocate the p2 delegated conditions puzzle and return its pointer.
-    pub fn p2_delegated_conditions_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
-            &P2_DELEGATED_CONDITIONS_PUZZLE,
-        )
-    }
-
-    /// Allocate the p2 one of many puzzle and return its pointer.
-    pub fn p2_one_of_many_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(P2_ONE_OF_MANY_PUZZLE_HASH, &P2_ONE_OF_MANY_PUZZLE)
-    }
-
-    /// Allocate the p2 singleton puzzle and return its pointer.
-    pub fn p2_singleton_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(P2_SINGLETON_PUZZLE_HASH, &P2_SINGLETON_PUZZLE)
-    }
-
-    /// Allocate the p2 delegated singleton puzzle and return its pointer.
-    pub fn p2_delegated_singleton_puzzle(&mut self) -> Result<NodePtr, DriverError> {
-        self.puzzle(
-            P2_DELEGATED_SINGLETON_PUZZLE_HASH,
-            &P2_DELEGATED_SINGLETON_PUZZLE,
-        )
-    }
-
     /// Preload a puzzle into the cache.
     pub fn preload(&mut self, puzzle_hash: TreeHash, ptr: NodePtr) {
         self.puzzles.insert(puzzle_hash, ptr);
@@ -253,3 +287,170 @@ impl From<Allocator> for SpendContext {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_sdk_test::Simulator;
+    use chia_sdk_types::Conditions;
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    type Accessor = fn(&mut SpendContext) -> Result<NodePtr, DriverError>;
+
+    #[test]
+    fn test_puzzle_accessors() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let accessors: [(Accessor, TreeHash); 21] = [
+            (SpendContext::standard_puzzle, STANDARD_PUZZLE_HASH),
+            (
+                SpendContext::default_hidden_puzzle,
+                DEFAULT_HIDDEN_PUZZLE_HASH,
+            ),
+            (SpendContext::cat_puzzle, CAT_PUZZLE_HASH),
+            (SpendContext::cat_puzzle_v1, CAT_PUZZLE_HASH_V1),
+            (SpendContext::did_inner_puzzle, DID_INNER_PUZZLE_HASH),
+            (
+                SpendContext::nft_intermediate_launcher,
+                NFT_INTERMEDIATE_LAUNCHER_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::nft_royalty_transfer,
+                NFT_ROYALTY_TRANSFER_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::nft_metadata_updater,
+                NFT_METADATA_UPDATER_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::nft_ownership_layer,
+                NFT_OWNERSHIP_LAYER_PUZZLE_HASH,
+            ),
+            (SpendContext::nft_state_layer, NFT_STATE_LAYER_PUZZLE_HASH),
+            (
+                SpendContext::singleton_top_layer,
+                SINGLETON_TOP_LAYER_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::singleton_launcher,
+                SINGLETON_LAUNCHER_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::everything_with_signature_tail_puzzle,
+                EVERYTHING_WITH_SIGNATURE_TAIL_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::genesis_by_coin_id_tail_puzzle,
+                GENESIS_BY_COIN_ID_TAIL_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::genesis_by_puzzle_hash_tail_puzzle,
+                GENESIS_BY_PUZZLE_HASH_TAIL_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::settlement_payments_puzzle,
+                SETTLEMENT_PAYMENTS_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::settlement_payments_puzzle_v1,
+                SETTLEMENT_PAYMENTS_PUZZLE_HASH_V1,
+            ),
+            (
+                SpendContext::p2_delegated_conditions_puzzle,
+                P2_DELEGATED_CONDITIONS_PUZZLE_HASH,
+            ),
+            (
+                SpendContext::p2_one_of_many_puzzle,
+                P2_ONE_OF_MANY_PUZZLE_HASH,
+            ),
+            (SpendContext::p2_singleton_puzzle, P2_SINGLETON_PUZZLE_HASH),
+            (
+                SpendContext::p2_delegated_singleton_puzzle,
+                P2_DELEGATED_SINGLETON_PUZZLE_HASH,
+            ),
+        ];
+
+        for (accessor, puzzle_hash) in accessors {
+            let ptr = accessor(ctx)?;
+            assert_eq!(ctx.tree_hash(ptr), puzzle_hash);
+            assert_eq!(ctx.get_puzzle(&puzzle_hash), Some(ptr));
+            assert_eq!(accessor(ctx)?, ptr);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_context() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let other_coin = sim.new_coin(puzzle_hash, 2);
+        let p2 = StandardLayer::new(pk);
+
+        let mut ctx = SpendContext::new();
+        p2.spend(
+            &mut ctx,
+            coin,
+            Conditions::new().create_coin(puzzle_hash, 1, Vec::new()),
+        )?;
+
+        let mut other = SpendContext::new();
+        let memo = other.alloc(&"Hello, world!")?;
+        p2.spend(
+            &mut other,
+            other_coin,
+            Conditions::new().create_coin(puzzle_hash, 2, Vec::new()),
+        )?;
+
+        let imported = ctx.import(&other.allocator, memo)?;
+        assert_eq!(ctx.extract::<String>(imported)?, "Hello, world!");
+
+        // Spending the same coin in the same way again doesn't add a duplicate.
+        let duplicate = ctx.iter().cloned().collect::<Vec<_>>();
+        let mut copy = SpendContext::new();
+        copy.insert(duplicate[0].clone());
+
+        ctx.extend(other)?;
+        ctx.extend(copy)?;
+        assert_eq!(ctx.iter().count(), 2);
+
+        // But spending it in a different way is a conflict.
+        let mut conflict = SpendContext::new();
+        p2.spend(&mut conflict, coin, Conditions::new())?;
+        assert!(matches!(
+            ctx.extend(conflict),
+            Err(DriverError::ConflictingSpend(coin_id)) if coin_id == coin.coin_id()
+        ));
+        assert_eq!(ctx.iter().count(), 2);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extend_context_with_existing_duplicate() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (_sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let other_coin = sim.new_coin(puzzle_hash, 2);
+        let p2 = StandardLayer::new(pk);
+
+        // The context already contains the same spend twice, since it was inserted directly.
+        let mut ctx = SpendContext::new();
+        p2.spend(&mut ctx, coin, Conditions::new())?;
+        let duplicate = ctx.iter().next().cloned().expect("missing spend");
+        ctx.insert(duplicate);
+
+        let mut other = SpendContext::new();
+        p2.spend(&mut other, other_coin, Conditions::new())?;
+        let other_spend = other.iter().next().cloned().expect("missing spend");
+
+        ctx.extend(other)?;
+        assert_eq!(ctx.iter().count(), 3);
+        assert_eq!(ctx.iter().last(), Some(&other_spend));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_sdk_types::{run_puzzle, Condition, Conditions};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::{Allocator, NodePtr};
+
+use crate::{
+    coin_announcement_ids, puzzle_announcement_ids, DriverError, RemarkPayload, SpendContext,
+};
+
+/// The outputs of a single [`CoinSpend`], computed before the spend bundle is finalized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlannedSpend {
+    /// The coin being spent.
+    pub coin: Coin,
+    /// The coins that will be created by this spend, in the order of the `CREATE_COIN` conditions.
+    pub created_coins: Vec<Coin>,
+    /// The ids of the coin announcements that other spends can assert with `ASSERT_COIN_ANNOUNCEMENT`.
+    pub coin_announcement_ids: Vec<Bytes32>,
+    /// The ids of the puzzle announcements that other spends can assert with `ASSERT_PUZZLE_ANNOUNCEMENT`.
+    pub puzzle_announcement_ids: Vec<Bytes32>,
+    /// The structured application data attached to this spend with `REMARK` conditions.
+    pub remark_payloads: Vec<RemarkPayload>,
+}
+
+impl PlannedSpend {
+    /// Runs the coin spend and collects the coins and announcements that it outputs.
+    pub fn from_coin_spend(
+        allocator: &mut Allocator,
+        coin_spend: &CoinSpend,
+    ) -> Result<Self, DriverError> {
+        let coin = coin_spend.coin;
+        let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+        let solution = coin_spend.solution.to_clvm(allocator)?;
+        let output = run_puzzle(allocator, puzzle, solution)?;
+        let conditions = Conditions::<NodePtr>::from_clvm(allocator, output)?;
+
+        let created_coins = conditions
+            .as_ref()
+            .iter()
+            .filter_map(|condition| match condition {
+                Condition::CreateCoin(create_coin) => Some(Coin::new(
+                    coin.coin_id(),
+                    create_coin.puzzle_hash,
+                    create_coin.amount,
+                )),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Self {
+            coin,
+            created_coins,
+            coin_announcement_ids: coin_announcement_ids(coin.coin_id(), &conditions),
+            puzzle_announcement_ids: puzzle_announcement_ids(coin.puzzle_hash, &conditions),
+            remark_payloads: RemarkPayload::parse_all(allocator, conditions.as_ref()),
+        })
+    }
+}
+
+impl SpendContext {
+    /// Computes the outputs of each of the pending coin spends, without removing them.
+    ///
+    /// This can be used to find the ids of coins and announcements ahead of time, so that
+    /// other spends in the same bundle can assert on them.
+    pub fn plan(&mut self) -> Result<Vec<PlannedSpend>, DriverError> {
+        let coin_spends: Vec<CoinSpend> = self.iter().cloned().collect();
+
+        coin_spends
+            .iter()
+            .map(|coin_spend| PlannedSpend::from_coin_spend(&mut self.allocator, coin_spend))
+            .collect()
+    }
+
+    /// Computes the coins that will be created by all of the pending coin spends.
+    pub fn planned_coins(&mut self) -> Result<Vec<Coin>, DriverError> {
+        Ok(self
+            .plan()?
+            .into_iter()
+            .flat_map(|spend| spend.created_coins)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_protocol::Bytes;
+    use chia_sdk_test::Simulator;
+
+    use crate::StandardLayer;
+
+    use super::*;
+
+    #[test]
+    fn test_plan_spends() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(3)?;
+        let other = sim.new_coin(puzzle_hash, 1);
+        let p2 = StandardLayer::new(pk);
+
+        p2.spend(
+            ctx,
+            coin,
+            Conditions::new()
+                .create_coin(puzzle_hash, 1, Vec::new())
+                .create_coin(puzzle_hash, 2, Vec::new())
+                .create_coin_announcement(Bytes::new(b"coin".to_vec()))
+                .create_puzzle_announcement(Bytes::new(b"puzzle".to_vec())),
+        )?;
+
+        let plan = ctx.plan()?;
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].coin, coin);
+        assert_eq!(
+            plan[0].created_coins,
+            [
+                Coin::new(coin.coin_id(), puzzle_hash, 1),
+                Coin::new(coin.coin_id(), puzzle_hash, 2)
+            ]
+        );
+
+        // The second spend asserts the announcements of the first, and spends one of its outputs.
+        let child = plan[0].created_coins[0];
+        p2.spend(
+            ctx,
+            other,
+            Conditions::new()
+                .assert_coin_announcement(plan[0].coin_announcement_ids[0])
+                .assert_puzzle_announcement(plan[0].puzzle_announcement_ids[0])
+                .assert_concurrent_spend(child.coin_id()),
+        )?;
+        p2.spend(ctx, child, Conditions::new().reserve_fee(1))?;
+
+        assert_eq!(ctx.planned_coins()?, plan[0].created_coins);
+
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        assert!(sim.coin_state(child.coin_id()).is_some());
+        assert!(sim.coin_state(plan[0].created_coins[1].coin_id()).is_some());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,253 @@
+use chia_bls::SecretKey;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+use chia_puzzles::{nft::NftMetadata, standard::StandardArgs};
+use chia_sdk_types::{run_puzzle, Conditions};
+use chia_sdk_utils::CoinJson;
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::Allocator;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Cat, DriverError, Launcher, NftMint, Puzzle, PuzzleRegistry, SpendContext, StandardLayer,
+};
+
+/// A single coin spend produced from fixed inputs, along with the conditions it outputs.
+///
+/// The values are deterministic, so other implementations can construct the same spend
+/// and compare each field to check that they're compatible with this SDK.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TestVector {
+    /// The primitive that produced the spend, such as `cat`.
+    pub primitive: &'static str,
+    /// The name of the outermost puzzle, as labeled by [`PuzzleRegistry::standard`].
+    pub puzzle_name: String,
+    pub coin: Coin,
+    pub mod_hash: Bytes32,
+    pub puzzle_reveal: Program,
+    pub solution: Program,
+    pub conditions: Program,
+}
+
+/// The secret key that every test vector is derived from.
+pub fn test_vector_secret_key() -> SecretKey {
+    SecretKey::from_seed(&[1; 32])
+}
+
+/// Generates the canonical test vectors for each of the primitives, in a fixed order.
+pub fn test_vectors() -> Result<Vec<TestVector>, DriverError> {
+    let registry = PuzzleRegistry::standard();
+    let pk = test_vector_secret_key().public_key();
+    let p2 = StandardLayer::new(pk);
+    let puzzle_hash: Bytes32 = StandardArgs::curry_tree_hash(pk).into();
+
+    let coin = |index: u8, amount: u64| Coin::new(Bytes32::new([index; 32]), puzzle_hash, amount);
+
+    let mut vectors = Vec::new();
+    let ctx = &mut SpendContext::new();
+
+    // Standard
+    p2.spend(
+        ctx,
+        coin(0, 1000),
+        Conditions::new()
+            .create_coin(puzzle_hash, 900, vec![puzzle_hash.into()])
+            .reserve_fee(100),
+    )?;
+    collect(ctx, &registry, "standard", &mut vectors)?;
+
+    // CAT
+    let parent = coin(1, 1000);
+    let (issue_cat, _cat) = Cat::single_issuance_eve(
+        ctx,
+        parent.coin_id(),
+        1000,
+        Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+    )?;
+    p2.spend(ctx, parent, issue_cat)?;
+    collect(ctx, &registry, "cat", &mut vectors)?;
+
+    // DID
+    let parent = coin(2, 1);
+    let (create_did, did) = Launcher::new(parent.coin_id(), 1).create_simple_did(ctx, &p2)?;
+    p2.spend(ctx, parent, create_did)?;
+    let _did = did.update(ctx, &p2, Conditions::new())?;
+    collect(ctx, &registry, "did", &mut vectors)?;
+
+    // NFT
+    let parent = coin(3, 1);
+    let metadata = NftMetadata {
+        data_uris: vec!["https://example.com/nft.png".to_string()],
+        data_hash: Some(Bytes32::new([4; 32])),
+        ..Default::default()
+    };
+    let (mint_nft, nft) = Launcher::new(parent.coin_id(), 1)
+        .mint_nft(ctx, NftMint::new(metadata, puzzle_hash, 300, None))?;
+    p2.spend(ctx, parent, mint_nft)?;
+    let _nft = nft.transfer(ctx, &p2, puzzle_hash, Conditions::new())?;
+    collect(ctx, &registry, "nft", &mut vectors)?;
+
+    Ok(vectors)
+}
+
+/// A [`TestVector`] in the JSON format of the `test_data/test_vectors.json` fixture.
+/// Every byte field is hex encoded with a `0x` prefix, and the coin is in the same format as the RPCs.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectorJson {
+    pub primitive: String,
+    pub puzzle_name: String,
+    pub coin: CoinJson,
+    pub mod_hash: String,
+    pub puzzle_reveal: String,
+    pub solution: String,
+    pub conditions: String,
+}
+
+impl From<&TestVector> for TestVectorJson {
+    fn from(vector: &TestVector) -> Self {
+        Self {
+            primitive: vector.primitive.to_string(),
+            puzzle_name: vector.puzzle_name.clone(),
+            coin: CoinJson::from(&vector.coin),
+            mod_hash: encode_hex(&vector.mod_hash),
+            puzzle_reveal: encode_hex(vector.puzzle_reveal.as_ref()),
+            solution: encode_hex(vector.solution.as_ref()),
+            conditions: encode_hex(vector.conditions.as_ref()),
+        }
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn collect(
+    ctx: &mut SpendContext,
+    registry: &PuzzleRegistry,
+    primitive: &'static str,
+    vectors: &mut Vec<TestVector>,
+) -> Result<(), DriverError> {
+    let mut allocator = Allocator::new();
+
+    for CoinSpend {
+        coin,
+        puzzle_reveal,
+        solution,
+    } in ctx.take()
+    {
+        let puzzle = puzzle_reveal.to_clvm(&mut allocator)?;
+        let solution_ptr = solution.to_clvm(&mut allocator)?;
+        let output = run_puzzle(&mut allocator, puzzle, solution_ptr)?;
+
+        let parsed = Puzzle::parse(&allocator, puzzle);
+
+        vectors.push(TestVector {
+            primitive,
+            puzzle_name: registry
+                .identify(&parsed)
+                .map_or_else(|| "unknown".to_string(), |info| info.name.clone()),
+            coin,
+            mod_hash: parsed.mod_hash().into(),
+            puzzle_reveal,
+            solution,
+            conditions: Program::from_clvm(&allocator, output)?,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use anyhow::Context;
+    use chia_sdk_test::Simulator;
+
+    use super::*;
+
+    const FIXTURE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/test_vectors.json");
+
+    #[test]
+    fn test_vectors_match_fixture() -> anyhow::Result<()> {
+        let vectors: Vec<TestVectorJson> =
+            test_vectors()?.iter().map(TestVectorJson::from).collect();
+
+        // After an intentional change to the vectors, regenerate the fixture with `UPDATE_TEST_VECTORS=1`.
+        if env::var_os("UPDATE_TEST_VECTORS").is_some() {
+            fs::write(FIXTURE_PATH, serde_json::to_string_pretty(&vectors)? + "\n")?;
+        }
+
+        let fixture = fs::read_to_string(FIXTURE_PATH)
+            .with_context(|| format!("missing test vector fixture {FIXTURE_PATH}"))?;
+        assert_eq!(
+            serde_json::from_str::<Vec<TestVectorJson>>(&fixture)?,
+            vectors
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vectors_are_deterministic() -> anyhow::Result<()> {
+        let vectors = test_vectors()?;
+        assert_eq!(vectors, test_vectors()?);
+
+        let names: Vec<(&str, &str)> = vectors
+            .iter()
+            .map(|vector| (vector.primitive, vector.puzzle_name.as_str()))
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                ("standard", "p2_delegated_puzzle_or_hidden_puzzle"),
+                ("cat", "cat_v2"),
+                ("cat", "p2_delegated_puzzle_or_hidden_puzzle"),
+                ("did", "singleton_launcher"),
+                ("did", "singleton_top_layer_v1_1"),
+                ("did", "p2_delegated_puzzle_or_hidden_puzzle"),
+                ("did", "singleton_top_layer_v1_1"),
+                ("nft", "singleton_launcher"),
+                ("nft", "singleton_top_layer_v1_1"),
+                ("nft", "p2_delegated_puzzle_or_hidden_puzzle"),
+                ("nft", "singleton_top_layer_v1_1"),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_vectors_are_valid() -> anyhow::Result<()> {
+        let sk = test_vector_secret_key();
+        let vectors = test_vectors()?;
+
+        for primitive in ["standard", "cat", "did", "nft"] {
+            let mut sim = Simulator::new();
+
+            let coin_spends: Vec<CoinSpend> = vectors
+                .iter()
+                .filter(|vector| vector.primitive == primitive)
+                .map(|vector| {
+                    CoinSpend::new(
+                        vector.coin,
+                        vector.puzzle_reveal.clone(),
+                        vector.solution.clone(),
+                    )
+                })
+                .collect();
+
+            // Only the coins that aren't created within the same bundle need to exist beforehand.
+            let coin_ids: Vec<Bytes32> = coin_spends.iter().map(|cs| cs.coin.coin_id()).collect();
+            for coin_spend in &coin_spends {
+                if !coin_ids.contains(&coin_spend.coin.parent_coin_info) {
+                    sim.insert_coin(coin_spend.coin);
+                }
+            }
+
+            sim.spend_coins(coin_spends, &[sk.clone()])?;
+        }
+
+        Ok(())
+    }
+}
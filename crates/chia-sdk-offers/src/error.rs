@@ -1,5 +1,7 @@
 use std::{array::TryFromSliceError, io, num::TryFromIntError};
 
+use chia_protocol::Bytes32;
+use chia_sdk_driver::DriverError;
 use clvm_traits::{FromClvmError, ToClvmError};
 use thiserror::Error;
 
@@ -46,4 +48,21 @@ pub enum OfferError {
 
     #[error("Requested payment puzzle mismatch")]
     PuzzleMismatch,
+
+    #[error("Driver error: {0}")]
+    Driver(#[from] DriverError),
+
+    #[error("None of the offered coins can be spent by the given puzzle")]
+    NoCancellableCoin,
+
+    #[error("Requested payment puzzle {0} is not an XCH, CAT, or NFT settlement puzzle: {1}")]
+    UnsupportedAsset(Bytes32, String),
+
+    #[error(
+        "NFT {0} has a royalty of {1} ten thousandths, but offers don't pay NFT royalties yet"
+    )]
+    UnsupportedRoyalty(Bytes32, u16),
+
+    #[error("The total amount of an asset in the offer overflows a u64")]
+    AmountOverflow,
 }
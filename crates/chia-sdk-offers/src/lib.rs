@@ -2,6 +2,7 @@ mod compress;
 mod encode;
 mod error;
 mod offer;
+mod offer_assets;
 mod offer_builder;
 mod parsed_offer;
 
@@ -9,5 +10,6 @@ pub use compress::*;
 pub use encode::*;
 pub use error::*;
 pub use offer::*;
+pub use offer_assets::*;
 pub use offer_builder::*;
 pub use parsed_offer::*;
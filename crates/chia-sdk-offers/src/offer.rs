@@ -1,10 +1,16 @@
-use chia_protocol::{Bytes32, SpendBundle};
-use chia_puzzles::offer::SettlementPaymentsSolution;
-use chia_sdk_driver::Puzzle;
+use chia_protocol::{Bytes32, Coin, CoinSpend, SpendBundle};
+use chia_puzzles::{
+    cat::CatSolution, offer::SettlementPaymentsSolution, singleton::SingletonSolution,
+};
+use chia_sdk_driver::{
+    Cat, CatLayer, CatSpend, HashedPtr, Layer, Nft, NftInfo, Puzzle, SpendContext,
+    SpendWithConditions,
+};
+use chia_sdk_types::Conditions;
 use chia_traits::Streamable;
 use clvm_traits::{FromClvm, ToClvm};
 use clvm_utils::{tree_hash, ToTreeHash};
-use clvmr::Allocator;
+use clvmr::{Allocator, NodePtr};
 use indexmap::IndexMap;
 
 use crate::{
@@ -59,6 +65,92 @@ impl Offer {
         Self::decompress(&decode_offer_data(text)?)
     }
 
+    /// Returns the coins spent by the maker of the offer, excluding the requested payments.
+    /// Spending any of these coins in another transaction makes the offer invalid.
+    pub fn offered_coins(&self) -> Vec<Coin> {
+        self.offered_coin_spends()
+            .map(|coin_spend| coin_spend.coin)
+            .collect()
+    }
+
+    /// Cancels the offer on-chain, by spending one of the offered coins owned by the p2 puzzle back to itself.
+    /// The coin can be XCH, or a CAT or NFT whose inner p2 puzzle is the given one.
+    /// Any fee can be reserved with the extra conditions, and must be paid for by another coin.
+    ///
+    /// If the offer was never shared with anyone, it can be cancelled by discarding it instead.
+    pub fn cancel<P>(
+        &self,
+        ctx: &mut SpendContext,
+        p2: &P,
+        extra_conditions: Conditions,
+    ) -> Result<Coin, OfferError>
+    where
+        P: SpendWithConditions + ToTreeHash,
+    {
+        let p2_puzzle_hash: Bytes32 = p2.tree_hash().into();
+
+        for coin_spend in self.offered_coin_spends() {
+            let coin = coin_spend.coin;
+
+            if coin.puzzle_hash == p2_puzzle_hash {
+                let spend = p2.spend_with_conditions(
+                    ctx,
+                    extra_conditions.create_coin(p2_puzzle_hash, coin.amount, Vec::new()),
+                )?;
+                ctx.spend(coin, spend)?;
+                return Ok(coin);
+            }
+
+            let puzzle = ctx.alloc(&coin_spend.puzzle_reveal)?;
+            let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+            let solution = ctx.alloc(&coin_spend.solution)?;
+
+            if let Some(cat_layer) = CatLayer::<Puzzle>::parse_puzzle(&ctx.allocator, puzzle)? {
+                if cat_layer.inner_puzzle.curried_puzzle_hash() != p2_puzzle_hash.into() {
+                    continue;
+                }
+
+                let solution = CatSolution::<NodePtr>::from_clvm(&ctx.allocator, solution)?;
+                let cat = Cat::new(
+                    coin,
+                    solution.lineage_proof,
+                    cat_layer.asset_id,
+                    p2_puzzle_hash,
+                );
+
+                let inner_spend = p2.spend_with_conditions(
+                    ctx,
+                    extra_conditions.create_coin(
+                        p2_puzzle_hash,
+                        coin.amount,
+                        vec![p2_puzzle_hash.into()],
+                    ),
+                )?;
+                Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+                return Ok(coin);
+            }
+
+            if let Some((info, _)) = NftInfo::<HashedPtr>::parse(&ctx.allocator, puzzle)? {
+                if info.p2_puzzle_hash != p2_puzzle_hash {
+                    continue;
+                }
+
+                let solution = SingletonSolution::<NodePtr>::from_clvm(&ctx.allocator, solution)?;
+                let nft = Nft::new(coin, solution.lineage_proof, info);
+                nft.transfer(ctx, p2, p2_puzzle_hash, extra_conditions)?;
+                return Ok(coin);
+            }
+        }
+
+        Err(OfferError::NoCancellableCoin)
+    }
+
+    fn offered_coin_spends(&self) -> impl Iterator<Item = &CoinSpend> {
+        self.spend_bundle.coin_spends.iter().filter(|coin_spend| {
+            coin_spend.coin.parent_coin_info != Bytes32::default() || coin_spend.coin.amount != 0
+        })
+    }
+
     pub fn take(self, allocator: &mut Allocator) -> Result<OfferBuilder<Take>, OfferError> {
         Ok(self.parse(allocator)?.take())
     }
@@ -116,3 +208,155 @@ impl From<Offer> for SpendBundle {
         offer.spend_bundle
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::Program;
+    use chia_puzzles::{nft::NftMetadata, offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH};
+    use chia_sdk_driver::{Launcher, NftMint, StandardLayer};
+    use chia_sdk_test::{test_secret_keys, Simulator};
+
+    use super::*;
+
+    #[test]
+    fn test_cancel_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        p2.spend(
+            ctx,
+            coin,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, Vec::new()),
+        )?;
+        let offer = Offer::new(SpendBundle::new(ctx.take(), Signature::default()));
+        assert_eq!(offer.offered_coins(), vec![coin]);
+
+        assert_eq!(offer.cancel(ctx, &p2, Conditions::new())?, coin);
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let child = Coin::new(coin.coin_id(), puzzle_hash, 1000);
+        assert!(sim
+            .coin_state(coin.coin_id())
+            .expect("missing coin")
+            .spent_height
+            .is_some());
+        assert!(sim.coin_state(child.coin_id()).is_some());
+
+        // A puzzle which doesn't own any of the offered coins can't cancel the offer.
+        let other = StandardLayer::new(test_secret_keys(2)?[1].public_key());
+        assert!(matches!(
+            offer.cancel(ctx, &other, Conditions::new()),
+            Err(OfferError::NoCancellableCoin)
+        ));
+
+        Ok(())
+    }
+    #[test]
+    fn test_cancel_cat_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1000)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            coin.coin_id(),
+            1000,
+            Conditions::new().create_coin(puzzle_hash, 1000, vec![puzzle_hash.into()]),
+        )?;
+        p2.spend(ctx, coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+        let cat = cat.wrapped_child(puzzle_hash, 1000);
+
+        let inner_spend = p2.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, Vec::new()),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(cat, inner_spend)])?;
+        let offer = Offer::new(SpendBundle::new(ctx.take(), Signature::default()));
+        assert_eq!(offer.offered_coins(), vec![cat.coin]);
+
+        assert_eq!(offer.cancel(ctx, &p2, Conditions::new())?, cat.coin);
+        sim.spend_coins(ctx.take(), &[sk])?;
+
+        let child = cat.wrapped_child(puzzle_hash, 1000);
+        assert!(sim
+            .coin_state(cat.coin.coin_id())
+            .expect("missing coin")
+            .spent_height
+            .is_some());
+        assert!(sim.coin_state(child.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cancel_nft_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+        let (sk, pk, puzzle_hash, coin) = sim.new_p2(1)?;
+        let p2 = StandardLayer::new(pk);
+
+        let (mint_nft, nft) = Launcher::new(coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 0, None),
+        )?;
+        p2.spend(ctx, coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[sk.clone()])?;
+
+        let launcher_id = nft.info.launcher_id;
+        let nft_coin = nft.coin;
+        let _nft = nft.transfer(
+            ctx,
+            &p2,
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            Conditions::new(),
+        )?;
+        let offer = Offer::new(SpendBundle::new(ctx.take(), Signature::default()));
+        assert_eq!(offer.offered_coins(), vec![nft_coin]);
+
+        assert_eq!(offer.cancel(ctx, &p2, Conditions::new())?, nft_coin);
+        let coin_spends = ctx.take();
+
+        // The NFT is spent back to the same p2 puzzle.
+        let spend = &coin_spends[0];
+        let puzzle = ctx.alloc(&spend.puzzle_reveal)?;
+        let puzzle = Puzzle::parse(&ctx.allocator, puzzle);
+        let solution = ctx.alloc(&spend.solution)?;
+        let child =
+            Nft::<HashedPtr>::parse_child(&mut ctx.allocator, spend.coin, puzzle, solution)?
+                .expect("missing child");
+        assert_eq!(child.info.launcher_id, launcher_id);
+        assert_eq!(child.info.p2_puzzle_hash, puzzle_hash);
+
+        sim.spend_coins(coin_spends, &[sk])?;
+        assert!(sim.coin_state(child.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_deeply_nested_offer() -> anyhow::Result<()> {
+        let depth = 100_000;
+        let mut bytes = vec![0xff; depth];
+        bytes.extend(vec![0x80; depth + 1]);
+
+        let mut allocator = Allocator::new();
+        let program = Program::from(bytes);
+        let ptr = program.to_clvm(&mut allocator)?;
+
+        // The puzzle hash matches, so the nested solution reaches the settlement parser.
+        let coin = Coin::new(Bytes32::default(), tree_hash(&allocator, ptr).into(), 0);
+        let offer = Offer::new(SpendBundle::new(
+            vec![CoinSpend::new(coin, program.clone(), program)],
+            Signature::default(),
+        ));
+
+        assert!(offer.parse(&mut allocator).is_err());
+
+        Ok(())
+    }
+}
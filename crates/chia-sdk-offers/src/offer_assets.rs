@@ -0,0 +1,67 @@
+use chia_protocol::{Bytes32, Coin};
+use chia_sdk_driver::{Cat, HashedPtr, Nft};
+use indexmap::{IndexMap, IndexSet};
+
+use crate::OfferError;
+
+/// A summary of the assets on one side of an offer.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct OfferAssets {
+    /// The total amount of XCH, in mojos.
+    pub xch: u64,
+    /// The total amount of each CAT, keyed by asset id.
+    pub cats: IndexMap<Bytes32, u64>,
+    /// The launcher ids of the NFTs.
+    pub nfts: IndexSet<Bytes32>,
+}
+
+/// The coins that the maker of an offer has locked in the settlement payments puzzle,
+/// which can be claimed by whoever takes the offer.
+#[derive(Debug, Default, Clone)]
+pub struct OfferedCoins {
+    pub xch: Vec<Coin>,
+    pub cats: Vec<Cat>,
+    pub nfts: Vec<Nft<HashedPtr>>,
+}
+
+impl OfferAssets {
+    /// Adds to the total amount of XCH, failing if it overflows.
+    pub fn add_xch(&mut self, amount: u64) -> Result<(), OfferError> {
+        self.xch = self
+            .xch
+            .checked_add(amount)
+            .ok_or(OfferError::AmountOverflow)?;
+        Ok(())
+    }
+
+    /// Adds to the total amount of a CAT, failing if it overflows.
+    pub fn add_cat(&mut self, asset_id: Bytes32, amount: u64) -> Result<(), OfferError> {
+        let total = self.cats.entry(asset_id).or_insert(0);
+        *total = total
+            .checked_add(amount)
+            .ok_or(OfferError::AmountOverflow)?;
+        Ok(())
+    }
+}
+
+impl OfferedCoins {
+    /// Summarizes the offered coins by asset.
+    /// Fails if the total amount of an asset overflows a `u64`.
+    pub fn assets(&self) -> Result<OfferAssets, OfferError> {
+        let mut assets = OfferAssets::default();
+
+        for coin in &self.xch {
+            assets.add_xch(coin.amount)?;
+        }
+
+        for cat in &self.cats {
+            assets.add_cat(cat.asset_id, cat.coin.amount)?;
+        }
+
+        for nft in &self.nfts {
+            assets.nfts.insert(nft.info.launcher_id);
+        }
+
+        Ok(assets)
+    }
+}
@@ -1,12 +1,17 @@
+use std::collections::HashMap;
+
 use chia_protocol::{Bytes32, Coin, CoinSpend, SpendBundle};
 use chia_puzzles::offer::{NotarizedPayment, Payment, SettlementPaymentsSolution};
-use chia_sdk_driver::{DriverError, Puzzle, SpendContext};
+use chia_sdk_driver::{
+    deduplicate_coin_spends, Cat, CatLayer, CatSpend, DriverError, Layer, NftInfo, Puzzle, Spend,
+    SpendContext,
+};
 use chia_sdk_types::{announcement_id, AssertPuzzleAnnouncement};
-use clvm_traits::ToClvm;
+use clvm_traits::{FromClvm, ToClvm};
 use clvmr::Allocator;
 use indexmap::IndexMap;
 
-use crate::{Offer, ParsedOffer};
+use crate::{Offer, OfferError, OfferedCoins, ParsedOffer};
 
 #[derive(Debug, Clone)]
 pub struct OfferBuilder<T> {
@@ -80,6 +85,52 @@ impl OfferBuilder<Make> {
         Ok(self)
     }
 
+    /// Requests XCH payments, which are paid by the taker to the settlement payments puzzle.
+    pub fn request_xch(
+        self,
+        ctx: &mut SpendContext,
+        payments: Vec<Payment>,
+    ) -> Result<Self, DriverError> {
+        let puzzle = ctx.settlement_payments_puzzle()?;
+        self.request(ctx, &puzzle, payments)
+    }
+
+    /// Requests CAT payments, which are paid by the taker to the settlement payments puzzle
+    /// wrapped in the CAT layer for the given asset id.
+    pub fn request_cat(
+        self,
+        ctx: &mut SpendContext,
+        asset_id: Bytes32,
+        payments: Vec<Payment>,
+    ) -> Result<Self, DriverError> {
+        let settlement_puzzle = ctx.settlement_payments_puzzle()?;
+        let puzzle = CatLayer::new(asset_id, settlement_puzzle).construct_puzzle(ctx)?;
+        self.request(ctx, &puzzle, payments)
+    }
+
+    /// Requests an NFT, which is paid by the taker to the settlement payments puzzle
+    /// wrapped in the NFT's singleton, state, and ownership layers.
+    ///
+    /// The info must match the NFT's state once it has been transferred to the settlement payments puzzle,
+    /// including its metadata and DID owner. Its p2 puzzle hash is ignored.
+    ///
+    /// Royalty payments aren't created, so only NFTs without a royalty can be requested.
+    pub fn request_nft<M>(
+        self,
+        ctx: &mut SpendContext,
+        info: NftInfo<M>,
+        payments: Vec<Payment>,
+    ) -> Result<Self, OfferError>
+    where
+        M: ToClvm<Allocator> + FromClvm<Allocator>,
+    {
+        check_royalty(&info)?;
+
+        let settlement_puzzle = ctx.settlement_payments_puzzle()?;
+        let puzzle = info.into_layers(settlement_puzzle).construct_puzzle(ctx)?;
+        Ok(self.request(ctx, &puzzle, payments)?)
+    }
+
     /// This will create a new [`OfferBuilder`] with the requested payments frozen.
     /// It returns a list of announcements that can be asserted by the maker side.
     pub fn finish(self) -> (Vec<AssertPuzzleAnnouncement>, OfferBuilder<Partial>) {
@@ -145,13 +196,561 @@ impl OfferBuilder<Take> {
         )
     }
 
-    pub fn bundle(self, other_spend_bundle: SpendBundle) -> SpendBundle {
-        SpendBundle::aggregate(&[
-            SpendBundle::new(
-                self.data.parsed_offer.coin_spends,
-                self.data.parsed_offer.aggregated_signature,
-            ),
-            other_spend_bundle,
-        ])
+    /// Claims every offered coin, by spending it from the settlement payments puzzle to the given puzzle hash.
+    /// Returns the children, which belong to the puzzle hash once the offer has been taken.
+    ///
+    /// Royalty payments aren't created, so offers containing an NFT with a royalty are an error,
+    /// and nothing is spent.
+    pub fn claim(
+        &self,
+        ctx: &mut SpendContext,
+        puzzle_hash: Bytes32,
+    ) -> Result<OfferedCoins, OfferError> {
+        let offered = self.data.parsed_offer.offered_coins(&mut ctx.allocator)?;
+        let mut claimed = OfferedCoins::default();
+
+        for nft in &offered.nfts {
+            check_royalty(&nft.info)?;
+        }
+
+        for coin in offered.xch {
+            let spend =
+                settlement_spend(ctx, claim_payment(coin.coin_id(), puzzle_hash, coin.amount))?;
+            ctx.spend(coin, spend)?;
+            claimed
+                .xch
+                .push(Coin::new(coin.coin_id(), puzzle_hash, coin.amount));
+        }
+
+        for cat in offered.cats {
+            let spend = settlement_spend(
+                ctx,
+                claim_payment(cat.coin.coin_id(), puzzle_hash, cat.coin.amount),
+            )?;
+            Cat::spend_all(ctx, &[CatSpend::new(cat, spend)])?;
+            claimed
+                .cats
+                .push(cat.wrapped_child(puzzle_hash, cat.coin.amount));
+        }
+
+        for nft in offered.nfts {
+            let spend = settlement_spend(
+                ctx,
+                claim_payment(nft.coin.coin_id(), puzzle_hash, nft.coin.amount),
+            )?;
+            nft.spend(ctx, spend)?;
+            claimed.nfts.push(nft.wrapped_child(
+                puzzle_hash,
+                nft.info.current_owner,
+                nft.info.metadata,
+            ));
+        }
+
+        Ok(claimed)
+    }
+
+    /// Removes the coin spends that are already part of the offer, such as a settlement coin that
+    /// both sides spend. Conflicting spends of the same coin are an error.
+    ///
+    /// This must be done before the taker signs, since the offer's aggregated signature already
+    /// covers the spends it contains, and [`OfferBuilder::bundle`] can't take a signature back out.
+    pub fn new_coin_spends(
+        &self,
+        coin_spends: Vec<CoinSpend>,
+    ) -> Result<Vec<CoinSpend>, OfferError> {
+        let offered: HashMap<Bytes32, &CoinSpend> = self
+            .data
+            .parsed_offer
+            .coin_spends
+            .iter()
+            .map(|coin_spend| (coin_spend.coin.coin_id(), coin_spend))
+            .collect();
+
+        let mut new_coin_spends = Vec::new();
+
+        for coin_spend in coin_spends {
+            let coin_id = coin_spend.coin.coin_id();
+
+            match offered.get(&coin_id) {
+                Some(&existing) if *existing == coin_spend => {}
+                Some(_) => return Err(DriverError::ConflictingSpend(coin_id).into()),
+                None => new_coin_spends.push(coin_spend),
+            }
+        }
+
+        Ok(deduplicate_coin_spends(new_coin_spends)?)
+    }
+
+    /// Combines the offer with the spend bundle that takes it.
+    ///
+    /// The taker's coin spends should be filtered with [`OfferBuilder::new_coin_spends`] before
+    /// they're signed. Spends that are already part of the offer are only included once, but the
+    /// aggregated signatures are summed as-is, so a spend that was signed on both sides would
+    /// make the spend bundle invalid.
+    pub fn bundle(self, other_spend_bundle: SpendBundle) -> Result<SpendBundle, OfferError> {
+        let coin_spends = deduplicate_coin_spends(
+            self.data
+                .parsed_offer
+                .coin_spends
+                .into_iter()
+                .chain(other_spend_bundle.coin_spends),
+        )?;
+
+        Ok(SpendBundle::new(
+            coin_spends,
+            &self.data.parsed_offer.aggregated_signature + &other_spend_bundle.aggregated_signature,
+        ))
+    }
+}
+
+/// Creates a spend of the settlement payments puzzle, which makes the given payments.
+/// This is used both to pay for the requested payments, and to claim offered coins.
+pub fn settlement_spend(
+    ctx: &mut SpendContext,
+    notarized_payments: Vec<NotarizedPayment>,
+) -> Result<Spend, DriverError> {
+    let puzzle = ctx.settlement_payments_puzzle()?;
+    let solution = ctx.alloc(&SettlementPaymentsSolution { notarized_payments })?;
+    Ok(Spend::new(puzzle, solution))
+}
+
+// The offer builder doesn't pay royalties yet, so trading an NFT with a royalty would skip them.
+fn check_royalty<M>(info: &NftInfo<M>) -> Result<(), OfferError> {
+    match info.royalty_ten_thousandths {
+        0 => Ok(()),
+        royalty if royalty > 10_000 => Err(DriverError::InvalidRoyalty(royalty).into()),
+        royalty => Err(OfferError::UnsupportedRoyalty(info.launcher_id, royalty)),
+    }
+}
+
+// Each coin is claimed with its own id as the nonce, so that the announcements are unique.
+fn claim_payment(coin_id: Bytes32, puzzle_hash: Bytes32, amount: u64) -> Vec<NotarizedPayment> {
+    vec![NotarizedPayment {
+        nonce: coin_id,
+        payments: vec![Payment::with_memos(
+            puzzle_hash,
+            amount,
+            vec![puzzle_hash.into()],
+        )],
+    }]
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_puzzles::nft::NftMetadata;
+    use chia_puzzles::{
+        cat::CatArgs, offer::SETTLEMENT_PAYMENTS_PUZZLE_HASH, standard::StandardArgs,
+    };
+    use chia_sdk_driver::{Launcher, NftMint, SpendWithConditions, StandardLayer};
+    use chia_sdk_test::{sign_transaction, test_secret_keys, Simulator};
+    use chia_sdk_types::{Conditions, TESTNET11_CONSTANTS};
+
+    use super::*;
+
+    #[test]
+    fn test_cat_for_xch_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let keys = test_secret_keys(2)?;
+        let (maker_sk, taker_sk) = (keys[0].clone(), keys[1].clone());
+
+        let maker = StandardLayer::new(maker_sk.public_key());
+        let maker_puzzle_hash = StandardArgs::curry_tree_hash(maker_sk.public_key()).into();
+        let maker_coin = sim.new_coin(maker_puzzle_hash, 1000);
+
+        let taker = StandardLayer::new(taker_sk.public_key());
+        let taker_puzzle_hash = StandardArgs::curry_tree_hash(taker_sk.public_key()).into();
+        let taker_coin = sim.new_coin(taker_puzzle_hash, 100);
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            taker_coin.coin_id(),
+            100,
+            Conditions::new().create_coin(taker_puzzle_hash, 100, vec![taker_puzzle_hash.into()]),
+        )?;
+        taker.spend(ctx, taker_coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[taker_sk.clone()])?;
+        let taker_cat = cat.wrapped_child(taker_puzzle_hash, 100);
+
+        // The maker offers 1000 mojos for 100 of the taker's CAT.
+        let (assertions, builder) = Offer::build(vec![maker_coin.coin_id()])
+            .request_cat(
+                ctx,
+                cat.asset_id,
+                vec![Payment::with_memos(
+                    maker_puzzle_hash,
+                    100,
+                    vec![maker_puzzle_hash.into()],
+                )],
+            )?
+            .finish();
+
+        maker.spend(
+            ctx,
+            maker_coin,
+            Conditions::new()
+                .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, Vec::new())
+                .extend(assertions),
+        )?;
+        let partial_spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let offer = builder.bundle(ctx, partial_spend_bundle)?;
+
+        // The taker pays the requested CAT into the settlement puzzle, and claims the offered XCH.
+        let mut take = offer.take(&mut ctx.allocator)?;
+        let claimed = take.claim(ctx, taker_puzzle_hash)?;
+        let (_, notarized_payments) = take.fulfill().expect("missing requested payment");
+        assert!(take.fulfill().is_none());
+
+        let inner_spend = taker.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 100, Vec::new()),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(taker_cat, inner_spend)])?;
+
+        let settlement_cat = taker_cat.wrapped_child(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 100);
+        let spend = settlement_spend(ctx, notarized_payments)?;
+        Cat::spend_all(ctx, &[CatSpend::new(settlement_cat, spend)])?;
+
+        let spend_bundle = take.bundle(SpendBundle::new(ctx.take(), Signature::default()))?;
+        sim.spend_coins(spend_bundle.coin_spends, &[maker_sk, taker_sk])?;
+
+        let maker_cat = Coin::new(
+            settlement_cat.coin.coin_id(),
+            CatArgs::curry_tree_hash(cat.asset_id, maker_puzzle_hash.into()).into(),
+            100,
+        );
+        assert!(sim.coin_state(maker_cat.coin_id()).is_some());
+
+        assert_eq!(claimed.xch.len(), 1);
+        assert_eq!(claimed.xch[0].puzzle_hash, taker_puzzle_hash);
+        assert!(sim.coin_state(claimed.xch[0].coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nft_for_xch_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let keys = test_secret_keys(2)?;
+        let (maker_sk, taker_sk) = (keys[0].clone(), keys[1].clone());
+
+        let maker = StandardLayer::new(maker_sk.public_key());
+        let maker_puzzle_hash = StandardArgs::curry_tree_hash(maker_sk.public_key()).into();
+        let maker_coin = sim.new_coin(maker_puzzle_hash, 1000);
+
+        let taker = StandardLayer::new(taker_sk.public_key());
+        let taker_puzzle_hash = StandardArgs::curry_tree_hash(taker_sk.public_key()).into();
+        let taker_coin = sim.new_coin(taker_puzzle_hash, 1);
+
+        let (mint_nft, nft) = Launcher::new(taker_coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), taker_puzzle_hash, 0, None),
+        )?;
+        taker.spend(ctx, taker_coin, mint_nft)?;
+        sim.spend_coins(ctx.take(), &[taker_sk.clone()])?;
+
+        // The maker offers 1000 mojos for the taker's NFT.
+        let (assertions, builder) = Offer::build(vec![maker_coin.coin_id()])
+            .request_nft(
+                ctx,
+                nft.info.clone(),
+                vec![Payment::with_memos(
+                    maker_puzzle_hash,
+                    1,
+                    vec![maker_puzzle_hash.into()],
+                )],
+            )?
+            .finish();
+
+        maker.spend(
+            ctx,
+            maker_coin,
+            Conditions::new()
+                .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, Vec::new())
+                .extend(assertions),
+        )?;
+        let partial_spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let offer = builder.bundle(ctx, partial_spend_bundle)?;
+
+        let parsed = offer.parse(&mut ctx.allocator)?;
+        let requested = parsed.requested_assets(&ctx.allocator)?;
+        assert_eq!(
+            requested.nfts.iter().copied().collect::<Vec<_>>(),
+            [nft.info.launcher_id]
+        );
+
+        // The taker pays the requested NFT into the settlement puzzle, and claims the offered XCH.
+        let mut take = parsed.take();
+        let claimed = take.claim(ctx, taker_puzzle_hash)?;
+        let (puzzle, notarized_payments) = take.fulfill().expect("missing requested payment");
+
+        let settlement_nft = nft.transfer(
+            ctx,
+            &taker,
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            Conditions::new(),
+        )?;
+        assert_eq!(
+            settlement_nft.coin.puzzle_hash,
+            puzzle.curried_puzzle_hash().into()
+        );
+
+        let spend = settlement_spend(ctx, notarized_payments)?;
+        settlement_nft.spend(ctx, spend)?;
+
+        let spend_bundle = take.bundle(SpendBundle::new(ctx.take(), Signature::default()))?;
+        sim.spend_coins(spend_bundle.coin_spends, &[maker_sk, taker_sk])?;
+
+        let maker_nft = settlement_nft.wrapped_child(
+            maker_puzzle_hash,
+            settlement_nft.info.current_owner,
+            settlement_nft.info.metadata,
+        );
+        assert!(sim.coin_state(maker_nft.coin.coin_id()).is_some());
+        assert!(sim.coin_state(claimed.xch[0].coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_and_take_offer() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let keys = test_secret_keys(2)?;
+        let (maker_sk, taker_sk) = (keys[0].clone(), keys[1].clone());
+
+        let maker = StandardLayer::new(maker_sk.public_key());
+        let maker_puzzle_hash = StandardArgs::curry_tree_hash(maker_sk.public_key()).into();
+        let maker_coin = sim.new_coin(maker_puzzle_hash, 1000);
+        let nft_coin = sim.new_coin(maker_puzzle_hash, 1);
+
+        let taker = StandardLayer::new(taker_sk.public_key());
+        let taker_puzzle_hash = StandardArgs::curry_tree_hash(taker_sk.public_key()).into();
+        let taker_coin = sim.new_coin(taker_puzzle_hash, 100);
+
+        let (mint_nft, nft) = Launcher::new(nft_coin.coin_id(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), maker_puzzle_hash, 0, None),
+        )?;
+        maker.spend(ctx, nft_coin, mint_nft)?;
+
+        let (issue_cat, cat) = Cat::single_issuance_eve(
+            ctx,
+            taker_coin.coin_id(),
+            100,
+            Conditions::new().create_coin(taker_puzzle_hash, 100, vec![taker_puzzle_hash.into()]),
+        )?;
+        taker.spend(ctx, taker_coin, issue_cat)?;
+        sim.spend_coins(ctx.take(), &[maker_sk.clone(), taker_sk.clone()])?;
+        let taker_cat = cat.wrapped_child(taker_puzzle_hash, 100);
+
+        // The maker offers an NFT and 300 mojos for 100 of the taker's CAT.
+        let (assertions, builder) = Offer::build(vec![maker_coin.coin_id(), nft.coin.coin_id()])
+            .request_cat(
+                ctx,
+                cat.asset_id,
+                vec![Payment::new(maker_puzzle_hash, 100)],
+            )?
+            .finish();
+
+        maker.spend(
+            ctx,
+            maker_coin,
+            Conditions::new()
+                .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 300, Vec::new())
+                .create_coin(maker_puzzle_hash, 700, Vec::new())
+                .extend(assertions),
+        )?;
+        let launcher_id = nft.info.launcher_id;
+        let _nft = nft.transfer(
+            ctx,
+            &maker,
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            Conditions::new(),
+        )?;
+        let partial_spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let offer = builder.bundle(ctx, partial_spend_bundle)?;
+
+        let parsed = offer.parse(&mut ctx.allocator)?;
+
+        let requested = parsed.requested_assets(&ctx.allocator)?;
+        assert_eq!(requested.xch, 0);
+        assert_eq!(requested.cats.get(&cat.asset_id), Some(&100));
+        assert!(requested.nfts.is_empty());
+
+        let offered = parsed.offered_coins(&mut ctx.allocator)?.assets()?;
+        assert_eq!(offered.xch, 300);
+        assert!(offered.cats.is_empty());
+        assert_eq!(
+            offered.nfts.iter().copied().collect::<Vec<_>>(),
+            [launcher_id]
+        );
+
+        // The taker claims the offered coins, and pays the requested CAT into the settlement puzzle.
+        let mut take = parsed.take();
+        let claimed = take.claim(ctx, taker_puzzle_hash)?;
+        let (_, notarized_payments) = take.fulfill().expect("missing requested payment");
+
+        let inner_spend = taker.spend_with_conditions(
+            ctx,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 100, Vec::new()),
+        )?;
+        Cat::spend_all(ctx, &[CatSpend::new(taker_cat, inner_spend)])?;
+
+        let settlement_cat = taker_cat.wrapped_child(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 100);
+        let spend = settlement_spend(ctx, notarized_payments)?;
+        Cat::spend_all(ctx, &[CatSpend::new(settlement_cat, spend)])?;
+
+        let spend_bundle = take.bundle(SpendBundle::new(ctx.take(), Signature::default()))?;
+        sim.spend_coins(spend_bundle.coin_spends, &[maker_sk, taker_sk])?;
+
+        assert_eq!(claimed.xch.len(), 1);
+        assert!(sim.coin_state(claimed.xch[0].coin_id()).is_some());
+
+        assert_eq!(claimed.nfts.len(), 1);
+        assert_eq!(claimed.nfts[0].info.p2_puzzle_hash, taker_puzzle_hash);
+        assert!(sim.coin_state(claimed.nfts[0].coin.coin_id()).is_some());
+
+        let maker_cat = settlement_cat.wrapped_child(maker_puzzle_hash, 100);
+        assert!(sim.coin_state(maker_cat.coin.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_spend_signed_on_both_sides() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let ctx = &mut SpendContext::new();
+
+        let keys = test_secret_keys(2)?;
+        let (maker_sk, taker_sk) = (keys[0].clone(), keys[1].clone());
+
+        let maker = StandardLayer::new(maker_sk.public_key());
+        let maker_puzzle_hash = StandardArgs::curry_tree_hash(maker_sk.public_key()).into();
+        let maker_coin = sim.new_coin(maker_puzzle_hash, 1000);
+
+        let taker = StandardLayer::new(taker_sk.public_key());
+        let taker_puzzle_hash = StandardArgs::curry_tree_hash(taker_sk.public_key()).into();
+        let taker_coin = sim.new_coin(taker_puzzle_hash, 100);
+
+        // The maker offers 1000 mojos for 100 mojos, and signs their side of the offer.
+        let (assertions, builder) = Offer::build(vec![maker_coin.coin_id()])
+            .request_xch(ctx, vec![Payment::new(maker_puzzle_hash, 100)])?
+            .finish();
+
+        maker.spend(
+            ctx,
+            maker_coin,
+            Conditions::new()
+                .create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 1000, Vec::new())
+                .extend(assertions),
+        )?;
+        let maker_coin_spends = ctx.take();
+        let signature = sign_transaction(
+            &maker_coin_spends,
+            &[maker_sk.clone()],
+            &(&*TESTNET11_CONSTANTS).into(),
+        )?;
+        let offer = builder.bundle(ctx, SpendBundle::new(maker_coin_spends.clone(), signature))?;
+
+        // The taker pays the requested XCH, and also includes the maker's spend in their own bundle.
+        let mut take = offer.parse(&mut ctx.allocator)?.take();
+        take.claim(ctx, taker_puzzle_hash)?;
+        let (_, notarized_payments) = take.fulfill().expect("missing requested payment");
+
+        taker.spend(
+            ctx,
+            taker_coin,
+            Conditions::new().create_coin(SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(), 100, Vec::new()),
+        )?;
+        let settlement_coin = Coin::new(
+            taker_coin.coin_id(),
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            100,
+        );
+        let spend = settlement_spend(ctx, notarized_payments)?;
+        ctx.spend(settlement_coin, spend)?;
+
+        let coin_spends: Vec<CoinSpend> = maker_coin_spends.into_iter().chain(ctx.take()).collect();
+
+        // Signing the maker's spend again would count its signature twice.
+        let signature = sign_transaction(
+            &coin_spends,
+            &[maker_sk, taker_sk.clone()],
+            &(&*TESTNET11_CONSTANTS).into(),
+        )?;
+        let spend_bundle = take
+            .clone()
+            .bundle(SpendBundle::new(coin_spends.clone(), signature))?;
+        assert!(sim
+            .new_transaction(spend_bundle, &TESTNET11_CONSTANTS)
+            .is_err());
+
+        // So the spends that are already part of the offer are removed before signing.
+        let coin_spends = take.new_coin_spends(coin_spends)?;
+        assert_eq!(coin_spends.len(), 3);
+
+        let signature =
+            sign_transaction(&coin_spends, &[taker_sk], &(&*TESTNET11_CONSTANTS).into())?;
+        let spend_bundle = take.bundle(SpendBundle::new(coin_spends, signature))?;
+        sim.new_transaction(spend_bundle, &TESTNET11_CONSTANTS)?;
+
+        let maker_xch = Coin::new(settlement_coin.coin_id(), maker_puzzle_hash, 100);
+        assert!(sim.coin_state(maker_xch.coin_id()).is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nft_royalty_unsupported() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let sk = test_secret_keys(1)?.remove(0);
+        let p2 = StandardLayer::new(sk.public_key());
+        let puzzle_hash = StandardArgs::curry_tree_hash(sk.public_key()).into();
+
+        let (_mint_nft, nft) = Launcher::new(Bytes32::default(), 1).mint_nft(
+            ctx,
+            NftMint::new(NftMetadata::default(), puzzle_hash, 300, None),
+        )?;
+
+        assert!(matches!(
+            Offer::build(Vec::new()).request_nft(ctx, nft.info.clone(), Vec::new()),
+            Err(OfferError::UnsupportedRoyalty(launcher_id, 300)) if launcher_id == nft.info.launcher_id
+        ));
+
+        let mut invalid_info = nft.info.clone();
+        invalid_info.royalty_ten_thousandths = 10_001;
+        assert!(matches!(
+            Offer::build(Vec::new()).request_nft(ctx, invalid_info, Vec::new()),
+            Err(OfferError::Driver(DriverError::InvalidRoyalty(10_001)))
+        ));
+
+        // An offer can't be claimed if it contains an NFT with a royalty, since it wouldn't be paid.
+        let (_, builder) = Offer::build(vec![nft.coin.coin_id()])
+            .request_xch(ctx, vec![Payment::new(puzzle_hash, 1)])?
+            .finish();
+        let launcher_id = nft.info.launcher_id;
+        let _nft = nft.transfer(
+            ctx,
+            &p2,
+            SETTLEMENT_PAYMENTS_PUZZLE_HASH.into(),
+            Conditions::new(),
+        )?;
+        let partial_spend_bundle = SpendBundle::new(ctx.take(), Signature::default());
+        let offer = builder.bundle(ctx, partial_spend_bundle)?;
+
+        let take = offer.parse(&mut ctx.allocator)?.take();
+        assert!(matches!(
+            take.claim(ctx, puzzle_hash),
+            Err(OfferError::UnsupportedRoyalty(id, 300)) if id == launcher_id
+        ));
+
+        Ok(())
     }
 }
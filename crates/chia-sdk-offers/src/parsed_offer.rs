@@ -1,10 +1,16 @@
 use chia_bls::Signature;
-use chia_protocol::{Bytes32, CoinSpend};
-use chia_puzzles::offer::NotarizedPayment;
-use chia_sdk_driver::Puzzle;
+use chia_protocol::{Bytes32, Coin, CoinSpend};
+use chia_puzzles::offer::{NotarizedPayment, SETTLEMENT_PAYMENTS_PUZZLE_HASH};
+use chia_sdk_driver::{
+    Cat, CatLayer, DriverError, HashedPtr, Layer, Nft, Puzzle, PuzzleRegistry, SettlementLayer,
+    StandardNftLayers,
+};
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvmr::Allocator;
 use indexmap::IndexMap;
 
-use crate::{OfferBuilder, Take};
+use crate::{OfferAssets, OfferBuilder, OfferError, OfferedCoins, Take};
 
 #[derive(Debug, Default, Clone)]
 pub struct ParsedOffer {
@@ -17,4 +23,161 @@ impl ParsedOffer {
     pub fn take(self) -> OfferBuilder<Take> {
         OfferBuilder::from_parsed_offer(self)
     }
+
+    /// Summarizes the assets that the maker requested in return for the offered coins.
+    /// Requested payments with a puzzle other than an XCH, CAT, or NFT settlement puzzle are an error,
+    /// including singletons that aren't NFTs or don't have the settlement payments puzzle as their p2 puzzle,
+    /// which describes the puzzle's known layers using the [`PuzzleRegistry`].
+    /// Since the amounts come from an untrusted offer, a total that overflows a `u64` is also an error.
+    pub fn requested_assets(&self, allocator: &Allocator) -> Result<OfferAssets, OfferError> {
+        let mut assets = OfferAssets::default();
+
+        for (&puzzle_hash, (puzzle, notarized_payments)) in &self.requested_payments {
+            let amount = notarized_payments
+                .iter()
+                .flat_map(|notarized_payment| &notarized_payment.payments)
+                .try_fold(0, |total: u64, payment| total.checked_add(payment.amount))
+                .ok_or(OfferError::AmountOverflow)?;
+
+            if SettlementLayer::parse_puzzle(allocator, *puzzle)?.is_some() {
+                assets.add_xch(amount)?;
+            } else if let Some(cat_layer) =
+                CatLayer::<SettlementLayer>::parse_puzzle(allocator, *puzzle)?
+            {
+                assets.add_cat(cat_layer.asset_id, amount)?;
+            } else if let Some(nft_layers) =
+                StandardNftLayers::<HashedPtr, SettlementLayer>::parse_puzzle(allocator, *puzzle)?
+            {
+                assets.nfts.insert(nft_layers.launcher_id);
+            } else {
+                let description = PuzzleRegistry::standard().describe(allocator, puzzle.ptr());
+                return Err(OfferError::UnsupportedAsset(puzzle_hash, description));
+            }
+        }
+
+        Ok(assets)
+    }
+
+    /// Finds the coins that the maker's spends lock in the settlement payments puzzle.
+    pub fn offered_coins(&self, allocator: &mut Allocator) -> Result<OfferedCoins, OfferError> {
+        let mut offered = OfferedCoins::default();
+
+        for coin_spend in &self.coin_spends {
+            let puzzle = coin_spend.puzzle_reveal.to_clvm(allocator)?;
+            let puzzle = Puzzle::parse(allocator, puzzle);
+            let solution = coin_spend.solution.to_clvm(allocator)?;
+
+            if let Some(cats) = Cat::parse_children(allocator, coin_spend.coin, puzzle, solution)? {
+                offered
+                    .cats
+                    .extend(cats.into_iter().filter(|cat| {
+                        cat.p2_puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH.into()
+                    }));
+                continue;
+            }
+
+            if let Some(nft) =
+                Nft::<HashedPtr>::parse_child(allocator, coin_spend.coin, puzzle, solution)?
+            {
+                if nft.info.p2_puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH.into() {
+                    offered.nfts.push(nft);
+                }
+                continue;
+            }
+
+            let output =
+                run_puzzle(allocator, puzzle.ptr(), solution).map_err(DriverError::from)?;
+            let conditions = Vec::<Condition>::from_clvm(allocator, output)?;
+
+            offered.xch.extend(
+                conditions
+                    .into_iter()
+                    .filter_map(Condition::into_create_coin)
+                    .filter(|create_coin| {
+                        create_coin.puzzle_hash == SETTLEMENT_PAYMENTS_PUZZLE_HASH.into()
+                    })
+                    .map(|create_coin| {
+                        Coin::new(
+                            coin_spend.coin.coin_id(),
+                            create_coin.puzzle_hash,
+                            create_coin.amount,
+                        )
+                    }),
+            );
+        }
+
+        Ok(offered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::SpendBundle;
+    use chia_puzzles::offer::Payment;
+    use chia_sdk_driver::{SingletonLayer, SpendContext};
+
+    use crate::Offer;
+
+    use super::*;
+
+    #[test]
+    fn test_requested_non_nft_singleton() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let settlement_puzzle = ctx.settlement_payments_puzzle()?;
+        let puzzle =
+            SingletonLayer::new(Bytes32::new([1; 32]), settlement_puzzle).construct_puzzle(ctx)?;
+        let puzzle_hash: Bytes32 = ctx.tree_hash(puzzle).into();
+
+        let (_, builder) = Offer::build(Vec::new())
+            .request(ctx, &puzzle, vec![Payment::new(Bytes32::default(), 1)])?
+            .finish();
+        let offer = builder.bundle(ctx, SpendBundle::new(Vec::new(), Signature::default()))?;
+        let parsed = offer.parse(&mut ctx.allocator)?;
+
+        assert!(matches!(
+            parsed.requested_assets(&ctx.allocator),
+            Err(OfferError::UnsupportedAsset(hash, _)) if hash == puzzle_hash
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_requested_amount_overflow() -> anyhow::Result<()> {
+        let ctx = &mut SpendContext::new();
+
+        let settlement_puzzle = ctx.settlement_payments_puzzle()?;
+        let payments = vec![
+            Payment::new(Bytes32::default(), u64::MAX),
+            Payment::new(Bytes32::default(), 1),
+        ];
+
+        let (_, builder) = Offer::build(Vec::new())
+            .request(ctx, &settlement_puzzle, payments)?
+            .finish();
+        let offer = builder.bundle(ctx, SpendBundle::new(Vec::new(), Signature::default()))?;
+        let parsed = offer.parse(&mut ctx.allocator)?;
+
+        assert!(matches!(
+            parsed.requested_assets(&ctx.allocator),
+            Err(OfferError::AmountOverflow)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_offered_amount_overflow() {
+        let offered = OfferedCoins {
+            xch: vec![
+                Coin::new(Bytes32::new([1; 32]), Bytes32::default(), u64::MAX),
+                Coin::new(Bytes32::new([2; 32]), Bytes32::default(), 1),
+            ],
+            ..Default::default()
+        };
+
+        assert!(matches!(offered.assets(), Err(OfferError::AmountOverflow)));
+    }
 }
@@ -1,5 +1,9 @@
 use chia_consensus::consensus_constants::ConsensusConstants;
 use chia_protocol::Bytes32;
+use chia_sdk_types::{
+    opcodes::{self, ConditionOpcode},
+    AggSigKind,
+};
 use clvmr::sha2::Sha256;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,12 +21,12 @@ impl AggSigConstants {
     pub fn new(agg_sig_me: Bytes32) -> Self {
         Self {
             me: agg_sig_me,
-            parent: hash(agg_sig_me, 43),
-            puzzle: hash(agg_sig_me, 44),
-            amount: hash(agg_sig_me, 45),
-            puzzle_amount: hash(agg_sig_me, 46),
-            parent_amount: hash(agg_sig_me, 47),
-            parent_puzzle: hash(agg_sig_me, 48),
+            parent: hash(agg_sig_me, opcodes::AGG_SIG_PARENT),
+            puzzle: hash(agg_sig_me, opcodes::AGG_SIG_PUZZLE),
+            amount: hash(agg_sig_me, opcodes::AGG_SIG_AMOUNT),
+            puzzle_amount: hash(agg_sig_me, opcodes::AGG_SIG_PUZZLE_AMOUNT),
+            parent_amount: hash(agg_sig_me, opcodes::AGG_SIG_PARENT_AMOUNT),
+            parent_puzzle: hash(agg_sig_me, opcodes::AGG_SIG_PARENT_PUZZLE),
         }
     }
 
@@ -53,6 +57,21 @@ impl AggSigConstants {
     pub fn parent_puzzle(&self) -> Bytes32 {
         self.parent_puzzle
     }
+
+    /// The domain string appended to the message of an `AGG_SIG` condition of the given kind.
+    /// Returns `None` for `AGG_SIG_UNSAFE`, which has nothing appended.
+    pub fn domain_string(&self, kind: AggSigKind) -> Option<Bytes32> {
+        match kind {
+            AggSigKind::Parent => Some(self.parent),
+            AggSigKind::Puzzle => Some(self.puzzle),
+            AggSigKind::Amount => Some(self.amount),
+            AggSigKind::PuzzleAmount => Some(self.puzzle_amount),
+            AggSigKind::ParentAmount => Some(self.parent_amount),
+            AggSigKind::ParentPuzzle => Some(self.parent_puzzle),
+            AggSigKind::Unsafe => None,
+            AggSigKind::Me => Some(self.me),
+        }
+    }
 }
 
 impl From<&ConsensusConstants> for AggSigConstants {
@@ -75,9 +94,9 @@ impl From<ConsensusConstants> for AggSigConstants {
     }
 }
 
-fn hash(agg_sig_data: Bytes32, byte: u8) -> Bytes32 {
+fn hash(agg_sig_data: Bytes32, opcode: ConditionOpcode) -> Bytes32 {
     let mut hasher = Sha256::new();
     hasher.update(agg_sig_data);
-    hasher.update([byte]);
+    hasher.update([u8::try_from(opcode).expect("AGG_SIG opcodes fit in a single byte")]);
     hasher.finalize().into()
 }
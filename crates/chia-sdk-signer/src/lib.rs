@@ -1,7 +1,11 @@
 mod agg_sig_constants;
 mod error;
+mod ownership_proof;
 mod required_signature;
+mod reserves_proof;
 
 pub use agg_sig_constants::*;
 pub use error::*;
+pub use ownership_proof::*;
 pub use required_signature::*;
+pub use reserves_proof::*;
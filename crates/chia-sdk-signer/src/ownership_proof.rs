@@ -0,0 +1,111 @@
+use chia_bls::{sign, verify, PublicKey, SecretKey, Signature};
+use chia_protocol::{Bytes, Bytes32};
+use chia_puzzles::standard::StandardArgs;
+use clvm_utils::ToTreeHash;
+
+/// A signed statement that a standard puzzle hash (and therefore its address) is controlled by the signer.
+///
+/// The message binds the puzzle hash, the fingerprint of the wallet, and a nonce chosen by the verifier,
+/// so that a proof can't be replayed for another request. It's signed in the [CHIP-0002](https://github.com/Chia-Network/chips/blob/main/CHIPs/chip-0002.md)
+/// format, so it can also be checked by other wallets that support signed messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OwnershipProof {
+    pub puzzle_hash: Bytes32,
+    pub fingerprint: u32,
+    pub nonce: Bytes,
+    /// The synthetic public key that the puzzle hash is curried with.
+    pub public_key: PublicKey,
+    pub signature: Signature,
+}
+
+impl OwnershipProof {
+    /// The prefix of signed messages defined by CHIP-0002.
+    pub const MESSAGE_PREFIX: &'static str = "Chia Signed Message";
+
+    /// Signs a proof for the standard puzzle hash of the given synthetic secret key.
+    pub fn sign(synthetic_key: &SecretKey, fingerprint: u32, nonce: Bytes) -> Self {
+        let public_key = synthetic_key.public_key();
+        let puzzle_hash = StandardArgs::curry_tree_hash(public_key).into();
+        let signature = sign(
+            synthetic_key,
+            Self::message_hash(puzzle_hash, fingerprint, &nonce),
+        );
+
+        Self {
+            puzzle_hash,
+            fingerprint,
+            nonce,
+            public_key,
+            signature,
+        }
+    }
+
+    /// Checks that the public key controls the puzzle hash, and that the signature is valid.
+    /// The caller should also check that the nonce is the one it asked for.
+    pub fn is_valid(&self) -> bool {
+        if StandardArgs::curry_tree_hash(self.public_key) != self.puzzle_hash.into() {
+            return false;
+        }
+
+        verify(
+            &self.signature,
+            &self.public_key,
+            Self::message_hash(self.puzzle_hash, self.fingerprint, &self.nonce),
+        )
+    }
+
+    /// The message is the puzzle hash, followed by the big-endian fingerprint and the nonce.
+    pub fn message(puzzle_hash: Bytes32, fingerprint: u32, nonce: &[u8]) -> Bytes {
+        let mut message = puzzle_hash.to_vec();
+        message.extend_from_slice(&fingerprint.to_be_bytes());
+        message.extend_from_slice(nonce);
+        message.into()
+    }
+
+    /// The hash that gets signed, which is the tree hash of the prefix paired with the message.
+    pub fn message_hash(puzzle_hash: Bytes32, fingerprint: u32, nonce: &[u8]) -> Bytes32 {
+        Self::signed_message_hash(Self::message(puzzle_hash, fingerprint, nonce))
+    }
+
+    /// The hash of any CHIP-0002 signed message, which is the tree hash of the prefix paired with the message.
+    pub fn signed_message_hash(message: Bytes) -> Bytes32 {
+        (Self::MESSAGE_PREFIX, message).tree_hash().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::DerivableKey;
+
+    use super::*;
+
+    #[test]
+    fn test_ownership_proof() {
+        let master_key = SecretKey::from_seed(&[1; 32]);
+        let fingerprint = master_key.public_key().get_fingerprint();
+        let synthetic_key = master_key.derive_unhardened(0);
+        let nonce = Bytes::new(b"nonce".to_vec());
+
+        let proof = OwnershipProof::sign(&synthetic_key, fingerprint, nonce.clone());
+        assert_eq!(
+            proof.puzzle_hash,
+            StandardArgs::curry_tree_hash(synthetic_key.public_key()).into()
+        );
+        assert!(proof.is_valid());
+
+        // Changing any part of the message invalidates the signature.
+        let mut other = proof.clone();
+        other.nonce = Bytes::new(b"other".to_vec());
+        assert!(!other.is_valid());
+
+        let mut other = proof.clone();
+        other.fingerprint += 1;
+        assert!(!other.is_valid());
+
+        // The public key must be the one that the puzzle hash is curried with.
+        let other_key = master_key.derive_unhardened(1);
+        let mut other = OwnershipProof::sign(&other_key, fingerprint, nonce);
+        other.puzzle_hash = proof.puzzle_hash;
+        assert!(!other.is_valid());
+    }
+}
@@ -17,57 +17,11 @@ pub struct RequiredSignature {
 impl RequiredSignature {
     /// Converts a known [`AggSig`] condition to a `RequiredSignature` if possible.
     pub fn from_condition(coin: &Coin, condition: AggSig, constants: &AggSigConstants) -> Self {
-        let domain_string;
-
-        let public_key = condition.public_key;
-        let message = condition.message;
-
-        let appended_info = match condition.kind {
-            AggSigKind::Parent => {
-                domain_string = constants.parent();
-                coin.parent_coin_info.to_vec()
-            }
-            AggSigKind::Puzzle => {
-                domain_string = constants.puzzle();
-                coin.puzzle_hash.to_vec()
-            }
-            AggSigKind::Amount => {
-                domain_string = constants.amount();
-                u64_to_bytes(coin.amount)
-            }
-            AggSigKind::PuzzleAmount => {
-                domain_string = constants.puzzle_amount();
-                let puzzle = coin.puzzle_hash;
-                [puzzle.to_vec(), u64_to_bytes(coin.amount)].concat()
-            }
-            AggSigKind::ParentAmount => {
-                domain_string = constants.parent_amount();
-                let parent = coin.parent_coin_info;
-                [parent.to_vec(), u64_to_bytes(coin.amount)].concat()
-            }
-            AggSigKind::ParentPuzzle => {
-                domain_string = constants.parent_puzzle();
-                [coin.parent_coin_info.to_vec(), coin.puzzle_hash.to_vec()].concat()
-            }
-            AggSigKind::Unsafe => {
-                return Self {
-                    public_key,
-                    raw_message: message,
-                    appended_info: Vec::new(),
-                    domain_string: None,
-                }
-            }
-            AggSigKind::Me => {
-                domain_string = constants.me();
-                coin.coin_id().to_vec()
-            }
-        };
-
         Self {
-            public_key,
-            raw_message: message,
-            appended_info,
-            domain_string: Some(domain_string),
+            public_key: condition.public_key,
+            raw_message: condition.message,
+            appended_info: agg_sig_appended_info(condition.kind, coin),
+            domain_string: constants.domain_string(condition.kind),
         }
     }
 
@@ -147,6 +101,40 @@ impl RequiredSignature {
     }
 }
 
+/// The coin information appended to the message of an `AGG_SIG` condition of the given kind, before the domain string.
+pub fn agg_sig_appended_info(kind: AggSigKind, coin: &Coin) -> Vec<u8> {
+    match kind {
+        AggSigKind::Parent => coin.parent_coin_info.to_vec(),
+        AggSigKind::Puzzle => coin.puzzle_hash.to_vec(),
+        AggSigKind::Amount => u64_to_bytes(coin.amount),
+        AggSigKind::PuzzleAmount => [coin.puzzle_hash.to_vec(), u64_to_bytes(coin.amount)].concat(),
+        AggSigKind::ParentAmount => {
+            [coin.parent_coin_info.to_vec(), u64_to_bytes(coin.amount)].concat()
+        }
+        AggSigKind::ParentPuzzle => {
+            [coin.parent_coin_info.to_vec(), coin.puzzle_hash.to_vec()].concat()
+        }
+        AggSigKind::Unsafe => Vec::new(),
+        AggSigKind::Me => coin.coin_id().to_vec(),
+    }
+}
+
+/// Computes the message that needs to be signed for an `AGG_SIG` condition of the given kind, output by the spend of the coin.
+/// This is the condition's message, followed by the appended coin information and the domain string.
+pub fn agg_sig_final_message(
+    kind: AggSigKind,
+    coin: &Coin,
+    message: &[u8],
+    constants: &AggSigConstants,
+) -> Vec<u8> {
+    let mut final_message = message.to_vec();
+    final_message.extend(agg_sig_appended_info(kind, coin));
+    if let Some(domain_string) = constants.domain_string(kind) {
+        final_message.extend(domain_string.to_bytes());
+    }
+    final_message
+}
+
 fn u64_to_bytes(value: u64) -> Vec<u8> {
     let mut allocator = Allocator::new();
     let atom = allocator.new_number(value.into()).unwrap();
@@ -241,6 +229,7 @@ mod tests {
         let constants = AggSigConstants::from(&*MAINNET_CONSTANTS);
 
         for (condition, appended_info, domain_string) in cases {
+            let kind = condition.kind;
             let required = RequiredSignature::from_condition(&coin, condition, &constants);
 
             assert_eq!(required.public_key(), public_key);
@@ -255,7 +244,16 @@ mod tests {
                 message.extend(domain_string.to_bytes());
             }
 
-            assert_eq!(hex::encode(message), hex::encode(required.final_message()));
+            assert_eq!(hex::encode(&message), hex::encode(required.final_message()));
+            assert_eq!(
+                hex::encode(&message),
+                hex::encode(agg_sig_final_message(
+                    kind,
+                    &coin,
+                    required.raw_message(),
+                    &constants
+                ))
+            );
         }
     }
 }
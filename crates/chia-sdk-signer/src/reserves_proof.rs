@@ -0,0 +1,160 @@
+use std::collections::HashSet;
+
+use chia_bls::{sign, verify, PublicKey, SecretKey, Signature};
+use chia_protocol::{Bytes, Bytes32, Coin};
+use chia_puzzles::standard::StandardArgs;
+use chia_sdk_types::MerkleTree;
+
+use crate::OwnershipProof;
+
+/// A signature by the owner of a standard coin over the auditor's nonce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CoinOwnershipSignature {
+    pub coin: Coin,
+    /// The synthetic public key that the coin's puzzle hash is curried with.
+    pub public_key: PublicKey,
+    pub signature: Signature,
+}
+
+/// A proof that the signer controls a set of standard coins at the time of an audit.
+///
+/// Each coin is signed over a nonce chosen by the auditor, in the same [CHIP-0002](https://github.com/Chia-Network/chips/blob/main/CHIPs/chip-0002.md)
+/// format as an [`OwnershipProof`], so that the proof can't be prepared ahead of time. The coin ids are
+/// committed to with a merkle root, which can be published so that customers can check
+/// that specific coins were included.
+///
+/// This only proves control of the coins. The auditor should also check that each coin is unspent,
+/// for example by requesting its coin state from a full node.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReservesProof {
+    pub nonce: Bytes,
+    pub coins: Vec<CoinOwnershipSignature>,
+    pub merkle_root: Bytes32,
+}
+
+impl ReservesProof {
+    /// Signs each coin with the synthetic secret key that its standard puzzle hash is curried with.
+    pub fn sign(coins: &[(Coin, &SecretKey)], nonce: Bytes) -> Self {
+        let coins: Vec<CoinOwnershipSignature> = coins
+            .iter()
+            .map(|(coin, synthetic_key)| CoinOwnershipSignature {
+                coin: *coin,
+                public_key: synthetic_key.public_key(),
+                signature: sign(synthetic_key, Self::message_hash(coin.coin_id(), &nonce)),
+            })
+            .collect();
+
+        let merkle_root = Self::merkle_tree(&coins).root;
+
+        Self {
+            nonce,
+            coins,
+            merkle_root,
+        }
+    }
+
+    /// Checks every coin signature and the merkle root, and returns the total amount of the coins.
+    /// Returns [`None`] if anything is invalid, or the same coin is included more than once.
+    /// The caller should also check that the nonce is the one it asked for.
+    pub fn verify(&self) -> Option<u128> {
+        if Self::merkle_tree(&self.coins).root != self.merkle_root {
+            return None;
+        }
+
+        let mut coin_ids = HashSet::new();
+        let mut total = 0;
+
+        for item in &self.coins {
+            if !coin_ids.insert(item.coin.coin_id()) {
+                return None;
+            }
+
+            if StandardArgs::curry_tree_hash(item.public_key) != item.coin.puzzle_hash.into() {
+                return None;
+            }
+
+            let message_hash = Self::message_hash(item.coin.coin_id(), &self.nonce);
+
+            if !verify(&item.signature, &item.public_key, message_hash) {
+                return None;
+            }
+
+            total += u128::from(item.coin.amount);
+        }
+
+        Some(total)
+    }
+
+    /// Returns the merkle proof that a coin was included, in the same form as [`MerkleTree::get_proof`].
+    pub fn inclusion_proof(&self, coin_id: Bytes32) -> Option<(u32, Vec<Bytes32>)> {
+        Self::merkle_tree(&self.coins).get_proof(coin_id)
+    }
+
+    /// The message is the coin id, followed by the nonce.
+    pub fn message(coin_id: Bytes32, nonce: &[u8]) -> Bytes {
+        let mut message = coin_id.to_vec();
+        message.extend_from_slice(nonce);
+        message.into()
+    }
+
+    /// The hash that gets signed, in the same format as [`OwnershipProof::signed_message_hash`].
+    pub fn message_hash(coin_id: Bytes32, nonce: &[u8]) -> Bytes32 {
+        OwnershipProof::signed_message_hash(Self::message(coin_id, nonce))
+    }
+
+    fn merkle_tree(coins: &[CoinOwnershipSignature]) -> MerkleTree {
+        let coin_ids: Vec<Bytes32> = coins.iter().map(|item| item.coin.coin_id()).collect();
+        MerkleTree::new(&coin_ids)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::DerivableKey;
+
+    use super::*;
+
+    #[test]
+    fn test_reserves_proof() {
+        let master_key = SecretKey::from_seed(&[1; 32]);
+        let keys = [
+            master_key.derive_unhardened(0),
+            master_key.derive_unhardened(1),
+        ];
+
+        let coins: Vec<(Coin, &SecretKey)> = keys
+            .iter()
+            .zip([1, 2])
+            .map(|(sk, parent)| {
+                let puzzle_hash = StandardArgs::curry_tree_hash(sk.public_key()).into();
+                (Coin::new(Bytes32::new([parent; 32]), puzzle_hash, 1000), sk)
+            })
+            .collect();
+
+        let nonce = Bytes::new(b"audit-2024-q1".to_vec());
+        let proof = ReservesProof::sign(&coins, nonce.clone());
+        assert_eq!(proof.verify(), Some(2000));
+
+        let coin_id = coins[1].0.coin_id();
+        let (path, hashes) = proof.inclusion_proof(coin_id).expect("missing coin");
+        assert_eq!(
+            MerkleTree::new(&[coins[0].0.coin_id(), coin_id]).get_proof(coin_id),
+            Some((path, hashes))
+        );
+
+        // A signature can't be reused for another nonce.
+        let mut replayed = proof.clone();
+        replayed.nonce = Bytes::new(b"audit-2024-q2".to_vec());
+        assert_eq!(replayed.verify(), None);
+
+        // Coins can't be counted twice.
+        let duplicated = ReservesProof::sign(&[coins[0], coins[0]], nonce.clone());
+        assert_eq!(duplicated.verify(), None);
+
+        // A coin that isn't controlled by the signer can't be claimed.
+        let mut stolen = ReservesProof::sign(&[(coins[0].0, coins[1].1)], nonce);
+        assert_eq!(stolen.verify(), None);
+        stolen.coins[0].public_key = coins[0].1.public_key();
+        assert_eq!(stolen.verify(), None);
+    }
+}
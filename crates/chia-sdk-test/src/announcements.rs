@@ -6,7 +6,9 @@ use chia_sdk_types::{
 use clvm_traits::{FromClvm, ToClvm};
 use clvmr::{reduction::Reduction, run_program, Allocator, ChiaDialect, NodePtr};
 
-#[derive(Debug, Default, Clone)]
+use crate::SimulatorError;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Announcements {
     pub created_coin: Vec<Bytes>,
     pub asserted_coin: Vec<Bytes32>,
@@ -71,6 +73,10 @@ pub fn debug_announcements(coin_spends: &[CoinSpend]) {
 }
 
 pub fn announcements_for_spend(coin_spend: &CoinSpend) -> anyhow::Result<Announcements> {
+    Ok(spend_announcements(coin_spend)?)
+}
+
+pub(crate) fn spend_announcements(coin_spend: &CoinSpend) -> Result<Announcements, SimulatorError> {
     let mut announcements = Announcements::default();
 
     let allocator = &mut Allocator::new();
@@ -2,6 +2,8 @@ use std::io;
 
 use chia_consensus::gen::validation_error::ErrorCode;
 use chia_sdk_signer::SignerError;
+use clvm_traits::{FromClvmError, ToClvmError};
+use clvmr::reduction::EvalErr;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -17,4 +19,13 @@ pub enum SimulatorError {
 
     #[error("Missing key ")]
     MissingKey,
+
+    #[error("To CLVM error: {0}")]
+    ToClvm(#[from] ToClvmError),
+
+    #[error("From CLVM error: {0}")]
+    FromClvm(#[from] FromClvmError),
+
+    #[error("Eval error: {0}")]
+    Eval(#[from] EvalErr),
 }
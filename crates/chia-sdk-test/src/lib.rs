@@ -4,6 +4,7 @@ mod events;
 mod keys;
 mod peer_simulator;
 mod simulator;
+mod singleton_lineage;
 mod transaction;
 
 pub use announcements::*;
@@ -12,6 +13,7 @@ pub use events::*;
 pub use keys::*;
 pub use peer_simulator::*;
 pub use simulator::*;
+pub use singleton_lineage::*;
 pub use transaction::*;
 
 use chia_protocol::{Bytes32, Program};
@@ -14,7 +14,7 @@ use tokio::{
 use tokio_tungstenite::connect_async;
 use ws_connection::ws_connection;
 
-use crate::Simulator;
+use crate::{Simulator, TransactionFault};
 
 mod error;
 mod peer_map;
@@ -37,7 +37,7 @@ impl PeerSimulator {
     }
 
     pub async fn with_config(config: SimulatorConfig) -> Result<Self, PeerSimulatorError> {
-        log::info!("starting simulator");
+        tracing::info!("starting simulator");
 
         let addr = "127.0.0.1:0";
         let peer_map = PeerMap::default();
@@ -60,7 +60,7 @@ impl PeerSimulator {
                 let stream = match tokio_tungstenite::accept_async(stream).await {
                     Ok(stream) => stream,
                     Err(error) => {
-                        log::error!("error accepting websocket connection: {}", error);
+                        tracing::error!("error accepting websocket connection: {}", error);
                         continue;
                     }
                 };
@@ -91,7 +91,7 @@ impl PeerSimulator {
     pub async fn connect_split(
         &self,
     ) -> Result<(Peer, mpsc::Receiver<Message>), PeerSimulatorError> {
-        log::info!("connecting new peer to simulator");
+        tracing::info!("connecting new peer to simulator");
         let (ws, _) = connect_async(format!("ws://{}", self.addr)).await?;
         Ok(Peer::from_websocket(ws)?)
     }
@@ -101,7 +101,7 @@ impl PeerSimulator {
 
         tokio::spawn(async move {
             while let Some(message) = receiver.recv().await {
-                log::debug!("received message: {message:?}");
+                tracing::debug!("received message: {message:?}");
             }
         });
 
@@ -122,6 +122,27 @@ impl PeerSimulator {
         self.simulator.lock().await.hint_coin(coin_id, hint);
     }
 
+    /// See [`Simulator::set_validate_signatures`].
+    pub async fn set_validate_signatures(&self, validate_signatures: bool) {
+        self.simulator
+            .lock()
+            .await
+            .set_validate_signatures(validate_signatures);
+    }
+
+    /// See [`Simulator::inject_fault`]. Delayed transactions are included in the blocks created by later transactions.
+    pub async fn inject_fault(&self, fault: TransactionFault) {
+        self.simulator.lock().await.inject_fault(fault);
+    }
+
+    /// See [`Simulator::evict_transaction`].
+    pub async fn evict_transaction(&self, transaction_id: Bytes32) -> bool {
+        self.simulator
+            .lock()
+            .await
+            .evict_transaction(transaction_id)
+    }
+
     pub async fn coin_state(&self, coin_id: Bytes32) -> Option<CoinState> {
         self.simulator.lock().await.coin_state(coin_id)
     }
@@ -152,7 +173,9 @@ mod tests {
         Bytes, CoinSpend, CoinStateFilters, CoinStateUpdate, RespondCoinState, RespondPuzzleState,
         SpendBundle,
     };
+    use chia_sdk_client::ClientError;
     use chia_sdk_types::{AggSigMe, CreateCoin, Remark};
+    use futures_util::TryStreamExt;
 
     use crate::{coin_state_updates, test_secret_key, test_transaction, to_program, to_puzzle};
 
@@ -761,6 +784,64 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_multiple_peers() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let (peer_a, mut receiver_a) = sim.connect_split().await?;
+        let (peer_b, mut receiver_b) = sim.connect_split().await?;
+
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let other_puzzle_hash = Bytes32::new([1; 32]);
+
+        peer_a.register_for_ph_updates(vec![puzzle_hash], 0).await?;
+        peer_b
+            .register_for_ph_updates(vec![other_puzzle_hash], 0)
+            .await?;
+
+        for amount in [2, 3] {
+            let coin = sim.mint_coin(puzzle_hash, amount).await;
+            let child_coin = Coin::new(coin.coin_id(), other_puzzle_hash, 1);
+
+            let spend_bundle = SpendBundle::new(
+                vec![CoinSpend::new(
+                    coin,
+                    puzzle_reveal.clone(),
+                    to_program([CreateCoin::new(other_puzzle_hash, 1, Vec::new())])?,
+                )],
+                Signature::default(),
+            );
+
+            let ack = peer_a.send_transaction(spend_bundle).await?;
+            assert_eq!(ack.status, 1);
+
+            // The response is sent after any updates, so this waits for them to arrive.
+            peer_b.request_children(coin.coin_id()).await?;
+
+            let updates = coin_state_updates(&mut receiver_a);
+            assert_eq!(updates.len(), 1);
+            assert_eq!(updates[0].items.len(), 1);
+            assert_eq!(updates[0].items[0].coin, coin);
+
+            let updates = coin_state_updates(&mut receiver_b);
+
+            if amount == 2 {
+                assert_eq!(updates.len(), 1);
+                assert_eq!(
+                    updates[0].items,
+                    [CoinState::new(child_coin, None, Some(0))]
+                );
+
+                // Once the second peer unsubscribes, it no longer receives updates.
+                let removed = peer_b.remove_puzzle_subscriptions(None).await?;
+                assert_eq!(removed.puzzle_hashes, [other_puzzle_hash]);
+            } else {
+                assert_eq!(updates.len(), 0);
+            }
+        }
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_request_coin_state() -> anyhow::Result<()> {
         let sim = PeerSimulator::new().await?;
@@ -815,6 +896,75 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_batched_requests() -> anyhow::Result<()> {
+        let sim = PeerSimulator::new().await?;
+        let peer = sim.connect().await?;
+
+        let mut puzzle_hashes = Vec::new();
+        let mut coin_ids = Vec::new();
+
+        for i in 0..5 {
+            let (puzzle_hash, _) = to_puzzle(i)?;
+            let coin = sim.mint_coin(puzzle_hash, 0).await;
+            puzzle_hashes.push(puzzle_hash);
+            coin_ids.push(coin.coin_id());
+        }
+
+        let responses: Vec<_> = peer
+            .register_for_ph_updates_batched(&puzzle_hashes, 0, 2)?
+            .try_collect()
+            .await?;
+        assert_eq!(
+            responses
+                .iter()
+                .map(|response| response.puzzle_hashes.len())
+                .collect::<Vec<_>>(),
+            [2, 2, 1]
+        );
+        let coin_states: Vec<Bytes32> = responses
+            .into_iter()
+            .flat_map(|response| response.coin_states)
+            .map(|coin_state| coin_state.coin.coin_id())
+            .collect();
+        assert_eq!(coin_states, coin_ids);
+
+        let responses: Vec<_> = peer
+            .register_for_coin_updates_batched(&coin_ids, 0, 3)?
+            .try_collect()
+            .await?;
+        assert_eq!(responses.len(), 2);
+        assert_eq!(
+            responses
+                .into_iter()
+                .flat_map(|response| response.coin_states)
+                .count(),
+            5
+        );
+
+        let responses: Vec<_> = peer
+            .request_coin_state_batched(
+                &coin_ids,
+                None,
+                sim.config().constants.genesis_challenge,
+                false,
+                4,
+            )?
+            .try_collect()
+            .await?;
+        assert_eq!(responses.len(), 2);
+        for response in responses {
+            assert!(response.is_ok());
+        }
+
+        assert!(matches!(
+            peer.register_for_coin_updates_batched(&coin_ids, 0, 0),
+            Err(ClientError::InvalidBatchSize)
+        ));
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_request_puzzle_state() -> anyhow::Result<()> {
         let sim = PeerSimulator::new().await?;
@@ -28,6 +28,27 @@ impl Subscriptions {
             .extend(puzzle_hashes);
     }
 
+    pub(crate) fn remove_coin_subscriptions(
+        &mut self,
+        peer: SocketAddr,
+        coin_ids: Option<&[Bytes32]>,
+    ) -> Vec<Bytes32> {
+        remove_subscriptions(&mut self.coin_subscriptions, peer, coin_ids)
+    }
+
+    pub(crate) fn remove_puzzle_subscriptions(
+        &mut self,
+        peer: SocketAddr,
+        puzzle_hashes: Option<&[Bytes32]>,
+    ) -> Vec<Bytes32> {
+        remove_subscriptions(&mut self.puzzle_subscriptions, peer, puzzle_hashes)
+    }
+
+    pub(crate) fn remove_peer(&mut self, peer: SocketAddr) {
+        self.coin_subscriptions.shift_remove(&peer);
+        self.puzzle_subscriptions.shift_remove(&peer);
+    }
+
     pub(crate) fn subscription_count(&self, peer: SocketAddr) -> usize {
         self.coin_subscriptions.get(&peer).map_or(0, IndexSet::len)
             + self
@@ -52,3 +73,29 @@ impl Subscriptions {
         self.puzzle_subscriptions.get(&peer)
     }
 }
+
+/// Removes the given subscriptions, or all of them if [`None`], and returns the ones that were removed.
+fn remove_subscriptions(
+    subscriptions: &mut IndexMap<SocketAddr, IndexSet<Bytes32>>,
+    peer: SocketAddr,
+    items: Option<&[Bytes32]>,
+) -> Vec<Bytes32> {
+    let Some(existing) = subscriptions.get_mut(&peer) else {
+        return Vec::new();
+    };
+
+    let removed = match items {
+        Some(items) => items
+            .iter()
+            .copied()
+            .filter(|item| existing.shift_remove(item))
+            .collect(),
+        None => existing.drain(..).collect(),
+    };
+
+    if existing.is_empty() {
+        subscriptions.shift_remove(&peer);
+    }
+
+    removed
+}
@@ -8,9 +8,10 @@ use chia_protocol::{
     Bytes, Bytes32, CoinState, CoinStateUpdate, Message, NewPeakWallet, ProtocolMessageTypes,
     PuzzleSolutionResponse, RegisterForCoinUpdates, RegisterForPhUpdates, RejectCoinState,
     RejectPuzzleSolution, RejectPuzzleState, RejectStateReason, RequestChildren, RequestCoinState,
-    RequestPuzzleSolution, RequestPuzzleState, RespondChildren, RespondCoinState,
-    RespondPuzzleSolution, RespondPuzzleState, RespondToCoinUpdates, RespondToPhUpdates,
-    SendTransaction, SpendBundle, TransactionAck,
+    RequestPuzzleSolution, RequestPuzzleState, RequestRemoveCoinSubscriptions,
+    RequestRemovePuzzleSubscriptions, RespondChildren, RespondCoinState, RespondPuzzleSolution,
+    RespondPuzzleState, RespondRemoveCoinSubscriptions, RespondRemovePuzzleSubscriptions,
+    RespondToCoinUpdates, RespondToPhUpdates, SendTransaction, SpendBundle, TransactionAck,
 };
 use chia_traits::Streamable;
 use clvmr::NodePtr;
@@ -47,7 +48,7 @@ pub(crate) async fn ws_connection(
     tokio::spawn(async move {
         while let Some(message) = rx.next().await {
             if let Err(error) = sink.send(message).await {
-                log::error!("error sending message to peer: {}", error);
+                tracing::error!("error sending message to peer: {}", error);
                 continue;
             }
         }
@@ -57,7 +58,7 @@ pub(crate) async fn ws_connection(
         let message = match message {
             Ok(message) => message,
             Err(error) => {
-                log::info!("received error from stream: {:?}", error);
+                tracing::info!("received error from stream: {:?}", error);
                 break;
             }
         };
@@ -73,12 +74,13 @@ pub(crate) async fn ws_connection(
         )
         .await
         {
-            log::error!("error handling message: {}", error);
+            tracing::error!("error handling message: {}", error);
             break;
         }
     }
 
     peer_map.remove(addr).await;
+    subscriptions.lock().await.remove_peer(addr);
 }
 
 async fn handle_message(
@@ -113,6 +115,34 @@ async fn handle_message(
             let response = register_for_ph_updates(addr, request, &simulator, subscriptions)?;
             (ProtocolMessageTypes::RespondToPhUpdates, response)
         }
+        ProtocolMessageTypes::RequestRemoveCoinSubscriptions => {
+            let request = RequestRemoveCoinSubscriptions::from_bytes(&request.data)?;
+            let coin_ids = subscriptions
+                .lock()
+                .await
+                .remove_coin_subscriptions(addr, request.coin_ids.as_deref());
+            let response = RespondRemoveCoinSubscriptions::new(coin_ids)
+                .to_bytes()?
+                .into();
+            (
+                ProtocolMessageTypes::RespondRemoveCoinSubscriptions,
+                response,
+            )
+        }
+        ProtocolMessageTypes::RequestRemovePuzzleSubscriptions => {
+            let request = RequestRemovePuzzleSubscriptions::from_bytes(&request.data)?;
+            let puzzle_hashes = subscriptions
+                .lock()
+                .await
+                .remove_puzzle_subscriptions(addr, request.puzzle_hashes.as_deref());
+            let response = RespondRemovePuzzleSubscriptions::new(puzzle_hashes)
+                .to_bytes()?
+                .into();
+            (
+                ProtocolMessageTypes::RespondRemovePuzzleSubscriptions,
+                response,
+            )
+        }
         ProtocolMessageTypes::RequestPuzzleSolution => {
             let request = RequestPuzzleSolution::from_bytes(&request.data)?;
             let response = request_puzzle_solution(&request, &simulator)?;
@@ -226,7 +256,7 @@ async fn send_transaction(
     ) {
         Ok(updates) => updates,
         Err(error) => {
-            log::error!("error processing transaction: {:?}", &error);
+            tracing::error!("error processing transaction: {:?}", &error);
 
             let error_code = match error {
                 PeerSimulatorError::Simulator(SimulatorError::Validation(error_code)) => error_code,
@@ -1,17 +1,71 @@
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 
 use chia_bls::{DerivableKey, PublicKey, SecretKey};
 use chia_consensus::{
-    consensus_constants::ConsensusConstants, gen::validation_error::ErrorCode,
+    consensus_constants::ConsensusConstants,
+    gen::{owned_conditions::OwnedSpendBundleConditions, validation_error::ErrorCode},
+    spendbundle_conditions::get_conditions_from_spendbundle,
     spendbundle_validation::validate_clvm_and_signature,
 };
 use chia_protocol::{Bytes32, Coin, CoinSpend, CoinState, Program, SpendBundle};
 use chia_puzzles::standard::StandardArgs;
 use chia_sdk_types::TESTNET11_CONSTANTS;
+use clvmr::Allocator;
 use fastrand::Rng;
 use indexmap::{IndexMap, IndexSet};
 
-use crate::{sign_transaction, test_secret_key, SimulatorError};
+use crate::{
+    sign_transaction, spend_announcements, test_secret_key, Announcements, SimulatorError,
+};
+
+/// A failure to apply to the next transaction that passes validation, to test how wallets handle
+/// transactions that don't get confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionFault {
+    /// The transaction is accepted, but never included in a block.
+    Drop,
+    /// The transaction is accepted, but isn't included until this many blocks have been created.
+    /// It's included in the block after that, unless it has been evicted or is no longer valid.
+    Delay(u32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingTransaction {
+    spend_bundle: SpendBundle,
+    constants: ConsensusConstants,
+    blocks_remaining: u32,
+}
+
+#[derive(Debug, Clone)]
+struct TransactionChanges {
+    conditions: OwnedSpendBundleConditions,
+    updates: IndexMap<Bytes32, CoinState>,
+    hints: IndexMap<Bytes32, IndexSet<Bytes32>>,
+    puzzle_solutions: IndexMap<Bytes32, (Program, Program)>,
+}
+
+/// What a spend bundle would do if it were included in the next block, as computed by [`Simulator::dry_run`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DryRun {
+    /// The coins that would be spent, in the order of the coin spends.
+    pub spent_coins: Vec<Coin>,
+    /// The coins that would be created, including any which are spent in the same bundle.
+    pub created_coins: Vec<Coin>,
+    /// The announcements created and asserted by each coin spend, in the order of the coin spends.
+    pub announcements: Vec<Announcements>,
+    /// The lowest height the bundle can be included at, from `ASSERT_HEIGHT_ABSOLUTE`.
+    pub height_absolute: u32,
+    /// The lowest timestamp the bundle can be included at, from `ASSERT_SECONDS_ABSOLUTE`.
+    pub seconds_absolute: u64,
+    /// The height the bundle must be included before, from `ASSERT_BEFORE_HEIGHT_ABSOLUTE`.
+    pub before_height_absolute: Option<u32>,
+    /// The timestamp the bundle must be included before, from `ASSERT_BEFORE_SECONDS_ABSOLUTE`.
+    pub before_seconds_absolute: Option<u64>,
+    /// The amount of the spent coins that isn't used to create new coins.
+    pub fee: u128,
+    /// The total CLVM cost of the bundle, including the cost of its conditions and size.
+    pub cost: u64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Simulator {
@@ -21,6 +75,9 @@ pub struct Simulator {
     coin_states: IndexMap<Bytes32, CoinState>,
     hinted_coins: IndexMap<Bytes32, IndexSet<Bytes32>>,
     puzzle_and_solutions: IndexMap<Bytes32, (Program, Program)>,
+    validate_signatures: bool,
+    faults: VecDeque<TransactionFault>,
+    pending: Vec<PendingTransaction>,
 }
 
 impl Default for Simulator {
@@ -46,9 +103,46 @@ impl Simulator {
             coin_states: IndexMap::new(),
             hinted_coins: IndexMap::new(),
             puzzle_and_solutions: IndexMap::new(),
+            validate_signatures: true,
+            faults: VecDeque::new(),
+            pending: Vec::new(),
         }
     }
 
+    /// Sets whether the aggregated signature of spend bundles is checked.
+    /// Disabling this is useful for tests that only care about the structure of the spends.
+    pub fn set_validate_signatures(&mut self, validate_signatures: bool) {
+        self.validate_signatures = validate_signatures;
+    }
+
+    /// Queues a fault to apply to the next valid transaction. Faults are applied in the order they're injected.
+    pub fn inject_fault(&mut self, fault: TransactionFault) {
+        self.faults.push_back(fault);
+    }
+
+    /// The ids of the transactions that have been delayed, but not yet included in a block.
+    pub fn pending_transactions(&self) -> Vec<Bytes32> {
+        self.pending
+            .iter()
+            .map(|pending| pending.spend_bundle.name())
+            .collect()
+    }
+
+    /// Removes a delayed transaction before it's included in a block, as if it were evicted from the mempool.
+    /// Returns `false` if there's no pending transaction with the given id.
+    pub fn evict_transaction(&mut self, transaction_id: Bytes32) -> bool {
+        let len = self.pending.len();
+        self.pending
+            .retain(|pending| pending.spend_bundle.name() != transaction_id);
+        self.pending.len() != len
+    }
+
+    /// Creates an empty block, which includes any delayed transactions that are due.
+    /// Returns the coin states updated by those transactions.
+    pub fn new_block(&mut self) -> IndexMap<Bytes32, CoinState> {
+        self.create_block()
+    }
+
     pub fn height(&self) -> u32 {
         self.height
     }
@@ -66,10 +160,34 @@ impl Simulator {
         self.coin_states.insert(coin.coin_id(), coin_state);
     }
 
+    pub(crate) fn random_coin_id(&mut self) -> Bytes32 {
+        let mut coin_id = [0; 32];
+        self.rng.fill(&mut coin_id);
+        coin_id.into()
+    }
+
+    /// Inserts a coin that has been spent in the current block, along with its puzzle and solution.
+    pub(crate) fn insert_spent_coin(&mut self, coin: Coin, coin_spend: &CoinSpend) {
+        let created_height = self
+            .coin_state(coin.coin_id())
+            .and_then(|coin_state| coin_state.created_height)
+            .unwrap_or(self.height);
+
+        self.coin_states.insert(
+            coin.coin_id(),
+            CoinState::new(coin, Some(self.height), Some(created_height)),
+        );
+        self.puzzle_and_solutions.insert(
+            coin.coin_id(),
+            (
+                coin_spend.puzzle_reveal.clone(),
+                coin_spend.solution.clone(),
+            ),
+        );
+    }
+
     pub fn new_coin(&mut self, puzzle_hash: Bytes32, amount: u64) -> Coin {
-        let mut parent_coin_info = [0; 32];
-        self.rng.fill(&mut parent_coin_info);
-        let coin = Coin::new(parent_coin_info.into(), puzzle_hash, amount);
+        let coin = Coin::new(self.random_coin_id(), puzzle_hash, amount);
         self.insert_coin(coin);
         coin
     }
@@ -148,19 +266,117 @@ impl Simulator {
     }
 
     /// Processes a spend bunndle and returns the updated coin states.
+    ///
+    /// If a [`TransactionFault`] has been injected, the transaction is still validated,
+    /// but no coin states are updated until it's included in a later block (if ever).
     pub fn new_transaction(
         &mut self,
         spend_bundle: SpendBundle,
         constants: &ConsensusConstants,
     ) -> Result<IndexMap<Bytes32, CoinState>, SimulatorError> {
+        let changes = self.validate_transaction(&spend_bundle, constants)?;
+
+        match self.faults.pop_front() {
+            Some(TransactionFault::Drop) => return Ok(IndexMap::new()),
+            Some(TransactionFault::Delay(blocks)) => {
+                self.pending.push(PendingTransaction {
+                    spend_bundle,
+                    constants: constants.clone(),
+                    blocks_remaining: blocks,
+                });
+                return Ok(IndexMap::new());
+            }
+            None => {}
+        }
+
+        let mut updates = self.apply_changes(changes);
+        updates.extend(self.create_block());
+
+        Ok(updates)
+    }
+
+    /// Validates a spend bundle against the current state, and returns what it would do without applying it.
+    ///
+    /// The aggregated signature isn't checked, so that a bundle can be dry run before it's signed.
+    pub fn dry_run(
+        &self,
+        spend_bundle: &SpendBundle,
+        constants: &ConsensusConstants,
+    ) -> Result<DryRun, SimulatorError> {
+        let changes = self.validate_transaction_inner(spend_bundle, constants, false)?;
+        let conditions = changes.conditions;
+
+        let spent_coins = conditions
+            .spends
+            .iter()
+            .map(|spend| Coin::new(spend.parent_id, spend.puzzle_hash, spend.coin_amount))
+            .collect();
+
+        let created_coins = conditions
+            .spends
+            .iter()
+            .flat_map(|spend| {
+                spend
+                    .create_coin
+                    .iter()
+                    .map(|new_coin| Coin::new(spend.coin_id, new_coin.0, new_coin.1))
+            })
+            .collect();
+
+        let announcements = spend_bundle
+            .coin_spends
+            .iter()
+            .map(spend_announcements)
+            .collect::<Result<_, _>>()?;
+
+        Ok(DryRun {
+            spent_coins,
+            created_coins,
+            announcements,
+            height_absolute: conditions.height_absolute,
+            seconds_absolute: conditions.seconds_absolute,
+            before_height_absolute: conditions.before_height_absolute,
+            before_seconds_absolute: conditions.before_seconds_absolute,
+            fee: conditions.removal_amount - conditions.addition_amount,
+            cost: conditions.cost,
+        })
+    }
+
+    fn validate_transaction(
+        &self,
+        spend_bundle: &SpendBundle,
+        constants: &ConsensusConstants,
+    ) -> Result<TransactionChanges, SimulatorError> {
+        self.validate_transaction_inner(spend_bundle, constants, self.validate_signatures)
+    }
+
+    fn validate_transaction_inner(
+        &self,
+        spend_bundle: &SpendBundle,
+        constants: &ConsensusConstants,
+        validate_signatures: bool,
+    ) -> Result<TransactionChanges, SimulatorError> {
         if spend_bundle.coin_spends.is_empty() {
             return Err(SimulatorError::Validation(ErrorCode::InvalidSpendBundle));
         }
 
         // TODO: Fix cost
-        let (conds, _pairings, _duration) =
-            validate_clvm_and_signature(&spend_bundle, 7_700_000_000, constants, self.height)
-                .map_err(SimulatorError::Validation)?;
+        let conds = if validate_signatures {
+            validate_clvm_and_signature(spend_bundle, 7_700_000_000, constants, self.height)
+                .map_err(SimulatorError::Validation)?
+                .0
+        } else {
+            let mut allocator = Allocator::new();
+            let conds = get_conditions_from_spendbundle(
+                &mut allocator,
+                spend_bundle,
+                7_700_000_000,
+                self.height,
+                constants,
+            )
+            .map_err(|error| SimulatorError::Validation(error.1))?;
+            OwnedSpendBundleConditions::from(&allocator, conds)
+        };
 
         let puzzle_hashes: HashSet<Bytes32> =
             conds.spends.iter().map(|spend| spend.puzzle_hash).collect();
@@ -180,10 +396,13 @@ impl Simulator {
         let mut added_hints = IndexMap::new();
         let mut puzzle_solutions = IndexMap::new();
 
-        for coin_spend in spend_bundle.coin_spends {
+        for coin_spend in &spend_bundle.coin_spends {
             puzzle_solutions.insert(
                 coin_spend.coin.coin_id(),
-                (coin_spend.puzzle_reveal, coin_spend.solution),
+                (
+                    coin_spend.puzzle_reveal.clone(),
+                    coin_spend.solution.clone(),
+                ),
             );
         }
 
@@ -237,15 +456,22 @@ impl Simulator {
             coin_state.spent_height = Some(height);
         }
 
-        // Update the coin data.
-        let mut updates = added_coins.clone();
+        let mut updates = added_coins;
         updates.extend(removed_coins);
-        self.create_block();
-        self.coin_states.extend(updates.clone());
-        self.hinted_coins.extend(added_hints.clone());
-        self.puzzle_and_solutions.extend(puzzle_solutions);
 
-        Ok(updates)
+        Ok(TransactionChanges {
+            conditions: conds,
+            updates,
+            hints: added_hints,
+            puzzle_solutions,
+        })
+    }
+
+    fn apply_changes(&mut self, changes: TransactionChanges) -> IndexMap<Bytes32, CoinState> {
+        self.coin_states.extend(changes.updates.clone());
+        self.hinted_coins.extend(changes.hints);
+        self.puzzle_and_solutions.extend(changes.puzzle_solutions);
+        changes.updates
     }
 
     pub fn lookup_coin_ids(&self, coin_ids: &IndexSet<Bytes32>) -> Vec<CoinState> {
@@ -281,10 +507,189 @@ impl Simulator {
         coin_states.into_values().collect()
     }
 
-    fn create_block(&mut self) {
+    pub(crate) fn create_block(&mut self) -> IndexMap<Bytes32, CoinState> {
+        let mut updates = IndexMap::new();
+
+        // Delayed transactions that have become invalid in the meantime are dropped.
+        for pending in std::mem::take(&mut self.pending) {
+            if pending.blocks_remaining > 0 {
+                self.pending.push(PendingTransaction {
+                    blocks_remaining: pending.blocks_remaining - 1,
+                    ..pending
+                });
+            } else if let Ok(changes) =
+                self.validate_transaction(&pending.spend_bundle, &pending.constants)
+            {
+                updates.extend(self.apply_changes(changes));
+            }
+        }
+
         let mut header_hash = [0; 32];
         self.rng.fill(&mut header_hash);
         self.header_hashes.push(header_hash.into());
         self.height += 1;
+
+        updates
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::Bytes;
+    use chia_sdk_types::{AggSigMe, Conditions};
+
+    use crate::{to_program, to_puzzle};
+
+    use super::*;
+
+    fn spend_bundle(coin: Coin, puzzle_reveal: Program) -> anyhow::Result<SpendBundle> {
+        Ok(SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(())?)],
+            Signature::default(),
+        ))
+    }
+
+    #[test]
+    fn test_skip_signature_validation() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+
+        let public_key = test_secret_key()?.public_key();
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(
+                coin,
+                puzzle_reveal,
+                to_program([AggSigMe::new(public_key, Bytes::default())])?,
+            )],
+            Signature::default(),
+        );
+
+        assert!(matches!(
+            sim.new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS),
+            Err(SimulatorError::Validation(ErrorCode::BadAggregateSignature))
+        ));
+
+        sim.set_validate_signatures(false);
+        sim.new_transaction(spend_bundle, &TESTNET11_CONSTANTS)?;
+
+        assert!(sim
+            .coin_state(coin.coin_id())
+            .unwrap()
+            .spent_height
+            .is_some());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dry_run() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1000);
+
+        let conditions = Conditions::new()
+            .create_coin(puzzle_hash, 600, Vec::new())
+            .create_coin_announcement(Bytes::new(b"hello".to_vec()))
+            .assert_before_height_absolute(100)
+            .agg_sig_me(test_secret_key()?.public_key(), Bytes::default());
+        let spend_bundle = SpendBundle::new(
+            vec![CoinSpend::new(coin, puzzle_reveal, to_program(conditions)?)],
+            Signature::default(),
+        );
+
+        let dry_run = sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS)?;
+        assert_eq!(dry_run.spent_coins, [coin]);
+        assert_eq!(
+            dry_run.created_coins,
+            [Coin::new(coin.coin_id(), puzzle_hash, 600)]
+        );
+        assert_eq!(
+            dry_run.announcements[0].created_coin,
+            [Bytes::new(b"hello".to_vec())]
+        );
+        assert_eq!(dry_run.before_height_absolute, Some(100));
+        assert_eq!(dry_run.height_absolute, 0);
+        assert_eq!(dry_run.fee, 400);
+
+        // The cost includes at least the fixed costs of the CREATE_COIN and AGG_SIG_ME conditions.
+        assert!(dry_run.cost > 1_800_000 + 1_200_000);
+
+        // Nothing is applied to the simulator.
+        assert_eq!(sim.coin_state(coin.coin_id()).unwrap().spent_height, None);
+        assert_eq!(sim.height(), 0);
+
+        // The bundle can be dry run before it's signed, but is still validated against the current state.
+        sim.set_validate_signatures(false);
+        sim.new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS)?;
+        assert!(matches!(
+            sim.dry_run(&spend_bundle, &TESTNET11_CONSTANTS),
+            Err(SimulatorError::Validation(ErrorCode::DoubleSpend))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_drop_transaction() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+        let spend_bundle = spend_bundle(coin, puzzle_reveal)?;
+
+        sim.inject_fault(TransactionFault::Drop);
+        assert!(sim
+            .new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS)?
+            .is_empty());
+        sim.new_block();
+        assert_eq!(sim.coin_state(coin.coin_id()).unwrap().spent_height, None);
+
+        // Retrying the transaction succeeds, since the fault only applies once.
+        let updates = sim.new_transaction(spend_bundle, &TESTNET11_CONSTANTS)?;
+        assert_eq!(updates[&coin.coin_id()].spent_height, Some(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_delay_transaction() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+        let spend_bundle = spend_bundle(coin, puzzle_reveal)?;
+
+        sim.inject_fault(TransactionFault::Delay(2));
+        assert!(sim
+            .new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS)?
+            .is_empty());
+        assert_eq!(sim.pending_transactions(), [spend_bundle.name()]);
+
+        assert!(sim.new_block().is_empty());
+        assert!(sim.new_block().is_empty());
+
+        let updates = sim.new_block();
+        assert_eq!(updates[&coin.coin_id()].spent_height, Some(2));
+        assert!(sim.pending_transactions().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_evict_transaction() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle_reveal) = to_puzzle(1)?;
+        let coin = sim.new_coin(puzzle_hash, 1);
+        let spend_bundle = spend_bundle(coin, puzzle_reveal)?;
+
+        sim.inject_fault(TransactionFault::Delay(0));
+        sim.new_transaction(spend_bundle.clone(), &TESTNET11_CONSTANTS)?;
+
+        assert!(sim.evict_transaction(spend_bundle.name()));
+        assert!(!sim.evict_transaction(spend_bundle.name()));
+        assert!(sim.new_block().is_empty());
+        assert_eq!(sim.coin_state(coin.coin_id()).unwrap().spent_height, None);
+
+        Ok(())
     }
 }
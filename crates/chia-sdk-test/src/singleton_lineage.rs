@@ -0,0 +1,319 @@
+use anyhow::bail;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program};
+use chia_puzzles::{
+    singleton::{
+        LauncherSolution, SingletonArgs, SingletonSolution, SINGLETON_LAUNCHER_PUZZLE,
+        SINGLETON_LAUNCHER_PUZZLE_HASH, SINGLETON_TOP_LAYER_PUZZLE,
+    },
+    EveProof, LineageProof, Proof,
+};
+use chia_sdk_types::{run_puzzle, Condition};
+use clvm_traits::{FromClvm, ToClvm};
+use clvm_utils::{tree_hash, CurriedProgram};
+use clvmr::{serde::node_from_bytes, Allocator, NodePtr};
+
+use crate::Simulator;
+
+/// Builds the history of a singleton, and inserts it directly into the [`Simulator`].
+///
+/// Each spend is run to find the singleton's next inner puzzle hash, but the spends aren't validated
+/// by the simulator. This makes it cheap to set up a singleton with a long history, so that
+/// parsers and trackers can be tested against it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SingletonLineageBuilder {
+    amount: u64,
+    inner_spends: Vec<(Program, Program)>,
+}
+
+/// A singleton whose history has been inserted into the [`Simulator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SingletonLineage {
+    pub launcher_id: Bytes32,
+    /// The coin spends in the order they happened, starting with the launcher.
+    pub coin_spends: Vec<CoinSpend>,
+    /// The current unspent singleton coin.
+    pub coin: Coin,
+    /// The proof needed to spend the current singleton coin.
+    pub proof: Proof,
+    pub inner_puzzle_hash: Bytes32,
+}
+
+impl SingletonLineageBuilder {
+    /// Creates a builder for a singleton launched with the given amount, which must be odd.
+    pub fn new(amount: u64) -> Self {
+        Self {
+            amount,
+            inner_spends: Vec::new(),
+        }
+    }
+
+    /// Adds a spend of the singleton, with the given inner puzzle and solution.
+    ///
+    /// The inner puzzle of the first spend is the one that the singleton is launched with. The inner puzzle
+    /// of each spend after that must match the hash of the odd `CREATE_COIN` output of the previous spend.
+    #[must_use]
+    pub fn spend(mut self, inner_puzzle: Program, inner_solution: Program) -> Self {
+        self.inner_spends.push((inner_puzzle, inner_solution));
+        self
+    }
+
+    /// Inserts the launcher and each of the singleton spends into the simulator, one block at a time.
+    /// At least one spend is required, since it determines the inner puzzle of the eve singleton.
+    ///
+    /// Every spend is checked before anything is inserted, so no coins are added to the simulator on error.
+    pub fn build(self, sim: &mut Simulator) -> anyhow::Result<SingletonLineage> {
+        if self.amount % 2 == 0 {
+            bail!("a singleton must have an odd amount");
+        }
+
+        let Some((eve_inner_puzzle, _)) = self.inner_spends.first() else {
+            bail!("a singleton lineage needs at least one spend");
+        };
+
+        let mut allocator = Allocator::new();
+        let singleton_mod = node_from_bytes(&mut allocator, &SINGLETON_TOP_LAYER_PUZZLE)?;
+
+        let eve_inner_puzzle = eve_inner_puzzle.to_clvm(&mut allocator)?;
+        let eve_inner_puzzle_hash = Bytes32::from(tree_hash(&allocator, eve_inner_puzzle));
+
+        // Run each inner spend first, since its outputs don't depend on the launcher id.
+        let mut steps = Vec::with_capacity(self.inner_spends.len());
+        let mut inner_puzzle_hash = eve_inner_puzzle_hash;
+
+        for (inner_puzzle, inner_solution) in self.inner_spends {
+            let inner_puzzle = inner_puzzle.to_clvm(&mut allocator)?;
+            let inner_solution = inner_solution.to_clvm(&mut allocator)?;
+
+            if tree_hash(&allocator, inner_puzzle) != inner_puzzle_hash.into() {
+                bail!("inner puzzle doesn't match the singleton's inner puzzle hash");
+            }
+
+            let output = run_puzzle(&mut allocator, inner_puzzle, inner_solution)?;
+            let conditions = Vec::<Condition<NodePtr>>::from_clvm(&allocator, output)?;
+
+            let mut child = None;
+            let mut even_outputs = Vec::new();
+
+            for condition in conditions {
+                let Condition::CreateCoin(create_coin) = condition else {
+                    continue;
+                };
+
+                // Only the odd output is wrapped in the singleton layer.
+                if create_coin.amount % 2 == 0 {
+                    even_outputs.push((create_coin.puzzle_hash, create_coin.amount));
+                } else if child.replace(create_coin).is_some() {
+                    bail!("singleton spend has more than one odd output");
+                }
+            }
+
+            let Some(child) = child else {
+                bail!("singleton spend has no odd output");
+            };
+
+            let parent_inner_puzzle_hash = inner_puzzle_hash;
+            inner_puzzle_hash = child.puzzle_hash;
+
+            steps.push((
+                inner_puzzle,
+                inner_solution,
+                parent_inner_puzzle_hash,
+                child,
+                even_outputs,
+            ));
+        }
+
+        let launcher = Coin::new(
+            sim.random_coin_id(),
+            SINGLETON_LAUNCHER_PUZZLE_HASH.into(),
+            self.amount,
+        );
+        let launcher_id = launcher.coin_id();
+
+        let eve_coin = Coin::new(
+            launcher_id,
+            SingletonArgs::curry_tree_hash(launcher_id, eve_inner_puzzle_hash.into()).into(),
+            self.amount,
+        );
+        let mut coin = eve_coin;
+        let mut proof = Proof::Eve(EveProof {
+            parent_parent_coin_info: launcher.parent_coin_info,
+            parent_amount: launcher.amount,
+        });
+
+        let launcher_solution = LauncherSolution {
+            singleton_puzzle_hash: coin.puzzle_hash,
+            amount: coin.amount,
+            key_value_list: (),
+        }
+        .to_clvm(&mut allocator)?;
+
+        let mut coin_spends = vec![CoinSpend::new(
+            launcher,
+            SINGLETON_LAUNCHER_PUZZLE.to_vec().into(),
+            Program::from_clvm(&allocator, launcher_solution)?,
+        )];
+        let mut children = Vec::with_capacity(steps.len());
+
+        for (inner_puzzle, inner_solution, parent_inner_puzzle_hash, child, even_outputs) in steps {
+            let puzzle_reveal = CurriedProgram {
+                program: singleton_mod,
+                args: SingletonArgs::new(launcher_id, inner_puzzle),
+            }
+            .to_clvm(&mut allocator)?;
+
+            let solution = SingletonSolution {
+                lineage_proof: proof,
+                amount: coin.amount,
+                inner_solution,
+            }
+            .to_clvm(&mut allocator)?;
+
+            coin_spends.push(CoinSpend::new(
+                coin,
+                Program::from_clvm(&allocator, puzzle_reveal)?,
+                Program::from_clvm(&allocator, solution)?,
+            ));
+
+            proof = Proof::Lineage(LineageProof {
+                parent_parent_coin_info: coin.parent_coin_info,
+                parent_inner_puzzle_hash,
+                parent_amount: coin.amount,
+            });
+
+            let parent_coin_id = coin.coin_id();
+
+            coin = Coin::new(
+                parent_coin_id,
+                SingletonArgs::curry_tree_hash(launcher_id, child.puzzle_hash.into()).into(),
+                child.amount,
+            );
+
+            let mut created = even_outputs
+                .into_iter()
+                .map(|(puzzle_hash, amount)| Coin::new(parent_coin_id, puzzle_hash, amount))
+                .collect::<Vec<_>>();
+            created.push(coin);
+            children.push(created);
+        }
+
+        // Nothing can fail past this point, so the simulator is only changed once the lineage is complete.
+        sim.insert_spent_coin(launcher, &coin_spends[0]);
+        sim.insert_coin(eve_coin);
+
+        for (coin_spend, created) in coin_spends[1..].iter().zip(children) {
+            sim.create_block();
+            sim.insert_spent_coin(coin_spend.coin, coin_spend);
+
+            for created_coin in created {
+                sim.insert_coin(created_coin);
+            }
+        }
+
+        Ok(SingletonLineage {
+            launcher_id,
+            coin_spends,
+            coin,
+            proof,
+            inner_puzzle_hash,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::Signature;
+    use chia_protocol::SpendBundle;
+    use chia_sdk_types::{CreateCoin, TESTNET11_CONSTANTS};
+
+    use crate::{to_program, to_puzzle};
+
+    use super::*;
+
+    #[test]
+    fn test_singleton_lineage() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle) = to_puzzle(1)?;
+        let solution = to_program([CreateCoin::new(puzzle_hash, 1, Vec::new())])?;
+
+        let mut builder = SingletonLineageBuilder::new(1);
+        for _ in 0..10 {
+            builder = builder.spend(puzzle.clone(), solution.clone());
+        }
+        let lineage = builder.build(&mut sim)?;
+
+        assert_eq!(lineage.coin_spends.len(), 11);
+        assert_eq!(lineage.inner_puzzle_hash, puzzle_hash);
+        assert_eq!(sim.height(), 10);
+
+        for coin_spend in &lineage.coin_spends {
+            let coin_id = coin_spend.coin.coin_id();
+            assert!(sim.coin_state(coin_id).unwrap().spent_height.is_some());
+            assert_eq!(
+                sim.puzzle_reveal(coin_id),
+                Some(coin_spend.puzzle_reveal.clone())
+            );
+            assert_eq!(sim.children(coin_id).len(), 1);
+        }
+
+        // The fabricated lineage proof is accepted when the singleton is spent for real.
+        let mut allocator = Allocator::new();
+        let singleton_mod = node_from_bytes(&mut allocator, &SINGLETON_TOP_LAYER_PUZZLE)?;
+        let inner_puzzle = puzzle.to_clvm(&mut allocator)?;
+        let puzzle_reveal = CurriedProgram {
+            program: singleton_mod,
+            args: SingletonArgs::new(lineage.launcher_id, inner_puzzle),
+        }
+        .to_clvm(&mut allocator)?;
+        let solution = SingletonSolution {
+            lineage_proof: lineage.proof,
+            amount: lineage.coin.amount,
+            inner_solution: solution,
+        }
+        .to_clvm(&mut allocator)?;
+
+        let coin_spend = CoinSpend::new(
+            lineage.coin,
+            Program::from_clvm(&allocator, puzzle_reveal)?,
+            Program::from_clvm(&allocator, solution)?,
+        );
+        sim.new_transaction(
+            SpendBundle::new(vec![coin_spend], Signature::default()),
+            &TESTNET11_CONSTANTS,
+        )?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_singleton_lineage_wrong_puzzle() -> anyhow::Result<()> {
+        let mut sim = Simulator::new();
+        let (puzzle_hash, puzzle) = to_puzzle(1)?;
+        let (_, other_puzzle) = to_puzzle(2)?;
+        let solution = to_program([CreateCoin::new(puzzle_hash, 1, Vec::new())])?;
+
+        assert!(SingletonLineageBuilder::new(1).build(&mut sim).is_err());
+        assert!(SingletonLineageBuilder::new(2)
+            .spend(puzzle.clone(), solution.clone())
+            .build(&mut sim)
+            .is_err());
+        assert!(SingletonLineageBuilder::new(1)
+            .spend(puzzle.clone(), solution.clone())
+            .spend(other_puzzle, solution)
+            .build(&mut sim)
+            .is_err());
+
+        // The valid first spend isn't inserted when a later one fails.
+        assert_eq!(sim.height(), 0);
+        assert!(sim
+            .lookup_puzzle_hashes(
+                [SINGLETON_LAUNCHER_PUZZLE_HASH.into()]
+                    .into_iter()
+                    .collect(),
+                false
+            )
+            .is_empty());
+
+        Ok(())
+    }
+}
@@ -4,6 +4,8 @@ use clvmr::sha2::Sha256;
 use hex_literal::hex;
 use once_cell::sync::Lazy;
 
+use crate::opcodes::{self, ConditionOpcode};
+
 const MAINNET_GENESIS_CHALLENGE: Bytes32 = Bytes32::new(hex!(
     "ccd5bb71183532bff220ba46c268991a3ff07eb358e8255a65c30a2dce0e5fbb"
 ));
@@ -35,12 +37,12 @@ pub fn default_constants(genesis_challenge: Bytes32, agg_sig_me: Bytes32) -> Con
         number_of_timestamps: 11,
         genesis_challenge,
         agg_sig_me_additional_data: agg_sig_me,
-        agg_sig_parent_additional_data: hash(agg_sig_me, 43),
-        agg_sig_puzzle_additional_data: hash(agg_sig_me, 44),
-        agg_sig_amount_additional_data: hash(agg_sig_me, 45),
-        agg_sig_puzzle_amount_additional_data: hash(agg_sig_me, 46),
-        agg_sig_parent_amount_additional_data: hash(agg_sig_me, 47),
-        agg_sig_parent_puzzle_additional_data: hash(agg_sig_me, 48),
+        agg_sig_parent_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_PARENT),
+        agg_sig_puzzle_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_PUZZLE),
+        agg_sig_amount_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_AMOUNT),
+        agg_sig_puzzle_amount_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_PUZZLE_AMOUNT),
+        agg_sig_parent_amount_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_PARENT_AMOUNT),
+        agg_sig_parent_puzzle_additional_data: hash(agg_sig_me, opcodes::AGG_SIG_PARENT_PUZZLE),
         genesis_pre_farm_pool_puzzle_hash: Bytes32::new(hex!(
             "d23da14695a188ae5708dd152263c4db883eb27edeb936178d4d988b8f3ce5fc"
         )),
@@ -90,9 +92,17 @@ pub static TESTNET11_CONSTANTS: Lazy<ConsensusConstants> = Lazy::new(|| Consensu
     ..default_constants(TESTNET11_GENESIS_CHALLENGE, TESTNET11_GENESIS_CHALLENGE)
 });
 
-fn hash(agg_sig_data: Bytes32, byte: u8) -> Bytes32 {
+/// The maximum cost of a single spend bundle that the mempool will accept.
+///
+/// The mempool manager in chia-blockchain limits each transaction to half of the
+/// maximum block cost (`max_tx_clvm_cost = MAX_BLOCK_COST_CLVM // 2`).
+pub fn max_spend_bundle_cost(constants: &ConsensusConstants) -> u64 {
+    constants.max_block_cost_clvm / 2
+}
+
+fn hash(agg_sig_data: Bytes32, opcode: ConditionOpcode) -> Bytes32 {
     let mut hasher = Sha256::new();
     hasher.update(agg_sig_data);
-    hasher.update([byte]);
+    hasher.update([u8::try_from(opcode).expect("AGG_SIG opcodes fit in a single byte")]);
     hasher.finalize().into()
 }
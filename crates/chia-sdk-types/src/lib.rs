@@ -1,9 +1,13 @@
 mod condition;
 mod conditions;
 mod constants;
+mod merkle_tree;
 mod run_puzzle;
 
+pub mod opcodes;
+
 pub use condition::*;
 pub use conditions::*;
 pub use constants::*;
+pub use merkle_tree::*;
 pub use run_puzzle::*;
@@ -0,0 +1,6 @@
+//! The opcodes of the conditions understood by consensus, re-exported from `chia-consensus`.
+//!
+//! The opcodes of the `AGG_SIG` conditions, other than `AGG_SIG_ME` and `AGG_SIG_UNSAFE`, are also used as tags.
+//! The domain string appended to their messages is the hash of the `AGG_SIG_ME` additional data followed by the tag.
+
+pub use chia_consensus::gen::opcodes::*;
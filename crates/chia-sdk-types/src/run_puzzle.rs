@@ -1,19 +1,144 @@
+use chia_consensus::consensus_constants::ConsensusConstants;
 use clvmr::{
     reduction::{EvalErr, Reduction},
-    Allocator, NodePtr,
+    Allocator, NodePtr, MEMPOOL_MODE,
 };
 
+use crate::MAINNET_CONSTANTS;
+
+/// The cost limit and dialect flags used when running a puzzle.
+///
+/// The BLS and softfork operators are enabled unconditionally as of the 2.0 hard fork, so no
+/// dialect flags are needed to match consensus at the current height. The flags can be used to
+/// match the stricter mempool rules instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunPuzzleOptions {
+    /// The maximum cost that the puzzle can use before it fails.
+    pub max_cost: u64,
+    /// The [`ChiaDialect`](clvmr::ChiaDialect) flags, such as [`MEMPOOL_MODE`].
+    pub flags: u32,
+}
+
+impl Default for RunPuzzleOptions {
+    fn default() -> Self {
+        Self::from_constants(&MAINNET_CONSTANTS)
+    }
+}
+
+impl RunPuzzleOptions {
+    /// Uses the maximum block cost from the given consensus constants, with consensus flags.
+    pub fn from_constants(constants: &ConsensusConstants) -> Self {
+        Self {
+            max_cost: constants.max_block_cost_clvm,
+            flags: 0,
+        }
+    }
+
+    /// Uses the mempool rules, which disallow unknown operators and limit the heap size.
+    pub fn mempool() -> Self {
+        Self {
+            flags: MEMPOOL_MODE,
+            ..Self::default()
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_cost(mut self, max_cost: u64) -> Self {
+        self.max_cost = max_cost;
+        self
+    }
+
+    #[must_use]
+    pub fn with_flags(mut self, flags: u32) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+/// Runs a puzzle with the consensus rules, up to the maximum cost of a mainnet block.
 pub fn run_puzzle(
     allocator: &mut Allocator,
     puzzle: NodePtr,
     solution: NodePtr,
+) -> Result<NodePtr, EvalErr> {
+    run_puzzle_with_options(allocator, puzzle, solution, RunPuzzleOptions::default())
+}
+
+/// Runs a puzzle with the given cost limit and dialect flags.
+pub fn run_puzzle_with_options(
+    allocator: &mut Allocator,
+    puzzle: NodePtr,
+    solution: NodePtr,
+    options: RunPuzzleOptions,
 ) -> Result<NodePtr, EvalErr> {
     let Reduction(_cost, output) = clvmr::run_program(
         allocator,
-        &clvmr::ChiaDialect::new(0),
+        &clvmr::ChiaDialect::new(options.flags),
         puzzle,
         solution,
-        11_000_000_000,
+        options.max_cost,
     )?;
     Ok(output)
 }
+
+#[cfg(test)]
+mod tests {
+    use clvm_traits::{clvm_quote, ToClvm};
+
+    use crate::TESTNET11_CONSTANTS;
+
+    use super::*;
+
+    #[test]
+    fn test_run_puzzle_options() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        // (sha256 1), which just hashes the solution.
+        let puzzle = (11, (1, ())).to_clvm(&mut allocator)?;
+        let solution = "hello".to_clvm(&mut allocator)?;
+
+        run_puzzle(&mut allocator, puzzle, solution)?;
+        run_puzzle_with_options(
+            &mut allocator,
+            puzzle,
+            solution,
+            RunPuzzleOptions::from_constants(&TESTNET11_CONSTANTS),
+        )?;
+
+        assert!(run_puzzle_with_options(
+            &mut allocator,
+            puzzle,
+            solution,
+            RunPuzzleOptions::default().with_max_cost(10),
+        )
+        .is_err());
+
+        // An unknown operator is allowed by consensus, but not by the mempool.
+        let unknown = (0xff_u32, ()).to_clvm(&mut allocator)?;
+        run_puzzle(&mut allocator, unknown, solution)?;
+        assert!(run_puzzle_with_options(
+            &mut allocator,
+            unknown,
+            solution,
+            RunPuzzleOptions::mempool(),
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_puzzle_bls_operators() -> anyhow::Result<()> {
+        let mut allocator = Allocator::new();
+
+        // (g1_map (q . "hello")) only works if the BLS operators are enabled outside the softfork guard.
+        let puzzle = (56, (clvm_quote!("hello"), ())).to_clvm(&mut allocator)?;
+
+        for options in [RunPuzzleOptions::default(), RunPuzzleOptions::mempool()] {
+            let output = run_puzzle_with_options(&mut allocator, puzzle, NodePtr::NIL, options)?;
+            assert_eq!(allocator.atom(output).as_ref().len(), 48);
+        }
+
+        Ok(())
+    }
+}
@@ -1,7 +1,7 @@
 use std::cmp::Reverse;
 
-use chia_protocol::Coin;
-use indexmap::IndexSet;
+use chia_protocol::{Bytes32, Coin};
+use indexmap::{IndexMap, IndexSet};
 use rand::{Rng, SeedableRng};
 use rand_chacha::ChaCha8Rng;
 use thiserror::Error;
@@ -22,12 +22,15 @@ pub enum CoinSelectionError {
     ExceededMaxCoins,
 }
 
+/// The maximum number of coins that can be selected at once.
+const MAX_COINS: usize = 500;
+
 /// Uses the knapsack algorithm to select coins.
 pub fn select_coins(
     mut spendable_coins: Vec<Coin>,
     amount: u128,
 ) -> Result<Vec<Coin>, CoinSelectionError> {
-    let max_coins = 500;
+    let max_coins = MAX_COINS;
 
     // You cannot spend no coins.
     if spendable_coins.is_empty() {
@@ -106,6 +109,92 @@ pub fn select_coins(
     Err(CoinSelectionError::ExceededMaxCoins)
 }
 
+/// Selects coins without linking different puzzle hashes together, where possible.
+/// Spend history isn't known here, so puzzle hashes that have already been linked on chain are treated like any other.
+///
+/// If the coins of a single puzzle hash can cover the amount, only they are selected. Otherwise, the amount is
+/// split across as few puzzle hashes as possible, and each selection should be spent in a separate spend bundle
+/// so that they aren't linked on chain. Puzzle hashes with the lowest sufficient balance are preferred.
+///
+/// If a puzzle hash would need more than the maximum number of coins to cover its share, its largest coins
+/// are selected and the rest is covered by the next puzzle hash.
+pub fn select_unlinked_coins(
+    spendable_coins: Vec<Coin>,
+    amount: u128,
+) -> Result<Vec<Vec<Coin>>, CoinSelectionError> {
+    if spendable_coins.is_empty() {
+        return Err(CoinSelectionError::NoSpendableCoins);
+    }
+
+    let mut groups: IndexMap<Bytes32, Vec<Coin>> = IndexMap::new();
+
+    for coin in spendable_coins {
+        groups.entry(coin.puzzle_hash).or_default().push(coin);
+    }
+
+    let mut groups: Vec<(u128, Vec<Coin>)> = groups
+        .into_values()
+        .map(|coins| {
+            let balance = coins.iter().map(|coin| coin.amount as u128).sum();
+            (balance, coins)
+        })
+        .collect();
+
+    let spendable_amount = groups.iter().map(|(balance, _)| balance).sum::<u128>();
+
+    if spendable_amount < amount {
+        return Err(CoinSelectionError::InsufficientBalance(spendable_amount));
+    }
+
+    // Sorts by balance, ascending.
+    groups.sort_by_key(|(balance, _)| *balance);
+
+    // Prefer a single puzzle hash, so that nothing is linked.
+    for (balance, coins) in &groups {
+        if *balance < amount {
+            continue;
+        }
+
+        match select_coins(coins.clone(), amount) {
+            Ok(selected) => return Ok(vec![selected]),
+            Err(CoinSelectionError::ExceededMaxCoins) => continue,
+            Err(error) => return Err(error),
+        }
+    }
+
+    // Otherwise, use the largest balances first so that as few puzzle hashes as possible are involved.
+    let mut selections = Vec::new();
+    let mut remaining = amount;
+
+    for (balance, mut coins) in groups.into_iter().rev() {
+        if remaining == 0 {
+            break;
+        }
+
+        let target = remaining.min(balance);
+
+        match select_coins(coins.clone(), target) {
+            Ok(selected) => {
+                selections.push(selected);
+                remaining -= target;
+            }
+            Err(CoinSelectionError::ExceededMaxCoins) => {
+                coins.sort_unstable_by_key(|coin| Reverse(coin.amount));
+                coins.truncate(MAX_COINS);
+                remaining -= coins.iter().map(|coin| coin.amount as u128).sum::<u128>();
+                selections.push(coins);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    if remaining > 0 {
+        return Err(CoinSelectionError::ExceededMaxCoins);
+    }
+
+    Ok(selections)
+}
+
 fn sum_largest_coins(coins: &[Coin], amount: u128) -> IndexSet<Coin> {
     let mut selected_coins = IndexSet::new();
     let mut selected_sum = 0;
@@ -192,8 +281,6 @@ pub fn knapsack_coin_algorithm(
 
 #[cfg(test)]
 mod tests {
-    use chia_protocol::Bytes32;
-
     use super::*;
 
     macro_rules! coin_list {
@@ -228,6 +315,72 @@ mod tests {
         );
     }
 
+    fn coin_with_puzzle_hash(puzzle_hash: u8, amount: u64) -> Coin {
+        Coin::new(
+            Bytes32::from([0; 32]),
+            Bytes32::from([puzzle_hash; 32]),
+            amount,
+        )
+    }
+
+    #[test]
+    fn test_select_unlinked_coins() {
+        let coins = vec![
+            coin_with_puzzle_hash(1, 100),
+            coin_with_puzzle_hash(1, 200),
+            coin_with_puzzle_hash(2, 500),
+            coin_with_puzzle_hash(3, 240),
+            coin_with_puzzle_hash(3, 260),
+        ];
+
+        // The smallest balance that covers the amount is used on its own.
+        let selected = select_unlinked_coins(coins.clone(), 300).unwrap();
+        assert_eq!(
+            selected,
+            [[coin_with_puzzle_hash(1, 200), coin_with_puzzle_hash(1, 100)]]
+        );
+
+        let selected = select_unlinked_coins(coins.clone(), 450).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert!(selected[0]
+            .iter()
+            .all(|coin| coin.puzzle_hash == Bytes32::from([2; 32])));
+
+        // No single puzzle hash has enough, so the amount is split by puzzle hash.
+        let selected = select_unlinked_coins(coins.clone(), 900).unwrap();
+        assert_eq!(selected.len(), 2);
+        for selection in &selected {
+            assert!(selection
+                .iter()
+                .all(|coin| coin.puzzle_hash == selection[0].puzzle_hash));
+        }
+        let total: u64 = selected.iter().flatten().map(|coin| coin.amount).sum();
+        assert!(total >= 900);
+
+        assert_eq!(
+            select_unlinked_coins(coins, 2000),
+            Err(CoinSelectionError::InsufficientBalance(1300))
+        );
+        assert_eq!(
+            select_unlinked_coins(Vec::new(), 100),
+            Err(CoinSelectionError::NoSpendableCoins)
+        );
+    }
+
+    #[test]
+    fn test_select_unlinked_coins_max_coins() {
+        let mut coins: Vec<Coin> = (0..700)
+            .map(|index| Coin::new(Bytes32::new([0; 32]), Bytes32::new([1; 32]), 2000 + index))
+            .collect();
+        coins.push(coin_with_puzzle_hash(2, 1_000_000));
+
+        // The first puzzle hash only contributes its largest coins, and the second covers the rest.
+        let selected = select_unlinked_coins(coins, 2_000_000).unwrap();
+        assert_eq!(selected.len(), 2);
+        assert_eq!(selected[0].len(), MAX_COINS);
+        assert_eq!(selected[1], [coin_with_puzzle_hash(2, 1_000_000)]);
+    }
+
     #[test]
     fn test_no_coins() {
         // There is no amount to select from.
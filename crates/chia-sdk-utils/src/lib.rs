@@ -3,3 +3,9 @@ mod coin_selection;
 
 pub use address::*;
 pub use coin_selection::*;
+
+#[cfg(feature = "serde")]
+mod spend_bundle_json;
+
+#[cfg(feature = "serde")]
+pub use spend_bundle_json::*;
@@ -0,0 +1,211 @@
+use chia_bls::Signature;
+use chia_protocol::{Bytes32, Coin, CoinSpend, Program, SpendBundle};
+use hex::FromHexError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::strip_prefix;
+
+/// Errors you can get while converting JSON into a spend bundle.
+#[derive(Error, Debug, Clone, Copy, PartialEq)]
+pub enum SpendBundleJsonError {
+    /// A hex field could not be decoded.
+    #[error("error when decoding hex: {0}")]
+    Hex(#[from] FromHexError),
+
+    /// A hex field did not have the expected number of bytes.
+    #[error("wrong length, expected {expected} bytes but found {found}")]
+    WrongLength { expected: usize, found: usize },
+
+    /// The aggregated signature is not a valid G2 element.
+    #[error("invalid aggregated signature")]
+    InvalidSignature,
+}
+
+/// A coin in the JSON format used by the chia-blockchain RPCs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CoinJson {
+    pub parent_coin_info: String,
+    pub puzzle_hash: String,
+    pub amount: u64,
+}
+
+/// A coin spend in the JSON format used by the chia-blockchain RPCs.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CoinSpendJson {
+    pub coin: CoinJson,
+    pub puzzle_reveal: String,
+    pub solution: String,
+}
+
+/// A spend bundle in the JSON format used by the chia-blockchain RPCs,
+/// such as `push_tx`. Every byte field is hex encoded with a `0x` prefix.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpendBundleJson {
+    pub coin_spends: Vec<CoinSpendJson>,
+    pub aggregated_signature: String,
+}
+
+impl From<&Coin> for CoinJson {
+    fn from(coin: &Coin) -> Self {
+        Self {
+            parent_coin_info: encode_hex(&coin.parent_coin_info),
+            puzzle_hash: encode_hex(&coin.puzzle_hash),
+            amount: coin.amount,
+        }
+    }
+}
+
+impl TryFrom<&CoinJson> for Coin {
+    type Error = SpendBundleJsonError;
+
+    fn try_from(coin: &CoinJson) -> Result<Self, Self::Error> {
+        Ok(Coin::new(
+            decode_bytes32(&coin.parent_coin_info)?,
+            decode_bytes32(&coin.puzzle_hash)?,
+            coin.amount,
+        ))
+    }
+}
+
+impl From<&CoinSpend> for CoinSpendJson {
+    fn from(coin_spend: &CoinSpend) -> Self {
+        Self {
+            coin: CoinJson::from(&coin_spend.coin),
+            puzzle_reveal: encode_hex(coin_spend.puzzle_reveal.as_ref()),
+            solution: encode_hex(coin_spend.solution.as_ref()),
+        }
+    }
+}
+
+impl TryFrom<&CoinSpendJson> for CoinSpend {
+    type Error = SpendBundleJsonError;
+
+    fn try_from(coin_spend: &CoinSpendJson) -> Result<Self, Self::Error> {
+        Ok(CoinSpend::new(
+            Coin::try_from(&coin_spend.coin)?,
+            Program::from(decode_hex(&coin_spend.puzzle_reveal)?),
+            Program::from(decode_hex(&coin_spend.solution)?),
+        ))
+    }
+}
+
+impl From<&SpendBundle> for SpendBundleJson {
+    fn from(spend_bundle: &SpendBundle) -> Self {
+        Self {
+            coin_spends: spend_bundle
+                .coin_spends
+                .iter()
+                .map(CoinSpendJson::from)
+                .collect(),
+            aggregated_signature: encode_hex(&spend_bundle.aggregated_signature.to_bytes()),
+        }
+    }
+}
+
+impl TryFrom<&SpendBundleJson> for SpendBundle {
+    type Error = SpendBundleJsonError;
+
+    fn try_from(spend_bundle: &SpendBundleJson) -> Result<Self, Self::Error> {
+        let coin_spends = spend_bundle
+            .coin_spends
+            .iter()
+            .map(CoinSpend::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let signature = Signature::from_bytes(&decode_array(&spend_bundle.aggregated_signature)?)
+            .map_err(|_| SpendBundleJsonError::InvalidSignature)?;
+
+        Ok(SpendBundle::new(coin_spends, signature))
+    }
+}
+
+fn encode_hex(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+fn decode_hex(value: &str) -> Result<Vec<u8>, SpendBundleJsonError> {
+    Ok(hex::decode(strip_prefix(value))?)
+}
+
+fn decode_bytes32(value: &str) -> Result<Bytes32, SpendBundleJsonError> {
+    decode_array(value).map(Bytes32::new)
+}
+
+fn decode_array<const N: usize>(value: &str) -> Result<[u8; N], SpendBundleJsonError> {
+    let bytes = decode_hex(value)?;
+    let found = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| SpendBundleJsonError::WrongLength { expected: N, found })
+}
+
+#[cfg(test)]
+mod tests {
+    use chia_bls::{sign, SecretKey};
+
+    use super::*;
+
+    fn spend_bundle() -> SpendBundle {
+        let coin = Coin::new(Bytes32::new([1; 32]), Bytes32::new([2; 32]), 1000);
+        let coin_spend = CoinSpend::new(
+            coin,
+            Program::from(vec![0x01]),
+            Program::from(vec![0xff, 0x80, 0x80]),
+        );
+        let secret_key = SecretKey::from_seed(&[3; 32]);
+        SpendBundle::new(vec![coin_spend], sign(&secret_key, b"message"))
+    }
+
+    #[test]
+    fn test_spend_bundle_json_roundtrip() -> anyhow::Result<()> {
+        let spend_bundle = spend_bundle();
+        let json = SpendBundleJson::from(&spend_bundle);
+
+        let coin_spend = &json.coin_spends[0];
+        assert_eq!(
+            coin_spend.coin.parent_coin_info,
+            format!("0x{}", hex::encode([1; 32]))
+        );
+        assert_eq!(coin_spend.coin.amount, 1000);
+        assert_eq!(coin_spend.puzzle_reveal, "0x01");
+        assert_eq!(coin_spend.solution, "0xff8080");
+        assert_eq!(json.aggregated_signature.len(), 2 + 96 * 2);
+
+        assert_eq!(SpendBundle::try_from(&json)?, spend_bundle);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_spend_bundle_json_without_prefix() -> anyhow::Result<()> {
+        let spend_bundle = spend_bundle();
+        let mut json = SpendBundleJson::from(&spend_bundle);
+        json.aggregated_signature = strip_prefix(&json.aggregated_signature).to_string();
+        json.coin_spends[0].puzzle_reveal = "01".to_string();
+
+        assert_eq!(SpendBundle::try_from(&json)?, spend_bundle);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_spend_bundle_json() {
+        let mut json = SpendBundleJson::from(&spend_bundle());
+        json.coin_spends[0].coin.puzzle_hash = "0x1234".to_string();
+        assert_eq!(
+            SpendBundle::try_from(&json),
+            Err(SpendBundleJsonError::WrongLength {
+                expected: 32,
+                found: 2
+            })
+        );
+
+        let mut json = SpendBundleJson::from(&spend_bundle());
+        json.aggregated_signature = format!("0x{}", hex::encode([0xff; 96]));
+        assert_eq!(
+            SpendBundle::try_from(&json),
+            Err(SpendBundleJsonError::InvalidSignature)
+        );
+    }
+}